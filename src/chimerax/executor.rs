@@ -6,7 +6,7 @@ use std::process::{Command, Output, Stdio};
 
 /// Validate that a path is safe for use in ChimeraX commands.
 /// Rejects paths containing characters that could be interpreted specially.
-fn validate_path_for_command(path: &Path) -> Result<()> {
+pub(crate) fn validate_path_for_command(path: &Path) -> Result<()> {
     let path_str = path.to_string_lossy();
 
     // Characters that could cause issues in ChimeraX command strings
@@ -60,26 +60,7 @@ impl ChimeraXExecutor {
     pub fn run_command(&self, cmd: &str) -> Result<Output> {
         self.log_execution(&format!("ChimeraX --nogui --exit --cmd '{}'", cmd));
 
-        let output = Command::new(&self.executable)
-            .args(["--nogui", "--exit", "--cmd", cmd])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()?;
-
-        self.log_output(&output);
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            return Err(EchidnaError::ChimeraXCommandFailed(format!(
-                "exit code: {:?}\nstdout: {}\nstderr: {}",
-                output.status.code(),
-                stdout,
-                stderr
-            )));
-        }
-
-        Ok(output)
+        self.output(Command::new(&self.executable).args(["--nogui", "--exit", "--cmd", cmd]))
     }
 
     /// Execute a ChimeraX script in nogui mode.
@@ -90,26 +71,12 @@ impl ChimeraXExecutor {
             script_str
         ));
 
-        let output = Command::new(&self.executable)
-            .args(["--nogui", "--exit", "--script", &script_str])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()?;
-
-        self.log_output(&output);
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            return Err(EchidnaError::ChimeraXCommandFailed(format!(
-                "exit code: {:?}\nstdout: {}\nstderr: {}",
-                output.status.code(),
-                stdout,
-                stderr
-            )));
-        }
-
-        Ok(output)
+        self.output(Command::new(&self.executable).args([
+            "--nogui",
+            "--exit",
+            "--script",
+            &script_str,
+        ]))
     }
 
     /// Launch ChimeraX with GUI (optionally with a script).
@@ -131,6 +98,29 @@ impl ChimeraXExecutor {
         Ok(())
     }
 
+    /// Launch ChimeraX with the given options.
+    ///
+    /// In GUI mode the process is spawned and left running. In nogui mode
+    /// ChimeraX exits after opening all files and scripts, and the captured
+    /// output is returned.
+    pub fn launch_with(&self, options: &LaunchOptions) -> Result<Option<Output>> {
+        let args = options.to_args();
+        self.log_execution(&format!("ChimeraX {}", args.join(" ")));
+
+        let mut cmd = Command::new(&self.executable);
+        cmd.args(&args);
+        for (key, value) in &options.env {
+            cmd.env(key, value);
+        }
+
+        if !options.nogui && !options.offscreen {
+            cmd.spawn()?;
+            return Ok(None);
+        }
+
+        self.output(&mut cmd).map(Some)
+    }
+
     /// Execute `devel build` command.
     pub fn devel_build(&self, path: &Path) -> Result<Output> {
         validate_path_for_command(path)?;
@@ -158,6 +148,17 @@ impl ChimeraXExecutor {
         self.run_command(&cmd)
     }
 
+    /// Run `ChimeraX -m pip install` with the given arguments.
+    pub fn pip_install(&self, args: &[String]) -> Result<Output> {
+        self.log_execution(&format!("ChimeraX -m pip install {}", args.join(" ")));
+
+        self.output(
+            Command::new(&self.executable)
+                .args(["-m", "pip", "install"])
+                .args(args),
+        )
+    }
+
     /// Get Python environment information from ChimeraX.
     pub fn get_python_info(&self) -> Result<PythonInfo> {
         let python_code = r#"
@@ -207,6 +208,26 @@ print("ECHIDNA_JSON_END")
         Ok(info)
     }
 
+    /// Run a command with captured output; a non-zero exit is an error.
+    fn output(&self, cmd: &mut Command) -> Result<Output> {
+        let output = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).output()?;
+
+        self.log_output(&output);
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            return Err(EchidnaError::ChimeraXCommandFailed(format!(
+                "exit code: {:?}\nstdout: {}\nstderr: {}",
+                output.status.code(),
+                stdout,
+                stderr
+            )));
+        }
+
+        Ok(output)
+    }
+
     fn log_execution(&self, msg: &str) {
         // Level 1+: show commands being executed
         if self.verbosity >= 1 {
@@ -229,6 +250,48 @@ print("ECHIDNA_JSON_END")
     }
 }
 
+/// Options for launching a ChimeraX process with [`ChimeraXExecutor::launch_with`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LaunchOptions {
+    /// Run without GUI and exit when done
+    pub nogui: bool,
+    /// Render offscreen with OSMesa (implies nogui)
+    pub offscreen: bool,
+    /// Data files to open, in order
+    pub files: Vec<PathBuf>,
+    /// Scripts (.cxc or .py) to run after the data files, in order
+    pub scripts: Vec<PathBuf>,
+    /// Extra command-line arguments placed before the files
+    pub extra_args: Vec<String>,
+    /// Environment variables for the ChimeraX process
+    pub env: Vec<(String, String)>,
+}
+
+impl LaunchOptions {
+    /// Build the ChimeraX command-line arguments.
+    ///
+    /// Files and scripts are passed positionally so that ChimeraX opens them
+    /// in order: data first, then scripts operating on that data.
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.offscreen {
+            args.push("--offscreen".to_string());
+        }
+        if self.nogui || self.offscreen {
+            args.push("--nogui".to_string());
+            args.push("--exit".to_string());
+        }
+        args.extend(self.extra_args.iter().cloned());
+        args.extend(
+            self.files
+                .iter()
+                .chain(self.scripts.iter())
+                .map(|p| p.to_string_lossy().to_string()),
+        );
+        args
+    }
+}
+
 /// Python environment information from ChimeraX.
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct PythonInfo {
@@ -290,6 +353,43 @@ mod tests {
         assert!(validate_path_for_command(path).is_err());
     }
 
+    #[test]
+    fn test_launch_options_args_gui() {
+        let options = LaunchOptions {
+            files: vec![PathBuf::from("map.mrc")],
+            scripts: vec![PathBuf::from("show.cxc")],
+            extra_args: vec!["--nostatus".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(options.to_args(), vec!["--nostatus", "map.mrc", "show.cxc"]);
+    }
+
+    #[test]
+    fn test_launch_options_args_nogui() {
+        let options = LaunchOptions {
+            nogui: true,
+            scripts: vec![PathBuf::from("a.cxc"), PathBuf::from("b.py")],
+            ..Default::default()
+        };
+        assert_eq!(
+            options.to_args(),
+            vec!["--nogui", "--exit", "a.cxc", "b.py"]
+        );
+    }
+
+    #[test]
+    fn test_launch_options_args_offscreen() {
+        let options = LaunchOptions {
+            offscreen: true,
+            scripts: vec![PathBuf::from("render.cxc")],
+            ..Default::default()
+        };
+        assert_eq!(
+            options.to_args(),
+            vec!["--offscreen", "--nogui", "--exit", "render.cxc"]
+        );
+    }
+
     #[test]
     fn test_executor_new() {
         let executor = ChimeraXExecutor::new(PathBuf::from("/usr/bin/chimerax"), 0);
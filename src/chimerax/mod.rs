@@ -4,4 +4,5 @@ mod detect;
 mod executor;
 
 pub use detect::find_chimerax;
-pub use executor::{ChimeraXExecutor, PythonInfo, Verbosity};
+pub(crate) use executor::validate_path_for_command;
+pub use executor::{ChimeraXExecutor, LaunchOptions, PythonInfo, Verbosity};
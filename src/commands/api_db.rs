@@ -0,0 +1,231 @@
+//! Embedded database of deprecated and removed ChimeraX APIs for `echidna lint`.
+//!
+//! Each entry records the ChimeraX release that deprecated and (optionally)
+//! removed an API, and its replacement. Bump [`DATABASE_VERSION`] when entries
+//! are added or changed.
+
+use crate::pep440::Version;
+use std::cmp::Ordering;
+
+/// Revision of the database.
+pub const DATABASE_VERSION: u32 = 1;
+
+/// Newest ChimeraX release covered by the database (the default lint target).
+pub const LATEST_CHIMERAX: &str = "1.9";
+
+/// What an entry refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiKind {
+    /// A module; any import of it or its submodules matches
+    Module,
+    /// A function or class, matched on import and attribute access
+    Symbol,
+    /// A `BundleAPI` method
+    Method,
+    /// A `BundleAPI` method with the given number of parameters
+    /// (excluding `self`/`cls`)
+    Signature { params: usize },
+}
+
+/// A deprecated or removed API.
+#[derive(Debug, Clone)]
+pub struct ApiChange {
+    pub kind: ApiKind,
+    /// Dotted name (for signatures, the `BundleAPI` method name)
+    pub name: &'static str,
+    /// ChimeraX release that deprecated the API
+    pub deprecated_in: &'static str,
+    /// ChimeraX release that removed the API
+    pub removed_in: Option<&'static str>,
+    /// What to use instead
+    pub replacement: &'static str,
+    /// ChimeraX release that introduced the replacement
+    pub replacement_since: Option<&'static str>,
+}
+
+const fn module(
+    name: &'static str,
+    deprecated_in: &'static str,
+    removed_in: Option<&'static str>,
+    replacement: &'static str,
+    replacement_since: Option<&'static str>,
+) -> ApiChange {
+    ApiChange {
+        kind: ApiKind::Module,
+        name,
+        deprecated_in,
+        removed_in,
+        replacement,
+        replacement_since,
+    }
+}
+
+/// All known API changes.
+pub const API_CHANGES: &[ApiChange] = &[
+    // Modules split out of chimerax.core into their own bundles
+    module(
+        "chimerax.core.atomic",
+        "0.8",
+        Some("0.8"),
+        "chimerax.atomic",
+        Some("0.8"),
+    ),
+    module(
+        "chimerax.core.structure",
+        "0.8",
+        Some("0.8"),
+        "chimerax.atomic",
+        Some("0.8"),
+    ),
+    module(
+        "chimerax.core.graphics",
+        "0.8",
+        Some("0.8"),
+        "chimerax.graphics",
+        Some("0.8"),
+    ),
+    module(
+        "chimerax.core.map",
+        "0.8",
+        Some("0.8"),
+        "chimerax.map",
+        Some("0.8"),
+    ),
+    module(
+        "chimerax.core.ui",
+        "0.8",
+        Some("0.8"),
+        "chimerax.ui",
+        Some("0.8"),
+    ),
+    module(
+        "chimerax.core.geometry",
+        "0.9",
+        Some("1.0"),
+        "chimerax.geometry",
+        Some("0.9"),
+    ),
+    // File formats moved to the open/save provider managers
+    module(
+        "chimerax.core.io",
+        "1.1",
+        Some("1.2"),
+        "[chimerax.data-format] providers with chimerax.open_command / chimerax.save_command",
+        Some("1.1"),
+    ),
+    ApiChange {
+        kind: ApiKind::Symbol,
+        name: "chimerax.core.io.open_data",
+        deprecated_in: "1.1",
+        removed_in: Some("1.2"),
+        replacement: "chimerax.core.commands.run(session, 'open ...')",
+        replacement_since: None,
+    },
+    ApiChange {
+        kind: ApiKind::Symbol,
+        name: "chimerax.core.io.register_format",
+        deprecated_in: "1.1",
+        removed_in: Some("1.2"),
+        replacement: "a [chimerax.data-format] table in pyproject.toml",
+        replacement_since: Some("1.1"),
+    },
+    // Qt 6 in ChimeraX 1.4
+    module(
+        "PyQt5",
+        "1.4",
+        Some("1.4"),
+        "the Qt compatibility package (e.g. `from Qt.QtWidgets import QWidget`)",
+        None,
+    ),
+    // BundleAPI methods with api_version 0 signatures, and legacy methods
+    ApiChange {
+        kind: ApiKind::Signature { params: 2 },
+        name: "start_tool",
+        deprecated_in: "0.8",
+        removed_in: None,
+        replacement: "api_version = 1 and start_tool(session, bi, ti)",
+        replacement_since: Some("0.8"),
+    },
+    ApiChange {
+        kind: ApiKind::Signature { params: 2 },
+        name: "register_command",
+        deprecated_in: "0.8",
+        removed_in: None,
+        replacement: "api_version = 1 and register_command(bi, ci, logger)",
+        replacement_since: Some("0.8"),
+    },
+    ApiChange {
+        kind: ApiKind::Signature { params: 2 },
+        name: "register_selector",
+        deprecated_in: "0.8",
+        removed_in: None,
+        replacement: "api_version = 1 and register_selector(bi, si, logger)",
+        replacement_since: Some("0.8"),
+    },
+    ApiChange {
+        kind: ApiKind::Method,
+        name: "open_file",
+        deprecated_in: "1.1",
+        removed_in: None,
+        replacement:
+            "run_provider(session, name, mgr) returning an opener for session.open_command",
+        replacement_since: Some("1.1"),
+    },
+    ApiChange {
+        kind: ApiKind::Method,
+        name: "save_file",
+        deprecated_in: "1.1",
+        removed_in: None,
+        replacement: "run_provider(session, name, mgr) returning a saver for session.save_command",
+        replacement_since: Some("1.1"),
+    },
+];
+
+/// Compare ChimeraX versions by PEP 440 ("1.6" == "1.6.0" < "1.10",
+/// "1.8rc1" < "1.8").
+///
+/// Returns `None` if either version is not a valid version.
+pub fn compare_versions(a: &str, b: &str) -> Option<Ordering> {
+    Some(Version::parse(a)?.cmp(&Version::parse(b)?))
+}
+
+/// Whether `version` is at or after `release`.
+pub fn at_least(version: &str, release: &str) -> bool {
+    matches!(
+        compare_versions(version, release),
+        Some(Ordering::Greater | Ordering::Equal)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.6", "1.6.0"), Some(Ordering::Equal));
+        assert_eq!(compare_versions("1.9", "1.10"), Some(Ordering::Less));
+        assert_eq!(compare_versions("0.8", "0.9.1"), Some(Ordering::Less));
+        assert_eq!(compare_versions("1.8rc1", "1.8"), Some(Ordering::Less));
+        assert_eq!(compare_versions("1.x", "1.0"), None);
+        assert!(at_least("1.4", "1.4"));
+        assert!(!at_least("1.3.1", "1.4"));
+    }
+
+    #[test]
+    fn test_database_is_consistent() {
+        for change in API_CHANGES {
+            assert!(
+                at_least(LATEST_CHIMERAX, change.deprecated_in),
+                "{}",
+                change.name
+            );
+            if let Some(removed) = change.removed_in {
+                assert!(at_least(removed, change.deprecated_in), "{}", change.name);
+            }
+            if let Some(since) = change.replacement_since {
+                assert!(Version::parse(since).is_some(), "{}", change.name);
+            }
+        }
+    }
+}
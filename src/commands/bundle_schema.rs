@@ -0,0 +1,648 @@
+//! Schema of the BundleBuilder `[chimerax]` tables in pyproject.toml.
+//!
+//! BundleBuilder ignores keys it does not know, so a typo such as
+//! `descripton` only shows up as missing behaviour inside ChimeraX. The
+//! schema lets `echidna validate` report unknown keys (with suggestions),
+//! wrong value types, missing required keys, and invalid enum values.
+
+use crate::commands::validate::ValidationResult;
+use crate::diagnostics::{rules, Diagnostic};
+
+/// Expected type of a value in a BundleBuilder table.
+#[derive(Debug, Clone, Copy)]
+pub enum ValueType {
+    Str,
+    Bool,
+    /// Integer (a string of digits is accepted too)
+    Integer,
+    StrArray,
+    /// A single string or an array of strings
+    StrOrArray,
+    /// Table with a fixed schema
+    Table(&'static TableSchema),
+    /// Table of named entries sharing a schema (e.g. `[chimerax.command.<name>]`)
+    Entries(&'static TableSchema),
+    /// Table mapping arbitrary keys to arrays of strings
+    StrArrayMap,
+}
+
+impl ValueType {
+    /// Description used in type errors ("must be ...").
+    fn describe(&self) -> &'static str {
+        match self {
+            Self::Str => "a string",
+            Self::Bool => "a boolean",
+            Self::Integer => "an integer",
+            Self::StrArray => "an array of strings",
+            Self::StrOrArray => "a string or an array of strings",
+            Self::Table(_) | Self::Entries(_) => "a table",
+            Self::StrArrayMap => "a table of string arrays",
+        }
+    }
+
+    fn matches(&self, value: &toml::Value) -> bool {
+        match self {
+            Self::Str => value.is_str(),
+            Self::Bool => value.is_bool(),
+            Self::Integer => {
+                value.is_integer()
+                    || value
+                        .as_str()
+                        .map(|s| s.trim().parse::<i64>().is_ok())
+                        .unwrap_or(false)
+            }
+            Self::StrArray => is_string_array(value),
+            Self::StrOrArray => value.is_str() || is_string_array(value),
+            Self::Table(_) | Self::Entries(_) => value.is_table(),
+            Self::StrArrayMap => value
+                .as_table()
+                .map(|t| t.values().all(is_string_array))
+                .unwrap_or(false),
+        }
+    }
+}
+
+fn is_string_array(value: &toml::Value) -> bool {
+    value
+        .as_array()
+        .map(|a| a.iter().all(toml::Value::is_str))
+        .unwrap_or(false)
+}
+
+/// A key of a BundleBuilder table.
+#[derive(Debug, Clone, Copy)]
+pub struct KeySpec {
+    pub name: &'static str,
+    pub ty: ValueType,
+    pub required: bool,
+    /// Allowed values (empty: any value of the right type)
+    pub values: &'static [&'static str],
+}
+
+impl KeySpec {
+    const fn new(name: &'static str, ty: ValueType) -> Self {
+        Self {
+            name,
+            ty,
+            required: false,
+            values: &[],
+        }
+    }
+
+    const fn required(self) -> Self {
+        Self {
+            required: true,
+            ..self
+        }
+    }
+
+    const fn one_of(self, values: &'static [&'static str]) -> Self {
+        Self { values, ..self }
+    }
+}
+
+/// Keys of a BundleBuilder table.
+#[derive(Debug)]
+pub struct TableSchema {
+    pub keys: &'static [KeySpec],
+    /// Whether keys not listed are allowed (e.g. manager-specific provider attributes)
+    pub extensible: bool,
+}
+
+impl TableSchema {
+    const fn new(keys: &'static [KeySpec]) -> Self {
+        Self {
+            keys,
+            extensible: false,
+        }
+    }
+}
+
+use ValueType::*;
+
+/// `[chimerax.command.<name>]`
+pub const COMMAND: TableSchema = TableSchema::new(&[
+    KeySpec::new("category", Str),
+    KeySpec::new("description", Str),
+]);
+
+/// `[chimerax.tool.<name>]`
+pub const TOOL: TableSchema = TableSchema::new(&[
+    KeySpec::new("category", Str),
+    KeySpec::new("description", Str),
+]);
+
+/// `[chimerax.data-format.<name>.open]`
+const FORMAT_OPEN: TableSchema = TableSchema::new(&[
+    KeySpec::new("type", Str).one_of(&["open", "fetch"]),
+    KeySpec::new("format-name", Str),
+    KeySpec::new("is-default", Bool),
+    KeySpec::new("want-path", Bool),
+    KeySpec::new("check-path", Bool),
+    KeySpec::new("batch", Bool),
+    KeySpec::new("pregrouped-structures", Bool),
+]);
+
+/// `[chimerax.data-format.<name>.save]`
+const FORMAT_SAVE: TableSchema = TableSchema::new(&[
+    KeySpec::new("type", Str).one_of(&["save", "fetch"]),
+    KeySpec::new("format-name", Str),
+    KeySpec::new("is-default", Bool),
+    KeySpec::new("compression-okay", Bool),
+]);
+
+/// `[chimerax.data-format.<name>]`
+pub const DATA_FORMAT: TableSchema = TableSchema::new(&[
+    KeySpec::new("category", Str),
+    KeySpec::new("suffixes", StrOrArray),
+    KeySpec::new("nicknames", StrOrArray),
+    KeySpec::new("mime-types", StrOrArray),
+    KeySpec::new("reference-url", Str),
+    KeySpec::new("synopsis", Str),
+    KeySpec::new("encoding", Str),
+    KeySpec::new("allow-directory", Bool),
+    KeySpec::new("insecure", Bool),
+    KeySpec::new("open", Table(&FORMAT_OPEN)),
+    KeySpec::new("save", Table(&FORMAT_SAVE)),
+]);
+
+/// `[chimerax.selector.<name>]`
+pub const SELECTOR: TableSchema = TableSchema::new(&[
+    KeySpec::new("description", Str),
+    KeySpec::new("atomic", Bool),
+]);
+
+/// `[chimerax.preset.<name>]`
+pub const PRESET: TableSchema = TableSchema::new(&[
+    KeySpec::new("category", Str),
+    KeySpec::new("description", Str),
+]);
+
+/// Compiler settings that may be overridden per platform.
+const PLATFORM: TableSchema = TableSchema::new(&[
+    KeySpec::new("sources", StrArray),
+    KeySpec::new("include-dirs", StrArray),
+    KeySpec::new("library-dirs", StrArray),
+    KeySpec::new("libraries", StrArray),
+    KeySpec::new("extra-compile-args", StrArray),
+    KeySpec::new("extra-link-args", StrArray),
+    KeySpec::new("define-macros", StrArray),
+    KeySpec::new("undef-macros", StrArray),
+    KeySpec::new("framework-dirs", StrArray),
+    KeySpec::new("frameworks", StrArray),
+]);
+
+/// `[chimerax.extension.<name>.platform]`; unknown platform names are
+/// reported by [`extension_check`](super::extension_check).
+const PLATFORMS: TableSchema = TableSchema {
+    keys: &[
+        KeySpec::new("mac", Table(&PLATFORM)),
+        KeySpec::new("windows", Table(&PLATFORM)),
+        KeySpec::new("linux", Table(&PLATFORM)),
+    ],
+    extensible: true,
+};
+
+/// `[chimerax.extension.<name>]`
+pub const EXTENSION: TableSchema = TableSchema::new(&[
+    KeySpec::new("sources", StrArray).required(),
+    KeySpec::new("include-dirs", StrArray),
+    KeySpec::new("library-dirs", StrArray),
+    KeySpec::new("libraries", StrArray),
+    KeySpec::new("extra-compile-args", StrArray),
+    KeySpec::new("extra-link-args", StrArray),
+    KeySpec::new("define-macros", StrArray),
+    KeySpec::new("undef-macros", StrArray),
+    KeySpec::new("framework-dirs", StrArray),
+    KeySpec::new("frameworks", StrArray),
+    KeySpec::new("include-modules", StrArray),
+    KeySpec::new("library-modules", StrArray),
+    KeySpec::new("language", Str).one_of(&["c", "c++"]),
+    KeySpec::new("limited-api", Str),
+    KeySpec::new("platform", Table(&PLATFORMS)),
+]);
+
+/// `[chimerax.library.<name>]`
+pub const LIBRARY: TableSchema = TableSchema::new(&[
+    KeySpec::new("sources", StrArray).required(),
+    KeySpec::new("include-dirs", StrArray),
+    KeySpec::new("library-dirs", StrArray),
+    KeySpec::new("libraries", StrArray),
+    KeySpec::new("extra-compile-args", StrArray),
+    KeySpec::new("extra-link-args", StrArray),
+    KeySpec::new("define-macros", StrArray),
+    KeySpec::new("undef-macros", StrArray),
+    KeySpec::new("framework-dirs", StrArray),
+    KeySpec::new("frameworks", StrArray),
+    KeySpec::new("include-modules", StrArray),
+    KeySpec::new("library-modules", StrArray),
+    KeySpec::new("language", Str).one_of(&["c", "c++"]),
+    KeySpec::new("static", Bool),
+    KeySpec::new("platform", Table(&PLATFORMS)),
+]);
+
+/// `[chimerax.manager.<name>]`
+pub const MANAGER: TableSchema = TableSchema::new(&[
+    KeySpec::new("gui-only", Bool),
+    KeySpec::new("autostart", Bool),
+]);
+
+/// `[chimerax.provider.<name>]`; other keys are passed on to the manager.
+pub const PROVIDER: TableSchema = TableSchema {
+    keys: &[
+        KeySpec::new("manager", Str).required(),
+        KeySpec::new("name", Str),
+    ],
+    extensible: true,
+};
+
+/// `[chimerax]`
+pub const CHIMERAX: TableSchema = TableSchema::new(&[
+    KeySpec::new("package", Str).required(),
+    KeySpec::new("module-name-override", Str),
+    KeySpec::new("pure", Bool),
+    KeySpec::new("custom-init", Bool),
+    KeySpec::new("min-session-version", Integer),
+    KeySpec::new("max-session-version", Integer),
+    KeySpec::new("min-chimerax-version", Str),
+    KeySpec::new("categories", StrArray),
+    KeySpec::new("classifiers", StrArray),
+    KeySpec::new("supersedes", StrArray),
+    KeySpec::new("package-data", StrArrayMap),
+    KeySpec::new("extra-files", StrArrayMap),
+    KeySpec::new("data-files", StrArrayMap),
+    KeySpec::new("command", Entries(&COMMAND)),
+    KeySpec::new("tool", Entries(&TOOL)),
+    KeySpec::new("data-format", Entries(&DATA_FORMAT)),
+    KeySpec::new("selector", Entries(&SELECTOR)),
+    KeySpec::new("preset", Entries(&PRESET)),
+    KeySpec::new("extension", Entries(&EXTENSION)),
+    KeySpec::new("library", Entries(&LIBRARY)),
+    KeySpec::new("manager", Entries(&MANAGER)),
+    KeySpec::new("provider", Entries(&PROVIDER)),
+]);
+
+/// Minimum Jaro-Winkler similarity for a "did you mean" suggestion.
+const SUGGESTION_THRESHOLD: f64 = 0.8;
+
+/// Validate a `[chimerax]` table against the BundleBuilder schema.
+///
+/// Unknown keys are warnings (BundleBuilder ignores them); type errors,
+/// missing required keys, and invalid values are errors.
+pub fn validate(chimerax: &toml::Value, result: &mut ValidationResult) {
+    let keys = vec!["chimerax".to_string()];
+    if chimerax.is_table() {
+        check_table(chimerax, &CHIMERAX, &keys, result);
+    } else {
+        result.add(
+            Diagnostic::new(
+                &rules::MISSING_CHIMERAX_SECTION,
+                "[chimerax] must be a table",
+            )
+            .at_key(&keys),
+        );
+    }
+}
+
+fn check_table(
+    value: &toml::Value,
+    schema: &TableSchema,
+    keys: &[String],
+    result: &mut ValidationResult,
+) {
+    let Some(table) = value.as_table() else {
+        return;
+    };
+    let path = table_path(keys);
+
+    for spec in schema.keys {
+        if spec.required && !table.contains_key(spec.name) {
+            result.add(
+                Diagnostic::new(
+                    &rules::MISSING_REQUIRED_KEY,
+                    format!("[{}].{} is missing", path, spec.name),
+                )
+                .at_key(&child(keys, spec.name)),
+            );
+        }
+    }
+
+    for (key, value) in table {
+        match schema.keys.iter().find(|spec| spec.name == key) {
+            Some(spec) => check_value(value, spec, keys, result),
+            None if schema.extensible => {}
+            None => {
+                let mut msg = format!("[{}].{} is not a known key", path, key_segment(key));
+                if let Some(suggestion) = suggest(key, schema.keys.iter().map(|s| s.name)) {
+                    msg.push_str(&format!(" (did you mean '{}'?)", suggestion));
+                }
+                result.add(Diagnostic::new(&rules::UNKNOWN_KEY, msg).at_key(&child(keys, key)));
+            }
+        }
+    }
+}
+
+fn check_value(
+    value: &toml::Value,
+    spec: &KeySpec,
+    keys: &[String],
+    result: &mut ValidationResult,
+) {
+    let path = table_path(keys);
+    let value_keys = child(keys, spec.name);
+
+    if !spec.ty.matches(value) {
+        result.add(
+            Diagnostic::new(
+                &rules::INVALID_TYPE,
+                format!("[{}].{} must be {}", path, spec.name, spec.ty.describe()),
+            )
+            .at_key(&value_keys),
+        );
+        return;
+    }
+
+    if let Some(s) = value.as_str() {
+        if !spec.values.is_empty() && !spec.values.contains(&s) {
+            let allowed: Vec<String> = spec.values.iter().map(|v| format!("'{}'", v)).collect();
+            result.add(
+                Diagnostic::new(
+                    &rules::INVALID_VALUE,
+                    format!(
+                        "[{}].{} must be one of {} (found '{}')",
+                        path,
+                        spec.name,
+                        allowed.join(", "),
+                        s
+                    ),
+                )
+                .at_key(&value_keys),
+            );
+        }
+    }
+
+    match spec.ty {
+        Table(schema) => check_table(value, schema, &value_keys, result),
+        Entries(schema) => {
+            for (name, entry) in value.as_table().into_iter().flatten() {
+                let entry_keys = child(&value_keys, name);
+                if entry.is_table() {
+                    check_table(entry, schema, &entry_keys, result);
+                } else {
+                    result.add(
+                        Diagnostic::new(
+                            &rules::INVALID_TYPE,
+                            format!("[{}] must be a table", table_path(&entry_keys)),
+                        )
+                        .at_key(&entry_keys),
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Key path extended by one key.
+fn child(keys: &[String], key: &str) -> Vec<String> {
+    let mut keys = keys.to_vec();
+    keys.push(key.to_string());
+    keys
+}
+
+/// Format a key path as a TOML table header (without brackets).
+pub(crate) fn table_path(keys: &[String]) -> String {
+    keys.iter()
+        .map(|k| key_segment(k))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// The most similar candidate to an unknown key, if any is close enough.
+pub(crate) fn suggest<'a>(key: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    candidates
+        .map(|c| (strsim::jaro_winkler(key, c), c))
+        .filter(|(score, _)| *score >= SUGGESTION_THRESHOLD)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, c)| c)
+}
+
+/// Format a key as it appears in a TOML table header, quoting if needed.
+fn key_segment(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if bare {
+        key.to_string()
+    } else {
+        format!("\"{}\"", key.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages<'a>(diagnostics: impl Iterator<Item = &'a Diagnostic>) -> Vec<String> {
+        diagnostics.map(|d| d.message.clone()).collect()
+    }
+
+    fn validate_str(content: &str) -> ValidationResult {
+        let pyproject: toml::Value = toml::from_str(content).unwrap();
+        let mut result = ValidationResult::default();
+        validate(pyproject.get("chimerax").unwrap(), &mut result);
+        result
+    }
+
+    #[test]
+    fn test_valid_tables() {
+        let result = validate_str(
+            r#"
+[chimerax]
+package = "chimerax.test"
+min-session-version = 1
+categories = ["General"]
+
+[chimerax.command."test cmd"]
+category = "General"
+description = "Test"
+
+[chimerax.data-format.XYZ]
+suffixes = ".xyz"
+open = { format-name = "XYZ", is-default = true }
+
+[chimerax.extension._test]
+sources = ["src/_test.cpp"]
+language = "c++"
+
+[chimerax.extension._test.platform.mac]
+extra-compile-args = ["-stdlib=libc++"]
+
+[chimerax.provider.open_xyz]
+manager = "open command"
+example-ids = ["1abc"]
+"#,
+        );
+        assert!(
+            result.errors().next().is_none(),
+            "{:?}",
+            messages(result.errors())
+        );
+        assert!(
+            result.warnings().next().is_none(),
+            "{:?}",
+            messages(result.warnings())
+        );
+    }
+
+    #[test]
+    fn test_unknown_key_suggestion() {
+        let result = validate_str(
+            r#"
+[chimerax]
+package = "chimerax.test"
+
+[chimerax.tool."My Tool"]
+descripton = "Test"
+"#,
+        );
+        assert!(result.errors().next().is_none());
+        assert_eq!(
+            messages(result.warnings()),
+            vec![
+                "[chimerax.tool.\"My Tool\"].descripton is not a known key (did you mean 'description'?)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_key_without_suggestion() {
+        let result = validate_str(
+            r#"
+[chimerax]
+package = "chimerax.test"
+zzz = 1
+"#,
+        );
+        assert_eq!(
+            messages(result.warnings()),
+            vec!["[chimerax].zzz is not a known key"]
+        );
+    }
+
+    #[test]
+    fn test_wrong_types() {
+        let result = validate_str(
+            r#"
+[chimerax]
+package = "chimerax.test"
+pure = "yes"
+categories = "General"
+min-session-version = "one"
+command = ["a"]
+
+[chimerax.selector.sel]
+atomic = 1
+"#,
+        );
+        assert!(
+            messages(result.errors()).contains(&"[chimerax].pure must be a boolean".to_string())
+        );
+        assert!(messages(result.errors())
+            .contains(&"[chimerax].categories must be an array of strings".to_string()));
+        assert!(messages(result.errors())
+            .contains(&"[chimerax].min-session-version must be an integer".to_string()));
+        assert!(
+            messages(result.errors()).contains(&"[chimerax].command must be a table".to_string())
+        );
+        assert!(messages(result.errors())
+            .contains(&"[chimerax.selector.sel].atomic must be a boolean".to_string()));
+    }
+
+    #[test]
+    fn test_missing_required_keys() {
+        let result = validate_str(
+            r#"
+[chimerax]
+categories = ["General"]
+
+[chimerax.extension._ext]
+language = "c++"
+
+[chimerax.provider.p]
+name = "p"
+"#,
+        );
+        assert!(messages(result.errors()).contains(&"[chimerax].package is missing".to_string()));
+        assert!(messages(result.errors())
+            .contains(&"[chimerax.extension._ext].sources is missing".to_string()));
+        assert!(messages(result.errors())
+            .contains(&"[chimerax.provider.p].manager is missing".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_enum_value() {
+        let result = validate_str(
+            r#"
+[chimerax]
+package = "chimerax.test"
+
+[chimerax.extension._ext]
+sources = ["a.cpp"]
+language = "cxx"
+
+[chimerax.extension._ext.platform.macos]
+libraries = ["m"]
+"#,
+        );
+        assert_eq!(
+            messages(result.errors()),
+            vec!["[chimerax.extension._ext].language must be one of 'c', 'c++' (found 'cxx')"]
+        );
+        // Platform names are checked by extension_check
+        assert!(result.warnings().next().is_none());
+    }
+
+    #[test]
+    fn test_provider_allows_manager_keys() {
+        let result = validate_str(
+            r#"
+[chimerax]
+package = "chimerax.test"
+
+[chimerax.provider.p]
+manager = "presets"
+anything-goes = true
+"#,
+        );
+        assert!(result.errors().next().is_none());
+        assert!(result.warnings().next().is_none());
+    }
+
+    #[test]
+    fn test_templates_match_schema() {
+        let templates = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("templates");
+        let placeholder = regex::Regex::new(r"\{\{\w+\}\}").unwrap();
+        for entry in std::fs::read_dir(templates).unwrap() {
+            let path = entry.unwrap().path().join("pyproject.toml.tmpl");
+            if !path.exists() {
+                continue;
+            }
+            let content = std::fs::read_to_string(&path).unwrap();
+            let result = validate_str(&placeholder.replace_all(&content, "x"));
+            assert!(
+                result.errors().next().is_none(),
+                "{}: {:?}",
+                path.display(),
+                messages(result.errors())
+            );
+            assert!(
+                result.warnings().next().is_none(),
+                "{}: {:?}",
+                path.display(),
+                messages(result.warnings())
+            );
+        }
+    }
+}
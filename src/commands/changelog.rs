@@ -0,0 +1,388 @@
+//! `echidna changelog` command implementation.
+//!
+//! Writes a Keep a Changelog section for the version in pyproject.toml from
+//! the conventional commits (`feat: ...`, `fix(scope): ...`) since the last
+//! version tag. The same module reads a version's section back as release
+//! notes for `echidna release` and `echidna publish`.
+
+use crate::commands::version;
+use crate::error::{EchidnaError, Result};
+use crate::git::{self, CommitMessage};
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Changelog file, relative to the project.
+pub const CHANGELOG: &str = "CHANGELOG.md";
+
+/// Header of a new changelog.
+const HEADER: &str = "# Changelog
+
+All notable changes to this project will be documented in this file.
+
+The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/).
+";
+
+/// Keep a Changelog subsections, in order.
+const SECTIONS: [&str; 6] = [
+    "Added",
+    "Changed",
+    "Deprecated",
+    "Removed",
+    "Fixed",
+    "Security",
+];
+
+/// Arguments for the changelog command.
+pub struct ChangelogArgs {
+    /// Project directory
+    pub path: PathBuf,
+    /// Tag or commit to start after (default: the last version tag)
+    pub since: Option<String>,
+    /// Print the section instead of writing it
+    pub dry_run: bool,
+}
+
+/// Execute the changelog command.
+pub fn execute(args: ChangelogArgs) -> Result<()> {
+    let project_dir = args.path.canonicalize().unwrap_or(args.path.clone());
+    let version = version::read_version(&project_dir)?;
+
+    let content = read_changelog(&project_dir)?;
+    if content
+        .as_deref()
+        .and_then(|c| section(c, &version))
+        .is_some()
+    {
+        return Err(EchidnaError::ConfigError(format!(
+            "{} already has a section for {}; bump the version first",
+            CHANGELOG, version
+        )));
+    }
+
+    let since = args
+        .since
+        .clone()
+        .or_else(|| git::last_version_tag(&project_dir));
+    let text = generate_section(&project_dir, &version, since.as_deref())?;
+
+    if args.dry_run {
+        print!("{}", text);
+        return Ok(());
+    }
+
+    fs::write(
+        project_dir.join(CHANGELOG),
+        insert_section(content.as_deref(), &text),
+    )?;
+    println!(
+        "✓ Added [{}] to {} from the commits since {}",
+        version,
+        CHANGELOG,
+        since.as_deref().unwrap_or("the first commit")
+    );
+    Ok(())
+}
+
+/// Subject of a conventional commit: `type(scope)!: description`.
+static CONVENTIONAL_SUBJECT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\w+)(?:\(([^)]*)\))?(!)?:\s*(.+)$").unwrap());
+
+/// A conventional commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConventionalCommit {
+    pub kind: String,
+    pub scope: Option<String>,
+    pub description: String,
+    pub breaking: bool,
+}
+
+impl ConventionalCommit {
+    /// Parse `type(scope)!: description`; `None` for other messages.
+    pub fn parse(message: &CommitMessage) -> Option<Self> {
+        let captures = CONVENTIONAL_SUBJECT.captures(&message.subject)?;
+        let breaking = captures.get(3).is_some()
+            || message.body.lines().any(|line| {
+                line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:")
+            });
+        Some(Self {
+            kind: captures[1].to_lowercase(),
+            scope: captures
+                .get(2)
+                .map(|s| s.as_str().trim().to_string())
+                .filter(|s| !s.is_empty()),
+            description: captures[4].trim().to_string(),
+            breaking,
+        })
+    }
+
+    /// Changelog subsection of the commit; `None` for commits that are left
+    /// out (docs, tests, chores, ...).
+    fn section(&self) -> Option<&'static str> {
+        match self.kind.as_str() {
+            "feat" => Some("Added"),
+            "fix" => Some("Fixed"),
+            "perf" | "refactor" => Some("Changed"),
+            "deprecate" => Some("Deprecated"),
+            "remove" => Some("Removed"),
+            "security" => Some("Security"),
+            _ if self.breaking => Some("Changed"),
+            _ => None,
+        }
+    }
+
+    /// Changelog entry line.
+    fn entry(&self) -> String {
+        let mut description = self.description.clone();
+        if let Some(first) = description.get(..1) {
+            description.replace_range(..1, &first.to_uppercase());
+        }
+        let scope = self
+            .scope
+            .as_ref()
+            .map(|s| format!("**{}:** ", s))
+            .unwrap_or_default();
+        let breaking = if self.breaking { "**Breaking:** " } else { "" };
+        format!("- {}{}{}", breaking, scope, description)
+    }
+}
+
+/// Changelog section for `version` from the commits after `since`.
+pub fn generate_section(project_dir: &Path, version: &str, since: Option<&str>) -> Result<String> {
+    let commits = git::commits_since(project_dir, since)?;
+    let commits: Vec<ConventionalCommit> = commits
+        .iter()
+        .filter_map(ConventionalCommit::parse)
+        .collect();
+    render_section(version, &today(), &commits).ok_or_else(|| {
+        EchidnaError::ConfigError(format!(
+            "No feat, fix, or other changelog commits since {}",
+            since.unwrap_or("the first commit")
+        ))
+    })
+}
+
+/// Render a `## [version] - date` section, oldest commits first; `None` if
+/// no commit belongs in the changelog.
+fn render_section(version: &str, date: &str, commits: &[ConventionalCommit]) -> Option<String> {
+    let mut text = format!("## [{}] - {}\n", version, date);
+    let mut empty = true;
+    for heading in SECTIONS {
+        let entries: Vec<String> = commits
+            .iter()
+            .rev()
+            .filter(|commit| commit.section() == Some(heading))
+            .map(ConventionalCommit::entry)
+            .collect();
+        if entries.is_empty() {
+            continue;
+        }
+        empty = false;
+        text.push_str(&format!("\n### {}\n\n{}\n", heading, entries.join("\n")));
+    }
+    (!empty).then_some(text)
+}
+
+/// Insert a version section above the newest released version (below
+/// `## [Unreleased]`); a missing changelog starts from the standard header.
+pub fn insert_section(content: Option<&str>, text: &str) -> String {
+    let content = content.unwrap_or(HEADER);
+    let position = content
+        .match_indices("## [")
+        .map(|(i, _)| i)
+        .filter(|&i| i == 0 || content[..i].ends_with('\n'))
+        .find(|&i| !content[i..].starts_with("## [Unreleased]"));
+    match position {
+        Some(i) => format!("{}{}\n{}", &content[..i], text, &content[i..]),
+        None => format!("{}\n\n{}", content.trim_end_matches('\n'), text),
+    }
+}
+
+/// The changelog of a project, if it has one.
+fn read_changelog(project_dir: &Path) -> Result<Option<String>> {
+    let path = project_dir.join(CHANGELOG);
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(fs::read_to_string(path)?))
+}
+
+/// Entries of a version's section, without its heading.
+pub fn section(content: &str, version: &str) -> Option<String> {
+    let heading = format!("## [{}]", version);
+    let start = content
+        .match_indices(&heading)
+        .map(|(i, _)| i)
+        .find(|&i| i == 0 || content[..i].ends_with('\n'))?;
+    let body_start = content[start..].find('\n').map(|i| start + i + 1)?;
+    let body_end = content[body_start..]
+        .find("\n## ")
+        .map(|i| body_start + i)
+        .unwrap_or(content.len());
+    Some(content[body_start..body_end].trim().to_string())
+}
+
+/// Release notes of a version: its section of the project's changelog.
+pub fn release_notes(project_dir: &Path, version: &str) -> Result<String> {
+    read_changelog(project_dir)?
+        .and_then(|content| section(&content, version))
+        .filter(|notes| !notes.is_empty())
+        .ok_or_else(|| {
+            EchidnaError::ConfigError(format!(
+                "{} has no entries for {} to use as release notes",
+                CHANGELOG, version
+            ))
+        })
+}
+
+/// Move the `## [Unreleased]` entries of a Keep a Changelog file under a new
+/// `## [version] - date` heading, leaving an empty Unreleased section.
+pub fn promote_unreleased(content: &str, version: &str, date: &str) -> Result<String> {
+    let heading = content
+        .match_indices("## [Unreleased]")
+        .map(|(i, _)| i)
+        .find(|&i| i == 0 || content[..i].ends_with('\n'))
+        .ok_or_else(|| {
+            EchidnaError::ConfigError(format!("{} has no ## [Unreleased] section", CHANGELOG))
+        })?;
+    let body_start = content[heading..]
+        .find('\n')
+        .map(|i| heading + i + 1)
+        .unwrap_or(content.len());
+    let body_end = content[body_start..]
+        .find("\n## ")
+        .map(|i| body_start + i + 1)
+        .unwrap_or(content.len());
+
+    let body = &content[body_start..body_end];
+    if body.trim().is_empty() {
+        return Err(EchidnaError::ConfigError(format!(
+            "{} has no entries under ## [Unreleased]; describe the release first",
+            CHANGELOG
+        )));
+    }
+
+    Ok(format!(
+        "{}\n## [{}] - {}\n{}",
+        &content[..body_start],
+        version,
+        date,
+        &content[body_start..]
+    ))
+}
+
+/// Today's date (UTC) as YYYY-MM-DD.
+pub fn today() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Gregorian date of a day count since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGELOG_CONTENT: &str = "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- Thing\n\n## [0.1.0] - 2026-01-01\n\n- First\n";
+
+    fn commit(subject: &str, body: &str) -> Option<ConventionalCommit> {
+        ConventionalCommit::parse(&CommitMessage {
+            subject: subject.into(),
+            body: body.into(),
+        })
+    }
+
+    #[test]
+    fn test_parse_conventional_commit() {
+        assert_eq!(
+            commit("fix(session): restore tool state", ""),
+            Some(ConventionalCommit {
+                kind: "fix".into(),
+                scope: Some("session".into()),
+                description: "restore tool state".into(),
+                breaking: false,
+            })
+        );
+        assert!(commit("feat!: drop Python 3.9", "").unwrap().breaking);
+        assert!(
+            commit("refactor: rename command", "BREAKING CHANGE: renamed")
+                .unwrap()
+                .breaking
+        );
+        assert_eq!(commit("Release 0.1.0", ""), None);
+        assert_eq!(commit("Merge branch 'x': y", ""), None);
+    }
+
+    #[test]
+    fn test_render_section() {
+        // Newest first, as git log lists them
+        let commits: Vec<_> = [
+            "docs: fix typo",
+            "fix: handle empty selection",
+            "feat(tool): add dark theme",
+            "feat: add measure command",
+            "chore!: require ChimeraX 1.8",
+        ]
+        .iter()
+        .filter_map(|s| commit(s, ""))
+        .collect();
+        assert_eq!(
+            render_section("0.2.0", "2026-10-18", &commits).unwrap(),
+            "## [0.2.0] - 2026-10-18\n\n### Added\n\n- Add measure command\n- **tool:** Add dark theme\n\n\
+             ### Changed\n\n- **Breaking:** Require ChimeraX 1.8\n\n### Fixed\n\n- Handle empty selection\n"
+        );
+        assert_eq!(render_section("0.2.0", "2026-10-18", &commits[..1]), None);
+    }
+
+    #[test]
+    fn test_insert_and_read_section() {
+        let text = "## [0.2.0] - 2026-10-18\n\n### Fixed\n\n- Bug\n";
+        let content = insert_section(Some(CHANGELOG_CONTENT), text);
+        assert_eq!(
+            content,
+            "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- Thing\n\n## [0.2.0] - 2026-10-18\n\n### Fixed\n\n- Bug\n\n## [0.1.0] - 2026-01-01\n\n- First\n"
+        );
+        assert_eq!(section(&content, "0.2.0").unwrap(), "### Fixed\n\n- Bug");
+        assert_eq!(section(&content, "0.1.0").unwrap(), "- First");
+        assert_eq!(section(&content, "0.3.0"), None);
+
+        assert_eq!(insert_section(None, text), format!("{}\n{}", HEADER, text));
+    }
+
+    #[test]
+    fn test_promote_unreleased() {
+        let content = promote_unreleased(CHANGELOG_CONTENT, "0.2.0", "2026-10-18").unwrap();
+        assert_eq!(
+            content,
+            "# Changelog\n\n## [Unreleased]\n\n## [0.2.0] - 2026-10-18\n\n### Added\n\n- Thing\n\n## [0.1.0] - 2026-01-01\n\n- First\n"
+        );
+
+        let err = promote_unreleased(&content, "0.3.0", "2026-10-18").unwrap_err();
+        assert!(err.to_string().contains("no entries"));
+        assert!(promote_unreleased("# Changelog\n", "0.3.0", "2026-10-18").is_err());
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_723), (2024, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+}
@@ -48,6 +48,7 @@ pub fn execute(args: DebugArgs) -> Result<()> {
             path: project_dir.clone(),
             wheel: None,
             user: false,
+            index: None,
             chimerax: executor.executable().to_path_buf(),
             verbosity: args.verbosity,
         })?;
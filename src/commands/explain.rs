@@ -0,0 +1,47 @@
+//! `echidna explain` command implementation.
+
+use crate::commands::fix::{self, Applicability};
+use crate::diagnostics::{find_rule, rules};
+use crate::error::{EchidnaError, Result};
+
+/// Arguments for the explain command.
+pub struct ExplainArgs {
+    /// Diagnostic code or name (all rules are listed if not given)
+    pub code: Option<String>,
+}
+
+/// Execute the explain command.
+pub fn execute(args: ExplainArgs) -> Result<()> {
+    let Some(code) = args.code else {
+        for rule in rules::ALL {
+            println!("{}  {:<8} {}", rule.code, rule.severity, rule.name);
+        }
+        return Ok(());
+    };
+
+    let rule = find_rule(&code).ok_or_else(|| {
+        EchidnaError::ConfigError(format!(
+            "Unknown diagnostic code '{}'. Run 'echidna explain' to list all codes.",
+            code
+        ))
+    })?;
+
+    println!(
+        "{} ({}, {} by default)",
+        rule.code, rule.name, rule.severity
+    );
+    println!();
+    println!("{}", rule.explanation);
+    match fix::applicability(rule) {
+        Some(Applicability::Safe) => {
+            println!();
+            println!("Automatic fix: safe (`echidna validate --fix`)");
+        }
+        Some(Applicability::Unsafe) => {
+            println!();
+            println!("Automatic fix: unsafe (`echidna validate --fix --unsafe-fixes`)");
+        }
+        None => {}
+    }
+    Ok(())
+}
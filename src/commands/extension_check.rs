@@ -0,0 +1,467 @@
+//! Checks of `[chimerax.extension.*]` and `[chimerax.library.*]` declarations
+//! against the filesystem (and, if ChimeraX is available, its modules).
+
+use crate::chimerax::ChimeraXExecutor;
+use crate::commands::bundle_schema::{suggest, table_path};
+use crate::commands::glob::{glob_files, is_glob};
+use crate::commands::validate::{package_source_dir, ValidationResult};
+use crate::diagnostics::{rules, Diagnostic};
+use crate::error::Result;
+use std::path::Path;
+
+/// Platform subtables applied by BundleBuilder.
+const PLATFORMS: &[&str] = &["mac", "windows", "linux"];
+
+/// Common platform spellings and the name BundleBuilder expects.
+const PLATFORM_ALIASES: &[(&str, &str)] = &[
+    ("macos", "mac"),
+    ("darwin", "mac"),
+    ("osx", "mac"),
+    ("win", "windows"),
+    ("win32", "windows"),
+    ("win64", "windows"),
+];
+
+/// Platform subtable of the platform echidna runs on.
+const CURRENT_PLATFORM: &str = if cfg!(target_os = "macos") {
+    "mac"
+} else if cfg!(target_os = "windows") {
+    "windows"
+} else {
+    "linux"
+};
+
+/// A declared extension or library.
+struct CompiledModule<'a> {
+    /// `extension` or `library`
+    kind: &'static str,
+    name: &'a str,
+    table: &'a toml::value::Table,
+}
+
+impl CompiledModule<'_> {
+    fn key(&self, keys: &[&str]) -> Vec<String> {
+        ["chimerax", self.kind, self.name]
+            .iter()
+            .chain(keys)
+            .map(|k| k.to_string())
+            .collect()
+    }
+
+    /// The table and key path prefix of each platform subtable, top level first.
+    fn tables(&self) -> Vec<(&toml::value::Table, Vec<&str>)> {
+        let mut tables = vec![(self.table, Vec::new())];
+        if let Some(platforms) = self.table.get("platform").and_then(|p| p.as_table()) {
+            for (platform, table) in platforms {
+                if let Some(table) = table.as_table() {
+                    tables.push((table, vec!["platform", platform.as_str()]));
+                }
+            }
+        }
+        tables
+    }
+}
+
+/// Declared extensions and libraries of a `[chimerax]` table.
+fn compiled_modules(chimerax: &toml::Value) -> Vec<CompiledModule<'_>> {
+    let mut modules = Vec::new();
+    for kind in ["extension", "library"] {
+        let Some(entries) = chimerax.get(kind).and_then(|e| e.as_table()) else {
+            continue;
+        };
+        for (name, table) in entries {
+            if let Some(table) = table.as_table() {
+                modules.push(CompiledModule { kind, name, table });
+            }
+        }
+    }
+    modules
+}
+
+/// String array value of a key (non-strings are reported by the schema check).
+fn strings<'a>(table: &'a toml::value::Table, key: &str) -> Vec<&'a str> {
+    table
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default()
+}
+
+/// Check extension and library declarations against the project.
+pub fn check(
+    project_dir: &Path,
+    chimerax: &toml::Value,
+    result: &mut ValidationResult,
+) -> Result<()> {
+    let modules = compiled_modules(chimerax);
+    if modules.is_empty() {
+        return Ok(());
+    }
+
+    if chimerax.get("pure").and_then(|p| p.as_bool()) != Some(false) {
+        result.add(
+            Diagnostic::new(
+                &rules::EXTENSION_NOT_PURE_FALSE,
+                "[chimerax].pure must be false for a bundle with C/C++ extensions",
+            )
+            .at_key(&["chimerax", "pure"]),
+        );
+    }
+
+    let package_dir = package_source_dir(project_dir, Some(chimerax));
+    for module in &modules {
+        check_platforms(module, result);
+
+        for (table, prefix) in module.tables() {
+            let platform = prefix.get(1).copied();
+            check_sources(project_dir, module, table, &prefix, result)?;
+            // Directories of other platforms may only exist on those platforms
+            if platform.is_none() || platform == Some(CURRENT_PLATFORM) {
+                check_dirs(project_dir, module, table, &prefix, result);
+            }
+        }
+
+        for key in ["include-modules", "library-modules"] {
+            for name in strings(module.table, key) {
+                if !is_module_name(name) {
+                    result.add(
+                        Diagnostic::new(
+                            &rules::UNKNOWN_EXTENSION_MODULE,
+                            format!(
+                                "[{}].{} entry '{}' is not a Python module name",
+                                table_path(&module.key(&[])),
+                                key,
+                                name
+                            ),
+                        )
+                        .at_key(&module.key(&[key])),
+                    );
+                }
+            }
+        }
+
+        if module.kind == "extension" {
+            if let Some(dir) = &package_dir {
+                check_collision(project_dir, dir, module, result);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that `include-modules` and `library-modules` are importable in ChimeraX.
+pub fn check_modules(
+    executor: &ChimeraXExecutor,
+    chimerax: &toml::Value,
+    result: &mut ValidationResult,
+) -> Result<()> {
+    let modules = compiled_modules(chimerax);
+    let mut names: Vec<&str> = modules
+        .iter()
+        .flat_map(|m| {
+            strings(m.table, "include-modules")
+                .into_iter()
+                .chain(strings(m.table, "library-modules"))
+        })
+        .filter(|name| is_module_name(name))
+        .collect();
+    names.sort();
+    names.dedup();
+    if names.is_empty() {
+        return Ok(());
+    }
+
+    // Module names are validated above, so they are safe to embed
+    let python_code = format!(
+        r#"
+import importlib.util
+for name in {:?}:
+    try:
+        found = importlib.util.find_spec(name) is not None
+    except Exception:
+        found = False
+    print("ECHIDNA_MODULE:%s:%s" % (name, "YES" if found else "NO"))
+"#,
+        names
+    );
+    let escaped = python_code.replace('\n', "\\n").replace('"', "\\\"");
+    let cmd = format!("runscript python -c \"exec(\\\"{}\\\")\"; exit", escaped);
+
+    let output = match executor.run_command(&cmd) {
+        Ok(output) => output,
+        Err(e) => {
+            result.add(Diagnostic::new(
+                &rules::EXTENSION_MODULE_CHECK_FAILED,
+                format!("cannot check extension modules in ChimeraX: {}", e),
+            ));
+            return Ok(());
+        }
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let missing: Vec<&str> = stdout
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("ECHIDNA_MODULE:"))
+        .filter_map(|line| line.strip_suffix(":NO"))
+        .collect();
+
+    for module in &modules {
+        for key in ["include-modules", "library-modules"] {
+            for name in strings(module.table, key) {
+                if missing.contains(&name) {
+                    result.add(
+                        Diagnostic::new(
+                            &rules::UNKNOWN_EXTENSION_MODULE,
+                            format!(
+                                "[{}].{} entry '{}' is not importable in ChimeraX",
+                                table_path(&module.key(&[])),
+                                key,
+                                name
+                            ),
+                        )
+                        .at_key(&module.key(&[key])),
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Report platform subtables with names BundleBuilder ignores.
+fn check_platforms(module: &CompiledModule, result: &mut ValidationResult) {
+    let Some(platforms) = module.table.get("platform").and_then(|p| p.as_table()) else {
+        return;
+    };
+    for platform in platforms.keys() {
+        if PLATFORMS.contains(&platform.as_str()) {
+            continue;
+        }
+        let lower = platform.to_ascii_lowercase();
+        let suggestion = PLATFORM_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == lower)
+            .map(|(_, name)| *name)
+            .or_else(|| suggest(&lower, PLATFORMS.iter().copied()));
+        let mut message = format!(
+            "[{}] is not a known platform (expected mac, windows, or linux)",
+            table_path(&module.key(&["platform", platform]))
+        );
+        if let Some(suggestion) = suggestion {
+            message.push_str(&format!("; did you mean '{}'?", suggestion));
+        }
+        result.add(
+            Diagnostic::new(&rules::INVALID_PLATFORM, message)
+                .at_key(&module.key(&["platform", platform])),
+        );
+    }
+}
+
+/// Report `sources` entries that don't exist.
+fn check_sources(
+    project_dir: &Path,
+    module: &CompiledModule,
+    table: &toml::value::Table,
+    prefix: &[&str],
+    result: &mut ValidationResult,
+) -> Result<()> {
+    for source in strings(table, "sources") {
+        let found = if is_glob(source) {
+            !glob_files(project_dir, source)?.is_empty()
+        } else {
+            project_dir.join(source).is_file()
+        };
+        if !found {
+            let key: Vec<&str> = prefix.iter().copied().chain(["sources"]).collect();
+            result.add(
+                Diagnostic::new(
+                    &rules::MISSING_EXTENSION_SOURCE,
+                    format!(
+                        "[{}].sources: '{}' {}",
+                        table_path(&module.key(prefix)),
+                        source,
+                        if is_glob(source) {
+                            "matches no files"
+                        } else {
+                            "does not exist"
+                        }
+                    ),
+                )
+                .at_key(&module.key(&key)),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Report `include-dirs` and `library-dirs` entries that aren't directories.
+fn check_dirs(
+    project_dir: &Path,
+    module: &CompiledModule,
+    table: &toml::value::Table,
+    prefix: &[&str],
+    result: &mut ValidationResult,
+) {
+    for key in ["include-dirs", "library-dirs"] {
+        for dir in strings(table, key) {
+            if !project_dir.join(dir).is_dir() {
+                let path: Vec<&str> = prefix.iter().copied().chain([key]).collect();
+                result.add(
+                    Diagnostic::new(
+                        &rules::MISSING_EXTENSION_DIR,
+                        format!(
+                            "[{}].{}: '{}' is not a directory",
+                            table_path(&module.key(prefix)),
+                            key,
+                            dir
+                        ),
+                    )
+                    .at_key(&module.key(&path)),
+                );
+            }
+        }
+    }
+}
+
+/// Report an extension module that shadows a Python module of the package.
+fn check_collision(
+    project_dir: &Path,
+    package_dir: &Path,
+    module: &CompiledModule,
+    result: &mut ValidationResult,
+) {
+    let module_name = module.name.rsplit('.').next().unwrap_or(module.name);
+    let candidates = [
+        package_dir.join(format!("{}.py", module_name)),
+        package_dir.join(module_name).join("__init__.py"),
+    ];
+    if let Some(existing) = candidates.iter().find(|p| p.is_file()) {
+        let relative = existing.strip_prefix(project_dir).unwrap_or(existing);
+        result.add(
+            Diagnostic::new(
+                &rules::EXTENSION_MODULE_COLLISION,
+                format!(
+                    "Extension module '{}' collides with {}",
+                    module_name,
+                    relative.display()
+                ),
+            )
+            .at_key(&module.key(&[])),
+        );
+    }
+}
+
+/// Check for a dotted Python module name.
+fn is_module_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.split('.').all(|part| {
+            let mut chars = part.chars();
+            matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn check_toml(project_dir: &Path, content: &str) -> Vec<(&'static str, String)> {
+        let chimerax: toml::Value = toml::from_str(content).unwrap();
+        let mut result = ValidationResult::default();
+        check(project_dir, &chimerax, &mut result).unwrap();
+        result
+            .diagnostics
+            .into_iter()
+            .map(|d| (d.rule.code, d.message))
+            .collect()
+    }
+
+    fn create_project(dir: &Path) {
+        fs::create_dir_all(dir.join("src/include")).unwrap();
+        fs::write(dir.join("src/__init__.py"), "bundle_api = None").unwrap();
+        fs::write(dir.join("src/_ext.cpp"), "").unwrap();
+        fs::write(dir.join("src/util.cpp"), "").unwrap();
+    }
+
+    #[test]
+    fn test_valid_extension() {
+        let temp = TempDir::new().unwrap();
+        create_project(temp.path());
+        let diagnostics = check_toml(
+            temp.path(),
+            r#"
+package = "chimerax.ext"
+pure = false
+
+[extension._ext]
+sources = ["src/_ext.cpp", "src/*.cpp"]
+include-dirs = ["src/include"]
+include-modules = ["chimerax.core", "numpy"]
+
+[extension._ext.platform.mac]
+library-dirs = ["/opt/only/on/mac"]
+"#,
+        );
+        assert_eq!(diagnostics, vec![]);
+    }
+
+    #[test]
+    fn test_extension_problems() {
+        let temp = TempDir::new().unwrap();
+        create_project(temp.path());
+        fs::write(temp.path().join("src/_ext.py"), "").unwrap();
+        let diagnostics = check_toml(
+            temp.path(),
+            r#"
+package = "chimerax.ext"
+
+[extension._ext]
+sources = ["src/missing.cpp", "src/*.c"]
+include-dirs = ["include"]
+library-modules = ["chimerax-core"]
+
+[extension._ext.platform.macos]
+sources = ["src/_ext.cpp"]
+"#,
+        );
+        let codes: Vec<_> = diagnostics.iter().map(|(code, _)| *code).collect();
+        assert_eq!(
+            codes,
+            vec!["CX063", "CX064", "CX060", "CX060", "CX061", "CX062", "CX065"]
+        );
+        assert!(diagnostics[1].1.contains("did you mean 'mac'"));
+        assert!(diagnostics[3].1.contains("matches no files"));
+        assert!(diagnostics[6].1.contains("src/_ext.py"));
+    }
+
+    #[test]
+    fn test_no_extensions() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(check_toml(temp.path(), "package = \"chimerax.x\""), vec![]);
+    }
+
+    #[test]
+    fn test_is_module_name() {
+        assert!(is_module_name("chimerax.core"));
+        assert!(is_module_name("_private"));
+        assert!(!is_module_name("chimerax-core"));
+        assert!(!is_module_name("chimerax..core"));
+        assert!(!is_module_name("1abc"));
+    }
+
+    #[test]
+    fn test_check_modules_launch_failure_warns() {
+        let temp = TempDir::new().unwrap();
+        let executor = ChimeraXExecutor::new(temp.path().join("missing/ChimeraX"), 0);
+        let chimerax: toml::Value =
+            toml::from_str("[extension._ext]\ninclude-modules = [\"chimerax.core\"]\n").unwrap();
+        let mut result = ValidationResult::default();
+        check_modules(&executor, &chimerax, &mut result).unwrap();
+
+        assert_eq!(result.diagnostics.len(), 1);
+        assert_eq!(result.diagnostics[0].rule.code, "CX066");
+        assert!(result.is_valid());
+    }
+}
@@ -0,0 +1,308 @@
+//! Automatic fixes for `echidna validate --fix`.
+//!
+//! Fixes edit pyproject.toml with `toml_edit`, so formatting and comments of
+//! the rest of the file are preserved.
+
+use crate::diagnostics::{rules, Diagnostic, Rule};
+use crate::error::Result;
+use similar::TextDiff;
+use std::fmt;
+use toml_edit::{value, Array, DocumentMut, Item, Table};
+
+/// Build backend set by the missing-build-backend fix.
+const BUILD_BACKEND: &str = "chimerax.bundle_builder.cx_pep517";
+
+/// Classifier added by the missing-python-classifier fix.
+const PYTHON_CLASSIFIER: &str = "Programming Language :: Python :: 3";
+
+/// Whether a fix can be applied without review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Applicability {
+    /// Only adds conventional values; never changes behavior
+    Safe,
+    /// Changes user-visible data (e.g. the distribution name) or adds placeholder text
+    Unsafe,
+}
+
+impl fmt::Display for Applicability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Applicability::Safe => "safe",
+            Applicability::Unsafe => "unsafe",
+        })
+    }
+}
+
+/// Applicability of the automatic fix for a rule, if there is one.
+pub fn applicability(rule: &Rule) -> Option<Applicability> {
+    match rule.code {
+        c if c == rules::MISSING_BUILD_BACKEND.code => Some(Applicability::Safe),
+        c if c == rules::MISSING_MIN_SESSION_VERSION.code => Some(Applicability::Safe),
+        c if c == rules::MISSING_PYTHON_CLASSIFIER.code => Some(Applicability::Safe),
+        c if c == rules::BUNDLE_NAME_CONVENTION.code => Some(Applicability::Unsafe),
+        c if c == rules::MISSING_DESCRIPTION.code => Some(Applicability::Unsafe),
+        _ => None,
+    }
+}
+
+/// A fix applied to pyproject.toml.
+#[derive(Debug, Clone)]
+pub struct AppliedFix {
+    pub rule: &'static Rule,
+    pub applicability: Applicability,
+    /// What the fix changed
+    pub description: String,
+}
+
+/// Result of fixing pyproject.toml.
+#[derive(Debug, Default)]
+pub struct FixOutcome {
+    /// Fixed pyproject.toml content
+    pub content: String,
+    pub applied: Vec<AppliedFix>,
+    /// Unsafe fixes that were not applied (need `--unsafe-fixes`)
+    pub skipped: Vec<&'static Rule>,
+}
+
+impl FixOutcome {
+    /// Unified diff between the original and fixed content.
+    pub fn diff(&self, original: &str) -> String {
+        TextDiff::from_lines(original, &self.content)
+            .unified_diff()
+            .header("pyproject.toml", "pyproject.toml")
+            .to_string()
+    }
+}
+
+/// Apply the fixes for `diagnostics` to pyproject.toml content.
+pub fn apply(content: &str, diagnostics: &[Diagnostic], unsafe_fixes: bool) -> Result<FixOutcome> {
+    let mut doc: DocumentMut = content.parse()?;
+    let mut outcome = FixOutcome::default();
+
+    for diagnostic in diagnostics {
+        let Some(applicability) = applicability(diagnostic.rule) else {
+            continue;
+        };
+        if applicability == Applicability::Unsafe && !unsafe_fixes {
+            outcome.skipped.push(diagnostic.rule);
+            continue;
+        }
+        if let Some(description) = fix(&mut doc, diagnostic.rule) {
+            outcome.applied.push(AppliedFix {
+                rule: diagnostic.rule,
+                applicability,
+                description,
+            });
+        }
+    }
+
+    outcome.content = doc.to_string();
+    Ok(outcome)
+}
+
+/// Apply the fix for a rule, returning a description of the change.
+fn fix(doc: &mut DocumentMut, rule: &Rule) -> Option<String> {
+    match rule.code {
+        c if c == rules::MISSING_BUILD_BACKEND.code => {
+            let build_system = table(doc, "build-system")?;
+            build_system.insert("build-backend", value(BUILD_BACKEND));
+            Some(format!(
+                "set [build-system].build-backend = \"{}\"",
+                BUILD_BACKEND
+            ))
+        }
+        c if c == rules::MISSING_MIN_SESSION_VERSION.code => {
+            let chimerax = table(doc, "chimerax")?;
+            chimerax.insert("min-session-version", value(1));
+            if !chimerax.contains_key("max-session-version") {
+                chimerax.insert("max-session-version", value(1));
+                return Some(
+                    "set [chimerax].min-session-version and max-session-version = 1".into(),
+                );
+            }
+            Some("set [chimerax].min-session-version = 1".into())
+        }
+        c if c == rules::MISSING_PYTHON_CLASSIFIER.code => {
+            let classifiers = table(doc, "project")?
+                .get_mut("classifiers")?
+                .as_array_mut()?;
+            push_formatted(classifiers, PYTHON_CLASSIFIER);
+            Some(format!(
+                "added \"{}\" to [project].classifiers",
+                PYTHON_CLASSIFIER
+            ))
+        }
+        c if c == rules::BUNDLE_NAME_CONVENTION.code => {
+            let project = table(doc, "project")?;
+            let name = project.get_mut("name")?.as_value_mut()?;
+            let old = name.as_str()?.to_string();
+            // `chimerax_foo` becomes `ChimeraX-foo`, not `ChimeraX-chimerax_foo`
+            let stem = old
+                .get(..9)
+                .filter(|prefix| {
+                    prefix.eq_ignore_ascii_case("chimerax-")
+                        || prefix.eq_ignore_ascii_case("chimerax_")
+                })
+                .map_or(old.as_str(), |_| &old[9..]);
+            let renamed = format!("ChimeraX-{}", stem);
+            let decor = name.decor().clone();
+            *name = renamed.as_str().into();
+            *name.decor_mut() = decor;
+            Some(format!("renamed [project].name '{}' to '{}'", old, renamed))
+        }
+        c if c == rules::MISSING_DESCRIPTION.code => {
+            let project = table(doc, "project")?;
+            let name = project
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or("Bundle");
+            let description = format!("{} bundle for UCSF ChimeraX", name);
+            project.insert("description", value(&description));
+            Some(format!(
+                "set placeholder [project].description = \"{}\"",
+                description
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// A top-level table of the document.
+fn table<'a>(doc: &'a mut DocumentMut, name: &str) -> Option<&'a mut Table> {
+    doc.get_mut(name).and_then(Item::as_table_mut)
+}
+
+/// Append to an array, following the layout of a multi-line array.
+fn push_formatted(array: &mut Array, item: &str) {
+    let multiline = array
+        .iter()
+        .next()
+        .and_then(|first| first.decor().prefix())
+        .and_then(|prefix| prefix.as_str())
+        .filter(|prefix| prefix.contains('\n'))
+        .map(str::to_string);
+
+    array.push(item);
+    if let Some(prefix) = multiline {
+        if let Some(last) = array.iter_mut().last() {
+            last.decor_mut().set_prefix(prefix);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PYPROJECT: &str = r#"# My bundle
+[build-system]
+requires = ["ChimeraX-BundleBuilder"]
+
+[project]
+name = "my-tool" # distribution name
+version = "0.1.0"
+classifiers = [
+    "Development Status :: 3 - Alpha",
+]
+
+[chimerax]
+package = "chimerax.my_tool"
+"#;
+
+    fn diagnostics() -> Vec<Diagnostic> {
+        vec![
+            Diagnostic::new(&rules::MISSING_BUILD_BACKEND, ""),
+            Diagnostic::new(&rules::BUNDLE_NAME_CONVENTION, ""),
+            Diagnostic::new(&rules::MISSING_DESCRIPTION, ""),
+            Diagnostic::new(&rules::MISSING_PYTHON_CLASSIFIER, ""),
+            Diagnostic::new(&rules::MISSING_MIN_SESSION_VERSION, ""),
+            Diagnostic::new(&rules::MISSING_CATEGORIES, ""),
+        ]
+    }
+
+    #[test]
+    fn test_safe_fixes() {
+        let outcome = apply(PYPROJECT, &diagnostics(), false).unwrap();
+
+        assert_eq!(outcome.applied.len(), 3);
+        assert!(outcome
+            .applied
+            .iter()
+            .all(|f| f.applicability == Applicability::Safe));
+        let skipped: Vec<_> = outcome.skipped.iter().map(|r| r.code).collect();
+        assert_eq!(skipped, vec!["CX012", "CX014"]);
+
+        assert_eq!(
+            outcome.content,
+            r#"# My bundle
+[build-system]
+requires = ["ChimeraX-BundleBuilder"]
+build-backend = "chimerax.bundle_builder.cx_pep517"
+
+[project]
+name = "my-tool" # distribution name
+version = "0.1.0"
+classifiers = [
+    "Development Status :: 3 - Alpha",
+    "Programming Language :: Python :: 3",
+]
+
+[chimerax]
+package = "chimerax.my_tool"
+min-session-version = 1
+max-session-version = 1
+"#
+        );
+    }
+
+    #[test]
+    fn test_unsafe_fixes() {
+        let outcome = apply(PYPROJECT, &diagnostics(), true).unwrap();
+
+        assert_eq!(outcome.applied.len(), 5);
+        assert!(outcome.skipped.is_empty());
+        let doc: toml::Value = toml::from_str(&outcome.content).unwrap();
+        assert_eq!(doc["project"]["name"].as_str(), Some("ChimeraX-my-tool"));
+        assert!(outcome.applied[1].description.contains("'my-tool' to"));
+        assert_eq!(
+            doc["project"]["description"].as_str(),
+            Some("ChimeraX-my-tool bundle for UCSF ChimeraX")
+        );
+        assert!(outcome.content.contains("# distribution name"));
+
+        for prefixed in ["chimerax-my-tool", "Chimerax_my-tool"] {
+            let pyproject = PYPROJECT.replace("\"my-tool\"", &format!("\"{}\"", prefixed));
+            let outcome = apply(&pyproject, &diagnostics(), true).unwrap();
+            let doc: toml::Value = toml::from_str(&outcome.content).unwrap();
+            assert_eq!(doc["project"]["name"].as_str(), Some("ChimeraX-my-tool"));
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let outcome = apply(
+            PYPROJECT,
+            &[Diagnostic::new(&rules::MISSING_BUILD_BACKEND, "")],
+            false,
+        )
+        .unwrap();
+        let diff = outcome.diff(PYPROJECT);
+
+        assert!(diff.starts_with("--- pyproject.toml\n+++ pyproject.toml\n"));
+        assert!(diff.contains("+build-backend = \"chimerax.bundle_builder.cx_pep517\"\n"));
+        assert!(!diff.contains("-requires"));
+    }
+
+    #[test]
+    fn test_applicability() {
+        assert_eq!(
+            applicability(&rules::MISSING_BUILD_BACKEND),
+            Some(Applicability::Safe)
+        );
+        assert_eq!(
+            applicability(&rules::BUNDLE_NAME_CONVENTION),
+            Some(Applicability::Unsafe)
+        );
+        assert_eq!(applicability(&rules::MISSING_SRC), None);
+    }
+}
@@ -0,0 +1,133 @@
+//! Flaky test history for `echidna test --retries`.
+//!
+//! Tests that fail and then pass on retry are recorded in
+//! `.echidna/test-history.json` so recurring flakiness is visible across runs.
+
+use crate::error::Result;
+use crate::test_report::{TestReport, TestStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// History file (relative to the project).
+pub const HISTORY_FILE: &str = ".echidna/test-history.json";
+
+/// Flaky test history of a project.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlakyHistory {
+    /// Number of recorded test runs
+    pub runs: u64,
+    /// Flaky tests by test id (`classname::name`)
+    #[serde(default)]
+    pub tests: BTreeMap<String, FlakyRecord>,
+}
+
+/// How often a test has been flaky.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlakyRecord {
+    /// Number of runs in which the test was flaky
+    pub flaky_runs: u64,
+    /// Run number in which the test was last flaky
+    pub last_flaky_run: u64,
+}
+
+impl FlakyHistory {
+    /// Load the history of a project (empty if there is none yet).
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let path = project_dir.join(HISTORY_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Save the history of a project.
+    pub fn save(&self, project_dir: &Path) -> Result<()> {
+        let path = project_dir.join(HISTORY_FILE);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Record a test run, returning the tests that were flaky in it.
+    pub fn record(&mut self, report: &TestReport) -> Vec<(String, FlakyRecord)> {
+        self.runs += 1;
+        let mut flaky = Vec::new();
+        for test in report
+            .suites
+            .iter()
+            .flat_map(|suite| &suite.tests)
+            .filter(|test| test.status == TestStatus::Flaky)
+        {
+            let id = format!("{}::{}", test.classname, test.name);
+            let record = self.tests.entry(id.clone()).or_default();
+            record.flaky_runs += 1;
+            record.last_flaky_run = self.runs;
+            flaky.push((id, record.clone()));
+        }
+        flaky
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_report::{TestCase, TestSuite};
+    use tempfile::TempDir;
+
+    fn report(statuses: &[(&str, TestStatus)]) -> TestReport {
+        let mut suite = TestSuite::new("pytest");
+        for (name, status) in statuses {
+            suite
+                .tests
+                .push(TestCase::new(*name, "tests.test_gui", *status));
+        }
+        TestReport {
+            suites: vec![suite],
+        }
+    }
+
+    #[test]
+    fn test_record_flaky_runs() {
+        let mut history = FlakyHistory::default();
+
+        let flaky = history.record(&report(&[
+            ("test_a", TestStatus::Flaky),
+            ("test_b", TestStatus::Passed),
+        ]));
+        assert_eq!(flaky.len(), 1);
+        assert_eq!(flaky[0].0, "tests.test_gui::test_a");
+
+        history.record(&report(&[("test_a", TestStatus::Passed)]));
+        let flaky = history.record(&report(&[("test_a", TestStatus::Flaky)]));
+
+        assert_eq!(history.runs, 3);
+        assert_eq!(
+            flaky[0].1,
+            FlakyRecord {
+                flaky_runs: 2,
+                last_flaky_run: 3
+            }
+        );
+        assert!(!history.tests.contains_key("tests.test_gui::test_b"));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(
+            FlakyHistory::load(temp.path()).unwrap(),
+            FlakyHistory::default()
+        );
+
+        let mut history = FlakyHistory::default();
+        history.record(&report(&[("test_a", TestStatus::Flaky)]));
+        history.save(temp.path()).unwrap();
+
+        assert_eq!(FlakyHistory::load(temp.path()).unwrap(), history);
+    }
+}
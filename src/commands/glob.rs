@@ -0,0 +1,132 @@
+//! Glob matching of project files, as in BundleBuilder's file patterns.
+
+use crate::error::Result;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Whether a path contains glob wildcards.
+pub(crate) fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// Files under `base` matching a glob pattern (`*`, `?`, `[...]`, and `**`).
+pub(crate) fn glob_files(base: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let pattern = pattern.trim_start_matches("./");
+    // Walk from the longest directory prefix without wildcards
+    let literal: Vec<&str> = pattern
+        .split('/')
+        .take_while(|segment| !is_glob(segment))
+        .collect();
+    let root = base.join(literal.join("/"));
+
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => regex.push('['),
+            ']' => regex.push(']'),
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    let Ok(regex) = Regex::new(&regex) else {
+        return Ok(Vec::new());
+    };
+
+    let mut files = Vec::new();
+    walk(&root, &mut files)?;
+    files.retain(|file| {
+        file.strip_prefix(base)
+            .map(|relative| {
+                let relative: Vec<_> = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect();
+                regex.is_match(&relative.join("/"))
+            })
+            .unwrap_or(false)
+    });
+    files.sort();
+    Ok(files)
+}
+
+/// Collect the files under `dir`. Symlinked, hidden, and `__pycache__`
+/// directories are not entered.
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if !name.starts_with('.') && name != "__pycache__" {
+                walk(&path, files)?;
+            }
+        } else if !file_type.is_symlink() || path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_glob_files() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("src/sub")).unwrap();
+        fs::write(temp.path().join("src/a.cpp"), "").unwrap();
+        fs::write(temp.path().join("src/b.h"), "").unwrap();
+        fs::write(temp.path().join("src/sub/c.cpp"), "").unwrap();
+
+        let names = |pattern: &str| -> Vec<String> {
+            glob_files(temp.path(), pattern)
+                .unwrap()
+                .iter()
+                .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+                .collect()
+        };
+        assert_eq!(names("src/*.cpp"), vec!["a.cpp"]);
+        assert_eq!(names("src/**/*.cpp"), vec!["a.cpp", "c.cpp"]);
+        assert_eq!(names("./src/?.h"), vec!["b.h"]);
+        assert!(names("include/*.h").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_glob_files_skips_links_and_hidden_dirs() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("src/.git")).unwrap();
+        fs::create_dir_all(temp.path().join("src/__pycache__")).unwrap();
+        fs::write(temp.path().join("src/a.png"), "").unwrap();
+        fs::write(temp.path().join("src/.git/HEAD"), "").unwrap();
+        fs::write(temp.path().join("src/__pycache__/a.pyc"), "").unwrap();
+        std::os::unix::fs::symlink("..", temp.path().join("src/loop")).unwrap();
+        std::os::unix::fs::symlink("a.png", temp.path().join("src/b.png")).unwrap();
+
+        let files = glob_files(temp.path(), "src/**").unwrap();
+        assert_eq!(
+            files,
+            vec![temp.path().join("src/a.png"), temp.path().join("src/b.png")]
+        );
+    }
+}
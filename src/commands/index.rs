@@ -0,0 +1,404 @@
+//! `echidna index` command implementation.
+//!
+//! Builds a static PEP 503 "simple" package index from the wheels in `dist/`
+//! of a bundle or of every workspace member, and serves it on localhost:
+//!
+//! ```text
+//! DIR/index.html                      links to each project
+//! DIR/chimerax-mytool/index.html      links to its wheels, with #sha256= hashes
+//! DIR/chimerax-mytool/*.whl
+//! ```
+
+use crate::error::{EchidnaError, Result};
+use crate::workspace::Workspace;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long the server waits for a client to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum size of a request line and headers, in bytes.
+const MAX_REQUEST_HEAD: u64 = 8 * 1024;
+
+/// Arguments for the index build command.
+pub struct IndexBuildArgs {
+    /// Bundle or workspace directory
+    pub path: PathBuf,
+    /// Index directory
+    pub dir: PathBuf,
+}
+
+/// Arguments for the index serve command.
+pub struct IndexServeArgs {
+    /// Index directory
+    pub dir: PathBuf,
+    /// Port on 127.0.0.1
+    pub port: u16,
+}
+
+/// Execute the index build command.
+pub fn build(args: IndexBuildArgs) -> Result<()> {
+    let path = args.path.canonicalize().unwrap_or(args.path.clone());
+    let projects = match Workspace::load_from_path(&path)? {
+        Some((root, workspace)) => workspace.member_paths(&root),
+        None if path.join("pyproject.toml").exists() => vec![path.clone()],
+        None => return Err(EchidnaError::NotBundleDirectory(path)),
+    };
+
+    let mut copied = 0;
+    for project in &projects {
+        let wheels = wheels_in(&project.join("dist"))?;
+        if wheels.is_empty() {
+            println!(
+                "  - {}: no wheels in dist/ (run 'echidna build' first)",
+                project.display()
+            );
+            continue;
+        }
+        for wheel in wheels {
+            let file_name = wheel.file_name().unwrap_or_default();
+            let Some(name) = wheel_project(&wheel) else {
+                println!("  - skipping {}: not a wheel file name", wheel.display());
+                continue;
+            };
+            let project_dir = args.dir.join(&name);
+            fs::create_dir_all(&project_dir)?;
+            fs::copy(&wheel, project_dir.join(file_name))?;
+            copied += 1;
+        }
+    }
+    if copied == 0 {
+        return Err(EchidnaError::NoWheelFound);
+    }
+
+    let (projects, wheels) = write_index(&args.dir)?;
+    println!(
+        "✓ Indexed {} wheel(s) of {} bundle(s) in {}",
+        wheels,
+        projects,
+        args.dir.display()
+    );
+    println!(
+        "  Serve it with: echidna index serve {}",
+        args.dir.display()
+    );
+    Ok(())
+}
+
+/// Execute the index serve command.
+pub fn serve(args: IndexServeArgs) -> Result<()> {
+    let root = args.dir.canonicalize().unwrap_or(args.dir.clone());
+    if !root.join("index.html").is_file() {
+        return Err(EchidnaError::ConfigError(format!(
+            "{} is not a package index; run 'echidna index build {}' first",
+            root.display(),
+            args.dir.display()
+        )));
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", args.port))?;
+    let url = format!("http://{}/", listener.local_addr()?);
+    println!("Serving {} at {}", root.display(), url);
+    println!(
+        "  Install with: echidna install --index {} --package <bundle>",
+        url
+    );
+    println!("Press Ctrl+C to stop");
+    serve_on(listener, &root)
+}
+
+/// Answer requests for files of the index until the listener fails.
+fn serve_on(listener: TcpListener, root: &Path) -> Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        if let Err(e) = handle(stream, root) {
+            eprintln!("warning: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Answer one HTTP request.
+///
+/// Requests are answered one at a time, so a client that sends nothing is
+/// dropped after [`READ_TIMEOUT`], and one whose request head exceeds
+/// [`MAX_REQUEST_HEAD`] is refused.
+fn handle(stream: TcpStream, root: &Path) -> Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_HEAD));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut complete = false;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        if line == "\r\n" || line == "\n" {
+            complete = true;
+            break;
+        }
+    }
+    let too_large = !complete && reader.get_ref().limit() == 0;
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or("/");
+    let url_path = target.split(['?', '#']).next().unwrap_or("/");
+    let stream = reader.get_mut().get_mut();
+
+    if too_large {
+        respond(
+            stream,
+            "431 Request Header Fields Too Large",
+            "text/plain",
+            b"",
+            true,
+        )?;
+        // Read (a bounded amount of) the rest so that closing does not reset
+        // the connection before the client sees the response
+        stream.shutdown(Shutdown::Write)?;
+        io::copy(&mut stream.take(MAX_REQUEST_HEAD * 8), &mut io::sink())?;
+        return Ok(());
+    }
+
+    if method != "GET" && method != "HEAD" {
+        return respond(stream, "405 Method Not Allowed", "text/plain", b"", true);
+    }
+    let Some(path) = resolve(root, url_path) else {
+        return respond(
+            stream,
+            "404 Not Found",
+            "text/plain",
+            b"Not found\n",
+            method == "GET",
+        );
+    };
+    if path.is_dir() {
+        // Relative links on the page need the trailing slash
+        let location = format!("{}/", url_path);
+        write!(
+            stream,
+            "HTTP/1.1 301 Moved Permanently\r\nLocation: {}\r\nContent-Length: 0\r\n\
+             Connection: close\r\n\r\n",
+            location
+        )?;
+        return Ok(());
+    }
+    let content_type = if path.extension().is_some_and(|e| e == "html") {
+        "text/html; charset=utf-8"
+    } else {
+        "application/octet-stream"
+    };
+    let body = fs::read(&path)?;
+    respond(stream, "200 OK", content_type, &body, method == "GET")
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+    send_body: bool,
+) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    if send_body {
+        stream.write_all(body)?;
+    }
+    stream.flush()?;
+    Ok(())
+}
+
+/// File (or directory without trailing slash) of the index for a URL path;
+/// `None` if it does not exist or leaves the index.
+fn resolve(root: &Path, url_path: &str) -> Option<PathBuf> {
+    let decoded = urlencoding::decode(url_path).ok()?;
+    let mut path = root.to_path_buf();
+    for segment in decoded.split('/').filter(|s| !s.is_empty()) {
+        if segment == "." || segment == ".." || segment.contains('\\') {
+            return None;
+        }
+        path.push(segment);
+    }
+    if path.is_dir() && (decoded.ends_with('/') || path == root) {
+        path.push("index.html");
+    }
+    path.exists().then_some(path)
+}
+
+/// Wheels in a directory, sorted by file name.
+fn wheels_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut wheels: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == "whl"))
+        .collect();
+    wheels.sort();
+    Ok(wheels)
+}
+
+/// PEP 503 normalized project name of a wheel file.
+fn wheel_project(wheel: &Path) -> Option<String> {
+    let name = wheel.file_name()?.to_str()?.split('-').next()?;
+    (!name.is_empty()).then(|| normalize_name(name))
+}
+
+/// PEP 503 name normalization: lowercase, runs of `-`, `_`, `.` become `-`.
+pub fn normalize_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.extend(c.to_lowercase());
+        }
+    }
+    normalized
+}
+
+/// Write the root and project pages for the wheels in an index directory;
+/// returns the numbers of projects and wheels.
+fn write_index(dir: &Path) -> Result<(usize, usize)> {
+    let mut projects = Vec::new();
+    let mut total = 0;
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    entries.sort();
+
+    for project_dir in entries {
+        let wheels = wheels_in(&project_dir)?;
+        if wheels.is_empty() {
+            continue;
+        }
+        let name = project_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut links = Vec::new();
+        for wheel in &wheels {
+            let file_name = wheel
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            links.push(format!(
+                "<a href=\"{0}#sha256={1}\">{0}</a>",
+                file_name,
+                sha256_file(wheel)?
+            ));
+        }
+        fs::write(
+            project_dir.join("index.html"),
+            page(&format!("Links for {}", name), &links),
+        )?;
+        total += wheels.len();
+        projects.push(format!("<a href=\"{0}/\">{0}</a>", name));
+    }
+
+    fs::write(dir.join("index.html"), page("Simple index", &projects))?;
+    Ok((projects.len(), total))
+}
+
+/// A PEP 503 page listing links.
+fn page(title: &str, links: &[String]) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n  <head>\n    \
+         <meta name=\"pypi:repository-version\" content=\"1.0\">\n    \
+         <title>{0}</title>\n  </head>\n  <body>\n    <h1>{0}</h1>\n",
+        title
+    );
+    for link in links {
+        html.push_str(&format!("    {}<br>\n", link));
+    }
+    html.push_str("  </body>\n</html>\n");
+    html
+}
+
+/// Hex SHA-256 digest of a file.
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("ChimeraX_My.Tool"), "chimerax-my-tool");
+        assert_eq!(normalize_name("ChimeraX--Test"), "chimerax-test");
+        assert_eq!(
+            wheel_project(Path::new("dist/ChimeraX_Test-0.1.0-py3-none-any.whl")),
+            Some("chimerax-test".into())
+        );
+    }
+
+    #[test]
+    fn test_write_index() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("chimerax-test");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("ChimeraX_Test-0.1.0-py3-none-any.whl"), "abc").unwrap();
+        fs::create_dir_all(temp.path().join("empty")).unwrap();
+
+        assert_eq!(write_index(temp.path()).unwrap(), (1, 1));
+        let root = fs::read_to_string(temp.path().join("index.html")).unwrap();
+        assert!(root.contains("<a href=\"chimerax-test/\">chimerax-test</a>"));
+        assert!(!root.contains("empty"));
+        let links = fs::read_to_string(project.join("index.html")).unwrap();
+        assert!(links.contains(
+            "<a href=\"ChimeraX_Test-0.1.0-py3-none-any.whl#sha256=\
+             ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad\">"
+        ));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("chimerax-test")).unwrap();
+        fs::write(root.join("index.html"), "").unwrap();
+        fs::write(root.join("chimerax-test/index.html"), "").unwrap();
+
+        assert_eq!(resolve(root, "/"), Some(root.join("index.html")));
+        assert_eq!(
+            resolve(root, "/chimerax-test/"),
+            Some(root.join("chimerax-test/index.html"))
+        );
+        assert_eq!(
+            resolve(root, "/chimerax-test"),
+            Some(root.join("chimerax-test"))
+        );
+        assert_eq!(resolve(root, "/../etc/passwd"), None);
+        assert_eq!(resolve(root, "/%2e%2e/x"), None);
+        assert_eq!(resolve(root, "/missing/"), None);
+    }
+}
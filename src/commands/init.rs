@@ -85,6 +85,7 @@ pub fn execute(args: InitArgs) -> Result<()> {
     println!("  cd {}", target_dir.display());
     println!("  echidna build      # Build the wheel");
     println!("  echidna install    # Install to ChimeraX");
+    println!("  echidna test       # Run the tests in tests/");
     println!("  echidna run        # Build, install, and launch ChimeraX");
 
     Ok(())
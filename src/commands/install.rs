@@ -10,6 +10,8 @@ pub struct InstallArgs {
     pub path: PathBuf,
     pub wheel: Option<PathBuf>,
     pub user: bool,
+    /// Package index URL and the bundle to install from it
+    pub index: Option<(String, String)>,
     pub chimerax: PathBuf,
     pub verbosity: Verbosity,
 }
@@ -18,6 +20,15 @@ pub struct InstallArgs {
 pub fn execute(args: InstallArgs) -> Result<()> {
     let executor = ChimeraXExecutor::new(args.chimerax, args.verbosity);
 
+    if let Some((index, name)) = &args.index {
+        println!("Installing {} from {}...", name, index);
+        executor.pip_install(&pip_index_args(name, index, args.user))?;
+        println!("Installation successful!");
+        println!();
+        println!("Restart ChimeraX to use the bundle.");
+        return Ok(());
+    }
+
     // Determine the wheel to install
     let wheel = match args.wheel {
         Some(w) => {
@@ -50,3 +61,53 @@ pub fn execute(args: InstallArgs) -> Result<()> {
 
     Ok(())
 }
+
+/// pip arguments installing a bundle from one index only, so a package of the
+/// same name on PyPI cannot take its place.
+fn pip_index_args(name: &str, index: &str, user: bool) -> Vec<String> {
+    let mut args = vec!["--upgrade".to_string(), "--index-url".to_string()];
+    args.push(index.to_string());
+    // pip refuses plain HTTP hosts other than localhost unless trusted
+    if let Some(host) = index
+        .strip_prefix("http://")
+        .and_then(|rest| rest.split(['/', ':']).next())
+        .filter(|host| !host.is_empty())
+    {
+        args.extend(["--trusted-host".to_string(), host.to_string()]);
+    }
+    if user {
+        args.push("--user".to_string());
+    }
+    args.push(name.to_string());
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pip_index_args() {
+        assert_eq!(
+            pip_index_args("ChimeraX-Test", "http://lab-server:8000/", true),
+            vec![
+                "--upgrade",
+                "--index-url",
+                "http://lab-server:8000/",
+                "--trusted-host",
+                "lab-server",
+                "--user",
+                "ChimeraX-Test"
+            ]
+        );
+        assert_eq!(
+            pip_index_args("ChimeraX-Test", "https://pypi.example/simple/", false),
+            vec![
+                "--upgrade",
+                "--index-url",
+                "https://pypi.example/simple/",
+                "ChimeraX-Test"
+            ]
+        );
+    }
+}
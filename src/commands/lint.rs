@@ -0,0 +1,410 @@
+//! `echidna lint` command implementation.
+//!
+//! Scans `src/**/*.py` for imports, attribute accesses, and
+//! `BundleAPI` methods that the embedded [`api_db`] lists as deprecated or
+//! removed, relative to the project's `min-chimerax-version` and the targeted
+//! ChimeraX release.
+
+use crate::commands::api_db::{self, ApiChange, ApiKind, API_CHANGES};
+use crate::commands::python_source::{parse_imports, PythonClass, Sources};
+use crate::commands::validate::{apply_rule_levels, ValidationResult};
+use crate::config::ValidateConfig;
+use crate::diagnostics::{self, rules, Diagnostic, Location, OutputFormat};
+use crate::error::{EchidnaError, Result};
+use crate::pep440::Version;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Arguments for the lint command.
+pub struct LintArgs {
+    /// Project directory
+    pub path: PathBuf,
+    /// ChimeraX release to check against (defaults to the newest known release)
+    pub target: Option<String>,
+    /// Treat warnings as errors
+    pub strict: bool,
+    /// Output format
+    pub format: OutputFormat,
+}
+
+/// Execute the lint command.
+pub fn execute(args: LintArgs) -> Result<()> {
+    let project_dir = args.path.canonicalize().unwrap_or(args.path.clone());
+    let pyproject_path = project_dir.join("pyproject.toml");
+    if !pyproject_path.exists() {
+        return Err(EchidnaError::NotBundleDirectory(project_dir));
+    }
+
+    let target = args
+        .target
+        .clone()
+        .unwrap_or_else(|| api_db::LATEST_CHIMERAX.to_string());
+    if Version::parse(&target).is_none() {
+        return Err(EchidnaError::ConfigError(format!(
+            "Invalid ChimeraX version '{}' (expected e.g. 1.6)",
+            target
+        )));
+    }
+    let pyproject: toml::Value = toml::from_str(&std::fs::read_to_string(&pyproject_path)?)?;
+    let min_version = min_chimerax_version(&pyproject);
+
+    let mut result = ValidationResult::default();
+    check_api(&project_dir, min_version.as_deref(), &target, &mut result)?;
+    apply_rule_levels(&mut result, &ValidateConfig::load(&project_dir)?)?;
+
+    let is_valid = if args.strict {
+        result.is_valid_strict()
+    } else {
+        result.is_valid()
+    };
+
+    match args.format {
+        OutputFormat::Text => {
+            println!(
+                "Checking ChimeraX API usage in {}...",
+                project_dir.display()
+            );
+            println!(
+                "  target ChimeraX {}, min-chimerax-version {} (API database revision {})",
+                target,
+                min_version.as_deref().unwrap_or("not set"),
+                api_db::DATABASE_VERSION
+            );
+            println!();
+            for diagnostic in &result.diagnostics {
+                println!("{}", diagnostic.render(&project_dir, args.strict));
+            }
+            if result.diagnostics.is_empty() {
+                println!("✓ No deprecated or removed ChimeraX APIs found");
+            } else {
+                let errors = if args.strict {
+                    result.diagnostics.len()
+                } else {
+                    result.errors().count()
+                };
+                let warnings = result.diagnostics.len() - errors;
+                println!(
+                    "{} {} error{}, {} warning{}",
+                    if is_valid { "✓" } else { "✗" },
+                    errors,
+                    if errors == 1 { "" } else { "s" },
+                    warnings,
+                    if warnings == 1 { "" } else { "s" }
+                );
+                println!("  For more information about a diagnostic, run `echidna explain <CODE>`");
+            }
+        }
+        OutputFormat::Json => println!(
+            "{}",
+            diagnostics::to_json(&result.diagnostics, &args.path, args.strict)?
+        ),
+        OutputFormat::Sarif => println!(
+            "{}",
+            diagnostics::to_sarif(&result.diagnostics, &args.path, args.strict)?
+        ),
+    }
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(EchidnaError::ConfigError("lint found errors".into()))
+    }
+}
+
+/// `[chimerax].min-chimerax-version`, if set.
+fn min_chimerax_version(pyproject: &toml::Value) -> Option<String> {
+    pyproject
+        .get("chimerax")?
+        .get("min-chimerax-version")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Check the Python sources for deprecated and removed APIs.
+pub fn check_api(
+    project_dir: &Path,
+    min_version: Option<&str>,
+    target: &str,
+    result: &mut ValidationResult,
+) -> Result<()> {
+    let sources = Sources::load(project_dir)?;
+    let classes = sources.classes();
+    for (file, content) in &sources.files {
+        check_imports(file, content, min_version, target, result);
+        for class in classes.iter().filter(|c| &c.file == file) {
+            if class.inherits(&classes, &|c| c.bases.iter().any(|b| b == "BundleAPI")) {
+                check_bundle_api(class, content, min_version, target, result);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The most specific module or symbol entry matching a dotted name.
+fn find_change(name: &str) -> Option<&'static ApiChange> {
+    API_CHANGES
+        .iter()
+        .filter(|change| matches!(change.kind, ApiKind::Module | ApiKind::Symbol))
+        .filter(|change| {
+            name == change.name
+                || name
+                    .strip_prefix(change.name)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+        .max_by_key(|change| change.name.len())
+}
+
+/// Report deprecated imports and attribute accesses through imported names.
+fn check_imports(
+    file: &Path,
+    content: &str,
+    min_version: Option<&str>,
+    target: &str,
+    result: &mut ValidationResult,
+) {
+    let imports = parse_imports(content);
+    let mut bindings: HashMap<&str, &str> = HashMap::new();
+    // Changes reported at an import are not reported again at each use
+    let mut reported: Vec<&ApiChange> = Vec::new();
+    for import in &imports {
+        if let Some(change) = find_change(&import.name) {
+            let what = format!("import of {}", import.name);
+            if let Some(diagnostic) = finding(change, &what, min_version, target) {
+                result.add(diagnostic.at(Location::line(file, import.lines.0, content)));
+            }
+            reported.push(change);
+        }
+        bindings.insert(&import.binding, &import.target);
+    }
+
+    let is_import = |line: usize| {
+        imports
+            .iter()
+            .any(|i| (i.lines.0..=i.lines.1).contains(&line))
+    };
+    let access = Regex::new(r"\b([A-Za-z_]\w*)((?:\.[A-Za-z_]\w*)+)").unwrap();
+    for (i, line) in content.lines().enumerate() {
+        if is_import(i + 1) {
+            continue;
+        }
+        let mut reported = reported.clone();
+        for caps in access.captures_iter(line) {
+            let Some(name) = bindings.get(&caps[1]) else {
+                continue;
+            };
+            let full = format!("{}{}", name, &caps[2]);
+            let Some(change) = find_change(&full) else {
+                continue;
+            };
+            if reported.iter().any(|c| std::ptr::eq(*c, change)) {
+                continue;
+            }
+            reported.push(change);
+            if let Some(diagnostic) = finding(change, &full, min_version, target) {
+                result.add(diagnostic.at(Location::line(file, i + 1, content)));
+            }
+        }
+    }
+}
+
+/// Report deprecated `BundleAPI` methods and signatures in a subclass.
+fn check_bundle_api(
+    class: &PythonClass,
+    content: &str,
+    min_version: Option<&str>,
+    target: &str,
+    result: &mut ValidationResult,
+) {
+    // Methods are indented; parameters may span lines
+    let def = Regex::new(r"(?m)^[ \t]+def\s+(\w+)\s*\(([^)]*)\)").unwrap();
+    for caps in def.captures_iter(&class.body) {
+        let method = &caps[1];
+        let params: Vec<&str> = caps[2]
+            .split(',')
+            .map(|p| p.split([':', '=']).next().unwrap_or("").trim())
+            .filter(|p| !p.is_empty() && *p != "self" && *p != "cls" && !p.starts_with('*'))
+            .collect();
+
+        let change = API_CHANGES.iter().find(|change| {
+            change.name == method
+                && match change.kind {
+                    ApiKind::Method => true,
+                    ApiKind::Signature { params: n } => params.len() == n,
+                    _ => false,
+                }
+        });
+        let Some(change) = change else {
+            continue;
+        };
+        let what = format!("BundleAPI.{}({})", method, params.join(", "));
+        if let Some(diagnostic) = finding(change, &what, min_version, target) {
+            let offset = caps.get(0).map_or(0, |m| m.start());
+            let line = class.line + class.body[..offset].matches('\n').count() + 1;
+            result.add(diagnostic.at(Location::line(&class.file, line, content)));
+        }
+    }
+}
+
+/// A diagnostic for a use of a changed API, if it is deprecated at `target`.
+fn finding(
+    change: &ApiChange,
+    what: &str,
+    min_version: Option<&str>,
+    target: &str,
+) -> Option<Diagnostic> {
+    let removed = change
+        .removed_in
+        .filter(|removed| api_db::at_least(target, removed));
+    let (rule, mut message) = if let Some(removed) = removed {
+        (
+            &rules::REMOVED_API,
+            format!("{} was removed in ChimeraX {}", what, removed),
+        )
+    } else if api_db::at_least(target, change.deprecated_in) {
+        let mut message = format!(
+            "{} is deprecated since ChimeraX {}",
+            what, change.deprecated_in
+        );
+        if let Some(removed) = change.removed_in {
+            message.push_str(&format!(" and removed in {}", removed));
+        }
+        (&rules::DEPRECATED_API, message)
+    } else {
+        return None;
+    };
+
+    message.push_str(&format!("; use {}", change.replacement));
+    if let (Some(min), Some(since)) = (min_version, change.replacement_since) {
+        if !api_db::at_least(min, since) {
+            message.push_str(&format!(
+                " (requires ChimeraX {}, but min-chimerax-version is {})",
+                since, min
+            ));
+        }
+    }
+    Some(Diagnostic::new(rule, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn lint(source: &str, min_version: Option<&str>, target: &str) -> Vec<(String, usize)> {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("src")).unwrap();
+        fs::write(temp.path().join("src/__init__.py"), source).unwrap();
+
+        let mut result = ValidationResult::default();
+        check_api(temp.path(), min_version, target, &mut result).unwrap();
+        result
+            .diagnostics
+            .iter()
+            .map(|d| {
+                (
+                    format!("{} {}", d.rule.code, d.message),
+                    d.location.as_ref().unwrap().line,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_removed_module_import() {
+        let findings = lint(
+            "from chimerax.core.atomic import Structure\n\ndef f(s):\n    return Structure.x\n",
+            Some("1.0"),
+            "1.9",
+        );
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].0.starts_with(
+            "CX070 import of chimerax.core.atomic.Structure was removed in ChimeraX 0.8; use chimerax.atomic"
+        ));
+        assert_eq!(findings[0].1, 1);
+    }
+
+    #[test]
+    fn test_symbol_access() {
+        let findings = lint(
+            "from chimerax.core import io\n\ndef f(session):\n    io.open_data(session, 'x')\n",
+            None,
+            "1.1",
+        );
+        let messages: Vec<_> = findings.iter().map(|f| (f.0.as_str(), f.1)).collect();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].0.starts_with(
+            "CX071 import of chimerax.core.io is deprecated since ChimeraX 1.1 and removed in 1.2"
+        ));
+        assert!(messages[1]
+            .0
+            .starts_with("CX071 chimerax.core.io.open_data is deprecated"));
+        assert_eq!(messages[1].1, 4);
+    }
+
+    #[test]
+    fn test_target_version() {
+        let source = "from PyQt5.QtWidgets import QWidget\n";
+        assert!(lint(source, None, "1.3").is_empty());
+        assert!(lint(source, None, "1.4")[0].0.starts_with("CX070"));
+    }
+
+    #[test]
+    fn test_bundle_api_signatures() {
+        let source = r#"
+from chimerax.core.toolshed import BundleAPI
+
+class _API(BundleAPI):
+    @staticmethod
+    def start_tool(session, tool_name):
+        pass
+
+    @staticmethod
+    def register_command(bi, ci, logger):
+        pass
+
+    @staticmethod
+    def open_file(session, path,
+                  format_name):
+        pass
+"#;
+        let findings = lint(source, Some("0.7"), "1.9");
+        assert_eq!(findings.len(), 2);
+        assert!(findings[0].0.starts_with(
+            "CX071 BundleAPI.start_tool(session, tool_name) is deprecated since ChimeraX 0.8; use api_version = 1"
+        ));
+        assert!(findings[0]
+            .0
+            .ends_with("(requires ChimeraX 0.8, but min-chimerax-version is 0.7)"));
+        assert_eq!(findings[0].1, 6);
+        assert!(findings[1]
+            .0
+            .contains("BundleAPI.open_file(session, path, format_name)"));
+        assert_eq!(findings[1].1, 14);
+    }
+
+    #[test]
+    fn test_bundle_api_other_classes() {
+        let source = r#"
+from chimerax.core.toolshed import BundleAPI
+from chimerax.core.tools import ToolInstance
+
+class Viewer(ToolInstance):
+    def open_file(self, path):
+        pass
+
+class _API(BundleAPI):
+    pass
+
+class _SubAPI(_API):
+    @staticmethod
+    def open_file(session, path):
+        pass
+"#;
+        let findings = lint(source, None, "1.9");
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].0.contains("BundleAPI.open_file(session, path)"));
+        assert_eq!(findings[0].1, 14);
+    }
+}
@@ -1,18 +1,38 @@
 //! Command implementations.
 
+pub mod api_db;
 pub mod build;
+pub mod bundle_schema;
+pub mod changelog;
 pub mod clean;
 pub mod debug;
 pub mod docs;
+pub mod explain;
+pub mod extension_check;
+pub mod fix;
+pub mod flaky;
+pub mod glob;
+pub mod index;
 pub mod info;
 pub mod init;
 pub mod install;
+pub mod lint;
+pub mod package_data_check;
 pub mod publish;
 pub mod python;
+pub mod python_source;
+pub mod release;
 pub mod run;
+pub mod script_tests;
+pub mod session_check;
 pub mod setup_ide;
+pub mod snapshot;
+pub mod source_check;
+pub mod test_select;
 pub mod testing;
 pub mod validate;
 pub mod version;
+pub mod version_sync;
+pub mod visual;
 pub mod watch;
 pub mod workspace;
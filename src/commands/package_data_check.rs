@@ -0,0 +1,267 @@
+//! Check package-data, extra-files, and data-files patterns against the project.
+//!
+//! BundleBuilder copies only the files these patterns match into the wheel, so
+//! a typo ships a bundle without its icons or HTML pages. Patterns that match
+//! nothing are errors, and non-Python files under `src/` that no pattern
+//! covers are warnings.
+
+use crate::commands::bundle_schema::table_path;
+use crate::commands::glob::{glob_files, is_glob};
+use crate::commands::validate::{package_source_dir, ValidationResult};
+use crate::diagnostics::{rules, Diagnostic};
+use crate::error::Result;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Tables of file patterns: package-data patterns are relative to the package
+/// directory named by the key, the others to the project directory.
+const TABLES: &[&str] = &["package-data", "extra-files", "data-files"];
+
+/// Extensions of files under `src/` that are not shipped as data: Python
+/// sources, C/C++ extension sources, and build products.
+const NON_DATA_EXTENSIONS: &[&str] = &[
+    "py", "pyi", "pyc", "pyo", "c", "cc", "cpp", "cxx", "h", "hh", "hpp", "hxx", "m", "mm", "pyx",
+    "pxd", "o", "obj", "so", "pyd", "dll", "dylib", "lib", "a",
+];
+
+/// Check the file pattern tables of `[chimerax]`.
+pub fn check(
+    project_dir: &Path,
+    chimerax: &toml::Value,
+    result: &mut ValidationResult,
+) -> Result<()> {
+    let mut covered = HashSet::new();
+
+    for table in TABLES {
+        let Some(entries) = chimerax.get(*table).and_then(|t| t.as_table()) else {
+            continue;
+        };
+        for (key, patterns) in entries {
+            let base = if *table == "package-data" {
+                package_data_dir(project_dir, chimerax, key)
+            } else {
+                project_dir.to_path_buf()
+            };
+            let patterns = patterns.as_array().into_iter().flatten();
+            for pattern in patterns.filter_map(|p| p.as_str()) {
+                let files = matching_files(&base, pattern)?;
+                if files.is_empty() {
+                    let keys = ["chimerax".to_string(), table.to_string()];
+                    result.add(
+                        Diagnostic::new(
+                            &rules::UNMATCHED_FILE_PATTERN,
+                            format!(
+                                "[{}]: '{}' matches no files in {}",
+                                table_path(&keys),
+                                pattern,
+                                report_dir(project_dir, &base.join(pattern_dir(pattern)))
+                            ),
+                        )
+                        .at_key(&["chimerax", table, key]),
+                    );
+                }
+                covered.extend(files);
+            }
+        }
+    }
+
+    for file in glob_files(project_dir, "src/**")? {
+        let relative = file.strip_prefix(project_dir).unwrap_or(&file);
+        if covered.contains(&file) || !is_data_file(relative) {
+            continue;
+        }
+        result.add(Diagnostic::new(
+            &rules::UNPACKAGED_FILE,
+            format!(
+                "{} is not matched by [chimerax.package-data], extra-files, or data-files, \
+                 so it is left out of the wheel",
+                report_dir(project_dir, relative)
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Directory a `[chimerax.package-data]` key refers to: a path relative to the
+/// project (e.g. "src/" or "src/icons"), or a package name such as
+/// "chimerax.mytool.data".
+fn package_data_dir(project_dir: &Path, chimerax: &toml::Value, key: &str) -> PathBuf {
+    let dir = project_dir.join(key);
+    if dir.is_dir() || key.contains('/') {
+        return dir;
+    }
+    let package = chimerax.get("package").and_then(|p| p.as_str());
+    let source_dir = package_source_dir(project_dir, Some(chimerax));
+    if let (Some(package), Some(source_dir)) = (package, source_dir) {
+        if key == package {
+            return source_dir;
+        }
+        if let Some(subpackage) = key
+            .strip_prefix(package)
+            .and_then(|rest| rest.strip_prefix('.'))
+        {
+            return source_dir.join(subpackage.replace('.', "/"));
+        }
+    }
+    dir
+}
+
+/// Files matching a pattern; a literal path matches itself (or the files of
+/// a directory).
+fn matching_files(base: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    if is_glob(pattern) {
+        return glob_files(base, pattern);
+    }
+    let path = base.join(pattern);
+    if path.is_dir() {
+        glob_files(&path, "**")
+    } else if path.is_file() {
+        Ok(vec![path])
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// The part of a pattern before its first wildcard segment.
+fn pattern_dir(pattern: &str) -> String {
+    let segments: Vec<&str> = pattern.split('/').collect();
+    segments[..segments.len() - 1]
+        .iter()
+        .take_while(|segment| !is_glob(segment))
+        .copied()
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Whether a file (relative to the project) is shipped as data: not hidden,
+/// a cache, or a source or build product.
+fn is_data_file(path: &Path) -> bool {
+    let hidden = path.components().any(|c| {
+        let name = c.as_os_str().to_string_lossy();
+        name.starts_with('.') || name == "__pycache__"
+    });
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    !hidden && !NON_DATA_EXTENSIONS.contains(&extension.as_str())
+}
+
+/// A path relative to the project, with `/` separators.
+fn report_dir(project_dir: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(project_dir).unwrap_or(path);
+    let parts: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect();
+    if parts.is_empty() {
+        "the project directory".into()
+    } else {
+        parts.join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn check_project(chimerax: &str, files: &[&str]) -> ValidationResult {
+        let temp = TempDir::new().unwrap();
+        for name in files {
+            let path = temp.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        let chimerax: toml::Value = toml::from_str(chimerax).unwrap();
+        let mut result = ValidationResult::default();
+        check(temp.path(), &chimerax, &mut result).unwrap();
+        result
+    }
+
+    fn messages(result: &ValidationResult) -> Vec<(&str, &str)> {
+        result
+            .diagnostics
+            .iter()
+            .map(|d| (d.rule.code, d.message.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_patterns_cover_files() {
+        let result = check_project(
+            r#"
+package = "chimerax.mytool"
+
+[package-data]
+"src/" = ["icons/*.png", "docs/**"]
+
+[extra-files]
+"src/data" = ["data/params.txt"]
+"#,
+            &[
+                "src/__init__.py",
+                "src/icons/logo.png",
+                "src/docs/user/index.html",
+                "src/_ext.cpp",
+                "src/__pycache__/x.txt",
+                "src/.DS_Store",
+                "data/params.txt",
+            ],
+        );
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+    }
+
+    #[test]
+    fn test_unmatched_and_uncovered() {
+        let result = check_project(
+            r#"
+package = "chimerax.mytool"
+
+[package-data]
+"chimerax.mytool" = ["icons/*.svg"]
+
+[data-files]
+"share" = ["share/missing.dat"]
+"#,
+            &["src/__init__.py", "src/icons/logo.png"],
+        );
+        assert_eq!(
+            messages(&result),
+            vec![
+                (
+                    "CX053",
+                    "[chimerax.package-data]: 'icons/*.svg' matches no files in src/icons"
+                ),
+                (
+                    "CX053",
+                    "[chimerax.data-files]: 'share/missing.dat' matches no files in share"
+                ),
+                (
+                    "CX054",
+                    "src/icons/logo.png is not matched by [chimerax.package-data], extra-files, \
+                     or data-files, so it is left out of the wheel"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_package_data_dir() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("src")).unwrap();
+        fs::write(temp.path().join("src/__init__.py"), "").unwrap();
+        let chimerax: toml::Value = toml::from_str("package = \"chimerax.mytool\"").unwrap();
+
+        assert_eq!(
+            package_data_dir(temp.path(), &chimerax, "chimerax.mytool.data"),
+            temp.path().join("src/data")
+        );
+        assert_eq!(
+            package_data_dir(temp.path(), &chimerax, "src/"),
+            temp.path().join("src/")
+        );
+    }
+}
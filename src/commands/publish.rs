@@ -1,10 +1,15 @@
 //! `echidna publish` command implementation.
 //!
-//! Validates and guides bundle submission to ChimeraX Toolshed.
+//! Validates a bundle, then opens the Toolshed submission page for the wheel,
+//! or uploads it to a Toolshed with an upload API configured by the user.
 
 use crate::commands::build::find_newest_wheel;
+use crate::commands::changelog;
 use crate::commands::validate::{validate_bundle, ValidationResult};
+use crate::config::UserConfig;
+use crate::diagnostics::{self, rules, Diagnostic, OutputFormat};
 use crate::error::{EchidnaError, Result};
+use crate::toolshed::{self, ToolshedClient};
 use std::path::{Path, PathBuf};
 
 /// ChimeraX Toolshed submission URL.
@@ -16,6 +21,14 @@ pub struct PublishArgs {
     pub path: PathBuf,
     /// Dry run (validate without publishing)
     pub dry_run: bool,
+    /// Output format of the dry-run checks
+    pub format: OutputFormat,
+    /// Toolshed upload API base URL (overrides the user configuration)
+    pub toolshed_url: Option<String>,
+    /// Open the Toolshed submission page even if an upload URL is configured
+    pub browser: bool,
+    /// Send the version's CHANGELOG.md section as release notes
+    pub release_notes: bool,
 }
 
 /// Result of publish preparation.
@@ -36,12 +49,57 @@ impl PublishPreparation {
     pub fn is_ready(&self) -> bool {
         self.validation.is_valid() && self.has_license && self.wheel_path.is_some()
     }
+
+    /// Validation diagnostics followed by the publish checks that failed.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.validation.diagnostics.clone();
+        if !self.has_license {
+            diagnostics.push(Diagnostic::new(
+                &rules::MISSING_LICENSE,
+                "LICENSE file not found (required for Toolshed)",
+            ));
+        }
+        if !self.has_readme {
+            diagnostics.push(Diagnostic::new(
+                &rules::MISSING_README,
+                "README file not found (recommended)",
+            ));
+        }
+        if self.wheel_path.is_none() {
+            diagnostics.push(Diagnostic::new(
+                &rules::MISSING_WHEEL,
+                "No wheel found in dist/. Run 'echidna build' first.",
+            ));
+        }
+        diagnostics
+    }
 }
 
 /// Execute the publish command.
 pub fn execute(args: PublishArgs) -> Result<()> {
     let project_dir = args.path.canonicalize().unwrap_or(args.path.clone());
 
+    if args.format != OutputFormat::Text {
+        if !args.dry_run {
+            return Err(EchidnaError::ConfigError(
+                "--format is only supported with --dry-run".into(),
+            ));
+        }
+        let prep = prepare_for_publish(&project_dir)?;
+        let diagnostics = prep.diagnostics();
+        let output = match args.format {
+            OutputFormat::Sarif => diagnostics::to_sarif(&diagnostics, &args.path, false)?,
+            _ => diagnostics::to_json(&diagnostics, &args.path, false)?,
+        };
+        println!("{}", output);
+        if !prep.is_ready() {
+            return Err(EchidnaError::ConfigError(
+                "bundle not ready for submission".into(),
+            ));
+        }
+        return Ok(());
+    }
+
     println!("Preparing bundle for Toolshed submission...");
     println!();
 
@@ -72,15 +130,103 @@ pub fn execute(args: PublishArgs) -> Result<()> {
         ));
     }
 
-    // Open Toolshed submission page
+    let Some(wheel) = prep.wheel_path else {
+        return Err(EchidnaError::NoWheelFound);
+    };
     println!();
+    let user = UserConfig::load()?;
+    let url = match toolshed::resolve_url(args.toolshed_url, &user) {
+        Some(url) if !args.browser => url,
+        // The public Toolshed takes submissions through its web form only
+        _ => {
+            if args.release_notes {
+                println!(
+                    "Note: release notes are only sent with API uploads; add them in the form"
+                );
+            }
+            return open_submission_page(&wheel);
+        }
+    };
+    upload(&project_dir, &wheel, &url, &user, args.release_notes)
+}
+
+/// Check that the version is new on the Toolshed and upload the wheel.
+fn upload(
+    project_dir: &Path,
+    wheel: &Path,
+    url: &str,
+    user: &UserConfig,
+    release_notes: bool,
+) -> Result<()> {
+    let token = toolshed::resolve_token(user).ok_or_else(|| {
+        EchidnaError::ConfigError(format!(
+            "No Toolshed API token. Set {} or [toolshed].token in {}",
+            toolshed::TOKEN_ENV,
+            UserConfig::path()
+                .map(|p| p.display().to_string())
+                .unwrap_or_else(|| "the user configuration".into())
+        ))
+    })?;
+    let client = ToolshedClient::new(url, Some(token));
+
+    let (name, version) = wheel_name_version(wheel).ok_or_else(|| {
+        EchidnaError::ConfigError(format!(
+            "Cannot read the bundle name and version from {}",
+            wheel.display()
+        ))
+    })?;
+    let notes = if release_notes {
+        if !project_dir.is_dir() {
+            return Err(EchidnaError::ConfigError(
+                "--release-notes needs the project directory, not a wheel".into(),
+            ));
+        }
+        Some(changelog::release_notes(project_dir, &version)?)
+    } else {
+        None
+    };
+
+    println!("Checking {} {} on {}...", name, version, client.base_url());
+    if client.version_exists(&name, &version)? {
+        return Err(EchidnaError::Toolshed(format!(
+            "{} {} is already published on {}; bump the version first",
+            name,
+            version,
+            client.base_url()
+        )));
+    }
+
+    println!("Uploading {}...", wheel.display());
+    let submission = client.upload(wheel, notes.as_deref())?;
+    if !submission.is_accepted() {
+        return Err(EchidnaError::Toolshed(format!(
+            "submission {}: {}",
+            submission.status,
+            submission.message.as_deref().unwrap_or("no reason given")
+        )));
+    }
+
+    println!();
+    println!(
+        "✓ Submitted {} {} (status: {})",
+        name, version, submission.status
+    );
+    if let Some(message) = &submission.message {
+        println!("  {}", message);
+    }
+    if let Some(url) = &submission.url {
+        println!("  {}", url);
+    }
+    Ok(())
+}
+
+/// Open the Toolshed submission page for a manual upload.
+fn open_submission_page(wheel: &Path) -> Result<()> {
     println!("Opening Toolshed submission page...");
     println!("  URL: {}", TOOLSHED_SUBMIT_URL);
     println!();
     println!("Upload the wheel file:");
-    if let Some(ref wheel) = prep.wheel_path {
-        println!("  {}", wheel.display());
-    }
+    println!("  {}", wheel.display());
 
     open::that(TOOLSHED_SUBMIT_URL).map_err(|e| {
         EchidnaError::Io(std::io::Error::other(format!(
@@ -92,6 +238,16 @@ pub fn execute(args: PublishArgs) -> Result<()> {
     Ok(())
 }
 
+/// Published bundle name and version from a wheel file name
+/// (`ChimeraX_MyTool-0.1.0-py3-none-any.whl` gives `ChimeraX-MyTool`, `0.1.0`).
+fn wheel_name_version(wheel: &Path) -> Option<(String, String)> {
+    let stem = wheel.file_name()?.to_str()?.strip_suffix(".whl")?;
+    let mut parts = stem.split('-');
+    let name = parts.next().filter(|n| !n.is_empty())?;
+    let version = parts.next().filter(|v| !v.is_empty())?;
+    Some((name.replace('_', "-"), version.to_string()))
+}
+
 /// Prepare bundle for publishing by running all checks.
 pub fn prepare_for_publish(project_dir: &Path) -> Result<PublishPreparation> {
     // Run standard validation
@@ -117,13 +273,13 @@ pub fn prepare_for_publish(project_dir: &Path) -> Result<PublishPreparation> {
 /// Print preparation results.
 fn print_preparation_results(prep: &PublishPreparation) {
     // Validation errors
-    for error in &prep.validation.errors {
-        println!("  ✗ {}", error);
+    for error in prep.validation.errors() {
+        println!("  ✗ {} [{}]", error.message, error.rule.code);
     }
 
     // Validation warnings
-    for warning in &prep.validation.warnings {
-        println!("  ⚠ {}", warning);
+    for warning in prep.validation.warnings() {
+        println!("  ⚠ {} [{}]", warning.message, warning.rule.code);
     }
 
     // License check
@@ -317,4 +473,31 @@ categories = ["General"]
         assert!(prep.wheel_path.is_some());
         assert!(prep.is_ready());
     }
+
+    #[test]
+    fn test_wheel_name_version() {
+        assert_eq!(
+            wheel_name_version(Path::new(
+                "dist/ChimeraX_My_Tool-1.2.0-cp311-cp311-linux_x86_64.whl"
+            )),
+            Some(("ChimeraX-My-Tool".into(), "1.2.0".into()))
+        );
+        assert_eq!(wheel_name_version(Path::new("dist/notawheel.zip")), None);
+    }
+
+    #[test]
+    fn test_preparation_diagnostics() {
+        let temp = TempDir::new().unwrap();
+        create_valid_bundle(temp.path());
+        fs::write(temp.path().join("README.md"), "# Test").unwrap();
+
+        let prep = prepare_for_publish(temp.path()).unwrap();
+        let codes: Vec<&str> = prep
+            .diagnostics()
+            .iter()
+            .filter(|d| d.severity == diagnostics::Severity::Error)
+            .map(|d| d.rule.code)
+            .collect();
+        assert_eq!(codes, vec!["CX050", "CX052"]);
+    }
 }
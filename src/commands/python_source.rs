@@ -0,0 +1,420 @@
+//! Lightweight scanning of a bundle's Python source.
+//!
+//! No Python parser: functions, classes, and imports are found line by line
+//! with regular expressions, which is enough for the conventional layout of
+//! bundle code. Shared by the source, session, and API checks and by test
+//! selection in `echidna watch --test`.
+
+use crate::diagnostics::Location;
+use crate::error::Result;
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// A `def` line: indentation and function name.
+static DEF: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\s*)def\s+(\w+)\s*\(").unwrap());
+
+/// A `class` line: indentation, class name, and base list.
+static CLASS_DEF: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\s*)class\s+(\w+)\s*(?:\(([^)]*)\))?\s*:").unwrap());
+
+/// A method definition in a class body.
+static METHOD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?m)^\s*(?:async\s+)?def\s+(\w+)").unwrap());
+
+/// An `import a, b as c` line.
+static IMPORT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*import\s+(.+)$").unwrap());
+
+/// A `from module import ...` line.
+static FROM_IMPORT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*from\s+([A-Za-z_][\w.]*)\s+import\s+(.+)$").unwrap());
+
+/// A triple-quoted string.
+static DOCSTRING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?s)""".*?"""|'''.*?'''"#).unwrap());
+
+/// A single-line string literal without escapes.
+static STRING_LITERAL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#""([^"\\\n]*)"|'([^'\\\n]*)'"#).unwrap());
+
+/// Python sources of a bundle.
+pub(crate) struct Sources {
+    /// Files relative to the project, with their content
+    pub(crate) files: Vec<(PathBuf, String)>,
+}
+
+impl Sources {
+    /// Read `src/**/*.py`; comment lines are dropped.
+    pub(crate) fn load(project_dir: &Path) -> Result<Self> {
+        let mut files = Vec::new();
+        for path in find_python_files(&project_dir.join("src"))? {
+            let content = fs::read_to_string(&path)?;
+            let relative = path
+                .strip_prefix(project_dir)
+                .unwrap_or(&path)
+                .to_path_buf();
+            files.push((relative, strip_comments(&content)));
+        }
+        Ok(Self { files })
+    }
+
+    /// All definitions of a function (or method) with the given name.
+    pub(crate) fn functions(&self, name: &str) -> Vec<PythonFunction> {
+        let mut functions = Vec::new();
+        for (file, content) in &self.files {
+            let lines: Vec<&str> = content.lines().collect();
+            for (i, line) in lines.iter().enumerate() {
+                let Some(caps) = DEF.captures(line).filter(|caps| &caps[2] == name) else {
+                    continue;
+                };
+                let indent = caps[1].len();
+                let body: Vec<&str> = lines[i + 1..]
+                    .iter()
+                    .take_while(|l| l.trim().is_empty() || indentation(l) > indent)
+                    .copied()
+                    .collect();
+                functions.push(PythonFunction {
+                    file: file.clone(),
+                    location: Location::line(file, i + 1, content),
+                    body: remove_docstrings(&body.join("\n")),
+                });
+            }
+        }
+        functions
+    }
+
+    /// All class definitions.
+    pub(crate) fn classes(&self) -> Vec<PythonClass> {
+        let mut classes = Vec::new();
+        for (file, content) in &self.files {
+            let imports = parse_imports(content);
+            let lines: Vec<&str> = content.lines().collect();
+            for (i, line) in lines.iter().enumerate() {
+                let Some(caps) = CLASS_DEF.captures(line) else {
+                    continue;
+                };
+                let indent = caps[1].len();
+                let body: Vec<&str> = lines[i + 1..]
+                    .iter()
+                    .take_while(|l| l.trim().is_empty() || indentation(l) > indent)
+                    .copied()
+                    .collect();
+                let body = body.join("\n");
+                let qualified: Vec<&str> = caps
+                    .get(3)
+                    .map(|bases| {
+                        bases
+                            .as_str()
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|base| !base.is_empty() && !base.contains('='))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let unqualified = |base: &&str| base.rsplit('.').next().unwrap_or(base).to_string();
+                // `Model` or `models.Model` bound by an import from `chimerax`
+                let from_chimerax = |base: &&&str| {
+                    let root = base.split('.').next().unwrap_or(base);
+                    imports.iter().any(|import| {
+                        import.binding == root
+                            && (import.target == "chimerax"
+                                || import.target.starts_with("chimerax."))
+                    })
+                };
+                classes.push(PythonClass {
+                    name: caps[2].to_string(),
+                    bases: qualified.iter().map(unqualified).collect(),
+                    chimerax_bases: qualified
+                        .iter()
+                        .filter(from_chimerax)
+                        .map(unqualified)
+                        .collect(),
+                    file: file.clone(),
+                    location: Location::line(file, i + 1, content),
+                    line: i + 1,
+                    methods: METHOD
+                        .captures_iter(&remove_docstrings(&body))
+                        .map(|m| m[1].to_string())
+                        .collect(),
+                    body,
+                });
+            }
+        }
+        classes
+    }
+}
+
+/// A function definition found in the source.
+#[derive(Debug)]
+pub(crate) struct PythonFunction {
+    /// File relative to the project
+    pub(crate) file: PathBuf,
+    /// Location of the `def` line
+    pub(crate) location: Location,
+    /// Body without comment lines and triple-quoted strings
+    pub(crate) body: String,
+}
+
+impl PythonFunction {
+    /// Single-line string literals in the body.
+    pub(crate) fn string_literals(&self) -> Vec<String> {
+        string_literals(&self.body)
+    }
+}
+
+/// A class definition found in the source.
+#[derive(Debug)]
+pub(crate) struct PythonClass {
+    pub(crate) name: String,
+    /// Base class names without module prefix
+    pub(crate) bases: Vec<String>,
+    /// Base class names imported from a `chimerax` module
+    pub(crate) chimerax_bases: Vec<String>,
+    /// File relative to the project
+    pub(crate) file: PathBuf,
+    /// Location of the `class` line
+    pub(crate) location: Location,
+    /// 1-based line of the `class` statement
+    pub(crate) line: usize,
+    /// Source of the class body, starting on the line after `class`
+    pub(crate) body: String,
+    /// Names of methods defined in the class body
+    pub(crate) methods: Vec<String>,
+}
+
+impl PythonClass {
+    /// Whether `predicate` holds for this class or one of its bundle base
+    /// classes in `classes`.
+    pub(crate) fn inherits(
+        &self,
+        classes: &[PythonClass],
+        predicate: &dyn Fn(&PythonClass) -> bool,
+    ) -> bool {
+        let mut pending = vec![self];
+        let mut visited = HashSet::new();
+        while let Some(class) = pending.pop() {
+            if !visited.insert(&class.name) {
+                continue;
+            }
+            if predicate(class) {
+                return true;
+            }
+            pending.extend(
+                class
+                    .bases
+                    .iter()
+                    .filter_map(|base| classes.iter().find(|c| &c.name == base)),
+            );
+        }
+        false
+    }
+}
+
+/// A name bound by an import statement.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Import {
+    /// 1-based first and last line of the import statement
+    pub(crate) lines: (usize, usize),
+    /// Fully qualified name of the imported module or symbol
+    pub(crate) name: String,
+    /// Local name it is bound to
+    pub(crate) binding: String,
+    /// Fully qualified name of the bound object (`a` for `import a.b`)
+    pub(crate) target: String,
+}
+
+/// Parse the imports of a Python file (relative imports are skipped).
+///
+/// `from a import *` is recorded as an import of `a` bound to `*`.
+pub(crate) fn parse_imports(content: &str) -> Vec<Import> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut imports = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = i + 1;
+        if let Some(caps) = FROM_IMPORT.captures(lines[i]) {
+            let module = caps[1].to_string();
+            let mut names = uncommented(&caps[2]).to_string();
+            // Parenthesized names may span lines
+            if names.trim_start().starts_with('(') {
+                while !names.contains(')') && i + 1 < lines.len() {
+                    i += 1;
+                    names.push(' ');
+                    names.push_str(uncommented(lines[i]));
+                }
+            }
+            for (name, binding) in import_names(&names) {
+                let name = if name == "*" {
+                    module.clone()
+                } else {
+                    format!("{}.{}", module, name)
+                };
+                imports.push(Import {
+                    lines: (line, i + 1),
+                    target: name.clone(),
+                    name,
+                    binding,
+                });
+            }
+        } else if let Some(caps) = IMPORT.captures(lines[i]) {
+            for (name, alias) in import_names(uncommented(&caps[1])) {
+                // `import a.b` binds `a`; `import a.b as c` binds `c` to `a.b`
+                let (binding, target) = if alias != name {
+                    (alias, name.clone())
+                } else {
+                    let top = name.split('.').next().unwrap_or(&name).to_string();
+                    (top.clone(), top)
+                };
+                imports.push(Import {
+                    lines: (line, line),
+                    name,
+                    binding,
+                    target,
+                });
+            }
+        }
+        i += 1;
+    }
+    imports
+}
+
+/// A line without its trailing comment.
+fn uncommented(line: &str) -> &str {
+    line.split('#').next().unwrap_or("")
+}
+
+/// `(name, binding)` pairs of a comma-separated import list.
+fn import_names(list: &str) -> Vec<(String, String)> {
+    list.replace(['(', ')', '\\'], " ")
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split_whitespace();
+            let name = parts.next()?.to_string();
+            let binding = match (parts.next(), parts.next()) {
+                (Some("as"), Some(alias)) => alias.to_string(),
+                _ => name.clone(),
+            };
+            Some((name, binding))
+        })
+        .collect()
+}
+
+/// Find `*.py` files under `dir` recursively (sorted), skipping `__pycache__`.
+fn find_python_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if dir.is_dir() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                if path.file_name().map(|n| n != "__pycache__").unwrap_or(true) {
+                    files.extend(find_python_files(&path)?);
+                }
+            } else if path.extension().map(|e| e == "py").unwrap_or(false) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Drop lines that contain only a comment.
+fn strip_comments(content: &str) -> String {
+    content
+        .lines()
+        .map(|line| {
+            if line.trim_start().starts_with('#') {
+                ""
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Remove triple-quoted strings (docstrings).
+pub(crate) fn remove_docstrings(content: &str) -> String {
+    DOCSTRING.replace_all(content, "").to_string()
+}
+
+/// Single-line string literals (without escapes) in Python source.
+fn string_literals(content: &str) -> Vec<String> {
+    STRING_LITERAL
+        .captures_iter(content)
+        .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)))
+        .map(|m| m.as_str().to_string())
+        .collect()
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_literals_skip_docstrings() {
+        let body =
+            remove_docstrings("\"\"\"Register 'x'.\"\"\"\nif name == 'a' or name == \"b c\":\n");
+        assert_eq!(string_literals(&body), vec!["a", "b c"]);
+    }
+
+    #[test]
+    fn test_parse_imports() {
+        let imports = parse_imports(
+            "import os, chimerax.core.atomic\nimport numpy as np\nfrom chimerax.core import (\n    io,  # file io\n    commands as cmds,\n)\nfrom . import cmd\nfrom chimerax.map import *\n",
+        );
+        let names: Vec<_> = imports
+            .iter()
+            .map(|i| {
+                (
+                    i.lines,
+                    i.name.as_str(),
+                    i.binding.as_str(),
+                    i.target.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ((1, 1), "os", "os", "os"),
+                ((1, 1), "chimerax.core.atomic", "chimerax", "chimerax"),
+                ((2, 2), "numpy", "np", "numpy"),
+                ((3, 6), "chimerax.core.io", "io", "chimerax.core.io"),
+                (
+                    (3, 6),
+                    "chimerax.core.commands",
+                    "cmds",
+                    "chimerax.core.commands"
+                ),
+                ((8, 8), "chimerax.map", "*", "chimerax.map"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classes() {
+        let sources = Sources {
+            files: vec![(
+                PathBuf::from("src/tool.py"),
+                "from chimerax.core import tools\n\nclass Base(tools.ToolInstance):\n    \"\"\"def hidden(self)\"\"\"\n    def show(self):\n        pass\n\nclass Panel(Base, metaclass=Meta):\n    pass\n"
+                    .to_string(),
+            )],
+        };
+        let classes = sources.classes();
+        assert_eq!(classes.len(), 2);
+        assert_eq!(classes[0].bases, vec!["ToolInstance"]);
+        assert_eq!(classes[0].chimerax_bases, vec!["ToolInstance"]);
+        assert_eq!(classes[0].methods, vec!["show"]);
+        assert_eq!(classes[0].line, 3);
+        assert_eq!(classes[1].bases, vec!["Base"]);
+        assert!(classes[1].chimerax_bases.is_empty());
+        assert!(classes[1].inherits(&classes, &|c| c.methods.iter().any(|m| m == "show")));
+    }
+}
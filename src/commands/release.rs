@@ -0,0 +1,473 @@
+//! `echidna release` command implementation.
+//!
+//! Runs the release steps in order: tests, version bump, CHANGELOG, clean
+//! build, strict validation, Toolshed upload, and git commit and tag. A failed
+//! step stops the release; `--from <step>` resumes it after the problem is fixed.
+
+use crate::chimerax::Verbosity;
+use crate::commands::changelog::{self, CHANGELOG};
+use crate::commands::version::{self, VersionAction};
+use crate::commands::version_sync;
+use crate::commands::{build, publish, testing, validate};
+use crate::config::{Config, UserConfig};
+use crate::diagnostics::OutputFormat;
+use crate::error::{EchidnaError, Result};
+use crate::git;
+use crate::toolshed;
+use clap::ValueEnum;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A step of the release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum ReleaseStep {
+    /// Run the tests
+    Test,
+    /// Bump the version in pyproject.toml and the `[version]` files
+    Version,
+    /// Move the [Unreleased] CHANGELOG entries to the new version
+    Changelog,
+    /// Clean build of the wheel
+    Build,
+    /// Validate with warnings as errors
+    Validate,
+    /// Upload the wheel to the Toolshed
+    Publish,
+    /// Commit the release and tag it
+    Commit,
+}
+
+impl ReleaseStep {
+    const ALL: [ReleaseStep; 7] = [
+        ReleaseStep::Test,
+        ReleaseStep::Version,
+        ReleaseStep::Changelog,
+        ReleaseStep::Build,
+        ReleaseStep::Validate,
+        ReleaseStep::Publish,
+        ReleaseStep::Commit,
+    ];
+}
+
+impl fmt::Display for ReleaseStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            ReleaseStep::Test => "test",
+            ReleaseStep::Version => "version",
+            ReleaseStep::Changelog => "changelog",
+            ReleaseStep::Build => "build",
+            ReleaseStep::Validate => "validate",
+            ReleaseStep::Publish => "publish",
+            ReleaseStep::Commit => "commit",
+        })
+    }
+}
+
+/// Arguments for the release command.
+pub struct ReleaseArgs {
+    /// Project directory
+    pub path: PathBuf,
+    /// Version bump; defaults to a patch bump (ignored when resuming after
+    /// the version step)
+    pub action: Option<VersionAction>,
+    /// First step to run
+    pub from: ReleaseStep,
+    /// Show the steps without running them
+    pub dry_run: bool,
+    /// Skip the Toolshed upload
+    pub no_publish: bool,
+    /// Write the CHANGELOG section from the git history instead of the
+    /// [Unreleased] entries
+    pub generate_changelog: bool,
+    /// Toolshed base URL
+    pub toolshed_url: Option<String>,
+    /// ChimeraX executable (needed for the tests and the build)
+    pub chimerax: Option<PathBuf>,
+    /// Verbosity level
+    pub verbosity: Verbosity,
+}
+
+/// Execute the release command.
+pub fn execute(args: ReleaseArgs) -> Result<()> {
+    let project_dir = args.path.canonicalize().unwrap_or(args.path.clone());
+    if !project_dir.join("pyproject.toml").exists() {
+        return Err(EchidnaError::NotBundleDirectory(project_dir));
+    }
+
+    let current = version::read_version(&project_dir)?;
+    let version = if args.from <= ReleaseStep::Version {
+        // Fail before the tests if the version files are out of sync
+        let mismatches = version_sync::locate(&project_dir)?
+            .iter()
+            .filter(|location| !version_sync::same_version(&location.version, &current))
+            .count();
+        if mismatches > 0 {
+            return Err(EchidnaError::VersionMismatch {
+                count: mismatches,
+                version: current,
+            });
+        }
+        let action = args.action.clone().unwrap_or(VersionAction::BumpPatch);
+        version::compute_new_version(&action, &current)?
+    } else if args.action.is_some() {
+        return Err(EchidnaError::ConfigError(format!(
+            "The version was already bumped; resuming from '{}' releases {} (omit the version argument)",
+            args.from, current
+        )));
+    } else {
+        current.clone()
+    };
+
+    check_git(&project_dir, args.from, &version)?;
+
+    let steps: Vec<ReleaseStep> = ReleaseStep::ALL
+        .into_iter()
+        .filter(|step| *step >= args.from)
+        .filter(|step| !(args.no_publish && *step == ReleaseStep::Publish))
+        .collect();
+
+    if args.dry_run {
+        println!("Release plan for {} (dry run):", version);
+        for (i, step) in steps.iter().enumerate() {
+            println!(
+                "  {}. {:<9} {}",
+                i + 1,
+                step,
+                describe(*step, &args, &project_dir, &current, &version)
+            );
+        }
+        if steps.contains(&ReleaseStep::Changelog) {
+            updated_changelog(&args, &project_dir, &version)?;
+        }
+        return Ok(());
+    }
+
+    let user = UserConfig::load().unwrap_or_default();
+    let manual_upload = toolshed::resolve_url(args.toolshed_url.clone(), &user).is_none();
+
+    println!("Releasing {}...", version);
+    for (i, step) in steps.iter().enumerate() {
+        println!();
+        println!("=== [{}/{}] {} ===", i + 1, steps.len(), step);
+        if let Err(e) = run_step(*step, &args, &project_dir, &current, &version) {
+            println!();
+            println!("✗ Release stopped at step '{}': {}", step, e);
+            println!(
+                "  Fix the problem, then resume with: echidna release --from {}",
+                step
+            );
+            return Err(e);
+        }
+        if *step == ReleaseStep::Publish && manual_upload {
+            println!();
+            println!("Release {} is waiting for the Toolshed upload", version);
+            println!(
+                "  Upload the wheel on the submission page, then resume with: \
+                 echidna release --from {}",
+                ReleaseStep::Commit
+            );
+            return Ok(());
+        }
+    }
+
+    println!();
+    println!("✓ Released {}", version);
+    println!("  Push with: git push && git push origin v{}", version);
+    Ok(())
+}
+
+/// What a step does, for the dry-run plan.
+fn describe(
+    step: ReleaseStep,
+    args: &ReleaseArgs,
+    project_dir: &Path,
+    current: &str,
+    version: &str,
+) -> String {
+    match step {
+        ReleaseStep::Test if !project_dir.join("tests").is_dir() => {
+            "no tests/ directory, skipped".into()
+        }
+        ReleaseStep::Test => "run the tests (echidna test)".into(),
+        ReleaseStep::Version => format!(
+            "bump the version {} -> {} in {}",
+            current,
+            version,
+            version_sync::files(project_dir)
+                .unwrap_or_default()
+                .iter()
+                .map(|file| file.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        ReleaseStep::Changelog if args.generate_changelog => format!(
+            "write [{}] to {} from the commits since {}",
+            version,
+            CHANGELOG,
+            git::last_version_tag(project_dir).unwrap_or_else(|| "the first commit".into())
+        ),
+        ReleaseStep::Changelog if !project_dir.join(CHANGELOG).exists() => {
+            format!("no {}, skipped", CHANGELOG)
+        }
+        ReleaseStep::Changelog => format!(
+            "move [Unreleased] entries in {} to [{}] - {}",
+            CHANGELOG,
+            version,
+            changelog::today()
+        ),
+        ReleaseStep::Build => "clean build (echidna build --clean)".into(),
+        ReleaseStep::Validate => "validate (echidna validate --strict)".into(),
+        ReleaseStep::Publish => {
+            let user = UserConfig::load().unwrap_or_default();
+            match toolshed::resolve_url(args.toolshed_url.clone(), &user) {
+                Some(url) => format!("upload the wheel to {} (echidna publish)", url),
+                None => "open the Toolshed submission page (echidna publish) and stop \
+                         until the wheel is uploaded"
+                    .into(),
+            }
+        }
+        ReleaseStep::Commit => format!("git commit \"Release {0}\" and tag v{0}", version),
+    }
+}
+
+/// Run one step.
+fn run_step(
+    step: ReleaseStep,
+    args: &ReleaseArgs,
+    project_dir: &Path,
+    current: &str,
+    version: &str,
+) -> Result<()> {
+    let chimerax = || args.chimerax.clone().ok_or(EchidnaError::ChimeraXNotFound);
+    match step {
+        ReleaseStep::Test => {
+            if !project_dir.join("tests").is_dir() {
+                println!("No tests/ directory; skipping tests");
+                return Ok(());
+            }
+            let config = Config::load(project_dir)?.unwrap_or_default();
+            testing::execute(testing::TestArgs {
+                path: project_dir.to_path_buf(),
+                paths: Vec::new(),
+                filter: None,
+                verbose: false,
+                no_build: false,
+                no_install: false,
+                coverage: false,
+                coverage_config: config.test.coverage,
+                timeout: None,
+                retries: 0,
+                pytest_args: Vec::new(),
+                scripts: false,
+                visual: false,
+                update_baselines: false,
+                visual_config: config.test.visual,
+                reports: Vec::new(),
+                chimerax: chimerax()?,
+                verbosity: args.verbosity,
+            })
+        }
+        ReleaseStep::Version => {
+            version::write_version(project_dir, current, version)?;
+            println!("{} -> {}", current, version);
+            Ok(())
+        }
+        ReleaseStep::Changelog => {
+            match updated_changelog(args, project_dir, version)? {
+                Some(content) => {
+                    fs::write(project_dir.join(CHANGELOG), content)?;
+                    println!("Added [{}] to {}", version, CHANGELOG);
+                }
+                None => println!("{} unchanged", CHANGELOG),
+            }
+            Ok(())
+        }
+        ReleaseStep::Build => build::execute(build::BuildArgs {
+            path: project_dir.to_path_buf(),
+            clean: true,
+            chimerax: chimerax()?,
+            verbosity: args.verbosity,
+        }),
+        ReleaseStep::Validate => validate::execute(validate::ValidateArgs {
+            path: project_dir.to_path_buf(),
+            strict: true,
+            format: OutputFormat::Text,
+            fix: false,
+            unsafe_fixes: false,
+            dry_run: false,
+            session: false,
+            check_imports: false,
+            chimerax: None,
+            verbosity: args.verbosity,
+        }),
+        ReleaseStep::Publish => publish::execute(publish::PublishArgs {
+            path: project_dir.to_path_buf(),
+            dry_run: false,
+            format: OutputFormat::Text,
+            toolshed_url: args.toolshed_url.clone(),
+            browser: false,
+            release_notes: changelog::release_notes(project_dir, version).is_ok(),
+        }),
+        ReleaseStep::Commit => {
+            let message = format!("Release {}", version);
+            let mut files: Vec<String> = version_sync::files(project_dir)?
+                .iter()
+                .map(|file| file.to_string_lossy().to_string())
+                .collect();
+            if project_dir.join(CHANGELOG).exists() {
+                files.push(CHANGELOG.to_string());
+            }
+            let mut add = vec!["add", "--"];
+            add.extend(files.iter().map(String::as_str));
+            git::run(project_dir, &add)?;
+            git::run(project_dir, &["commit", "-m", &message])?;
+            // The tag message carries the release notes; verbatim keeps
+            // their "### Added" headings, which git would strip as comments
+            let tag_message = match changelog::release_notes(project_dir, version) {
+                Ok(notes) => format!("{}\n\n{}", message, notes),
+                Err(_) => message,
+            };
+            git::run(
+                project_dir,
+                &[
+                    "tag",
+                    "-a",
+                    "--cleanup=verbatim",
+                    &format!("v{}", version),
+                    "-m",
+                    &tag_message,
+                ],
+            )?;
+            println!("Committed and tagged v{}", version);
+            Ok(())
+        }
+    }
+}
+
+/// New CHANGELOG.md content with the release's section, or `None` when the
+/// changelog is missing (and not generated) or already has the section.
+fn updated_changelog(
+    args: &ReleaseArgs,
+    project_dir: &Path,
+    version: &str,
+) -> Result<Option<String>> {
+    let path = project_dir.join(CHANGELOG);
+    let content = if path.exists() {
+        Some(fs::read_to_string(&path)?)
+    } else {
+        None
+    };
+    if let Some(content) = &content {
+        if changelog::section(content, version).is_some() {
+            return Ok(None);
+        }
+    }
+    if args.generate_changelog {
+        let since = git::last_version_tag(project_dir);
+        let text = changelog::generate_section(project_dir, version, since.as_deref())?;
+        return Ok(Some(changelog::insert_section(content.as_deref(), &text)));
+    }
+    content
+        .map(|content| changelog::promote_unreleased(&content, version, &changelog::today()))
+        .transpose()
+}
+
+/// Check that the project is in a clean git work tree and the tag is new.
+///
+/// When resuming after the version step, the release's own changes to the
+/// version files and CHANGELOG.md are allowed.
+fn check_git(project_dir: &Path, from: ReleaseStep, version: &str) -> Result<()> {
+    git::run(project_dir, &["rev-parse", "--is-inside-work-tree"]).map_err(|_| {
+        EchidnaError::Git(format!(
+            "{} is not in a git repository",
+            project_dir.display()
+        ))
+    })?;
+
+    let prefix = git::run(project_dir, &["rev-parse", "--show-prefix"])?;
+    let prefix = prefix.trim();
+    let allowed: Vec<String> = if from > ReleaseStep::Version {
+        version_sync::files(project_dir)?
+            .iter()
+            .map(|file| file.to_string_lossy().to_string())
+            .chain([CHANGELOG.to_string()])
+            .map(|file| format!("{}{}", prefix, file))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let status = git::run(
+        project_dir,
+        &["status", "--porcelain", "--untracked-files=no"],
+    )?;
+    let dirty: Vec<&str> = status
+        .lines()
+        .filter_map(|line| line.get(3..))
+        .filter(|file| !allowed.iter().any(|a| a == file))
+        .collect();
+    if !dirty.is_empty() {
+        return Err(EchidnaError::Git(format!(
+            "the work tree has uncommitted changes ({}); commit or stash them before releasing",
+            dirty.join(", ")
+        )));
+    }
+
+    let tag = format!("v{}", version);
+    if !git::run(project_dir, &["tag", "--list", &tag])?
+        .trim()
+        .is_empty()
+    {
+        return Err(EchidnaError::Git(format!("tag {} already exists", tag)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_git() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        let run = |args: &[&str]| {
+            let status = Command::new("git")
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .current_dir(dir)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?}", args);
+        };
+        run(&["init", "-q"]);
+        fs::write(
+            dir.join("pyproject.toml"),
+            "[project]\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        run(&["add", "."]);
+        run(&["commit", "-q", "-m", "init"]);
+
+        assert!(check_git(dir, ReleaseStep::Test, "0.1.1").is_ok());
+
+        fs::write(
+            dir.join("pyproject.toml"),
+            "[project]\nversion = \"0.1.1\"\n",
+        )
+        .unwrap();
+        let err = check_git(dir, ReleaseStep::Test, "0.1.1").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("uncommitted changes (pyproject.toml)"));
+        // The release's own changes are allowed when resuming
+        assert!(check_git(dir, ReleaseStep::Build, "0.1.1").is_ok());
+
+        run(&["tag", "v0.1.1"]);
+        let err = check_git(dir, ReleaseStep::Build, "0.1.1").unwrap_err();
+        assert!(err.to_string().contains("tag v0.1.1 already exists"));
+    }
+}
@@ -1,9 +1,13 @@
 //! `echidna run` command implementation.
 
-use crate::chimerax::{ChimeraXExecutor, Verbosity};
+use crate::chimerax::{ChimeraXExecutor, LaunchOptions, Verbosity};
 use crate::commands::{build, install};
+use crate::config::{RunConfig, RunProfile};
 use crate::error::Result;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Directory (relative to the project) holding isolated ChimeraX profiles.
+const ISOLATED_PROFILE_DIR: &str = ".echidna/profiles";
 
 /// Arguments for the run command.
 pub struct RunArgs {
@@ -12,6 +16,8 @@ pub struct RunArgs {
     pub no_build: bool,
     pub no_install: bool,
     pub nogui: bool,
+    /// Named run profile from echidna.toml
+    pub profile: Option<RunProfile>,
     pub chimerax: PathBuf,
     pub verbosity: Verbosity,
 }
@@ -39,12 +45,19 @@ pub fn execute(args: RunArgs) -> Result<()> {
             path: project_dir.clone(),
             wheel: None,
             user: false,
+            index: None,
             chimerax: args.chimerax.clone(),
             verbosity: args.verbosity,
         })?;
         println!();
     }
 
+    let executor = ChimeraXExecutor::new(args.chimerax, args.verbosity);
+
+    if let Some(profile) = args.profile {
+        return run_profile(&executor, &project_dir, &profile, args.script, args.nogui);
+    }
+
     // Determine script to run
     let script = args.script.or_else(|| {
         // Try default script location
@@ -56,8 +69,6 @@ pub fn execute(args: RunArgs) -> Result<()> {
         }
     });
 
-    let executor = ChimeraXExecutor::new(args.chimerax, args.verbosity);
-
     if args.nogui {
         // Run in nogui mode
         println!("=== Running (nogui) ===");
@@ -80,3 +91,215 @@ pub fn execute(args: RunArgs) -> Result<()> {
 
     Ok(())
 }
+
+/// Run ChimeraX using a named profile.
+///
+/// A script given on the command line replaces the profile's scripts, and
+/// `--nogui` forces nogui mode even if the profile doesn't set it.
+fn run_profile(
+    executor: &ChimeraXExecutor,
+    project_dir: &Path,
+    profile: &RunProfile,
+    script_override: Option<PathBuf>,
+    nogui: bool,
+) -> Result<()> {
+    let options = profile_launch_options(project_dir, profile, script_override, nogui)?;
+
+    if options.nogui {
+        println!("=== Running profile '{}' (nogui) ===", profile.name);
+    } else {
+        println!("=== Launching ChimeraX (profile '{}') ===", profile.name);
+    }
+    for file in &options.files {
+        println!("Open: {}", file.display());
+    }
+    for script in &options.scripts {
+        println!("Script: {}", script.display());
+    }
+    if profile.isolated {
+        println!(
+            "Isolated profile: {}",
+            isolated_profile_dir(project_dir, &profile.name).display()
+        );
+    }
+
+    executor.launch_with(&options)?;
+
+    if !options.nogui {
+        println!("ChimeraX launched.");
+    }
+
+    Ok(())
+}
+
+/// Build launch options for a profile.
+///
+/// Relative script and data paths are resolved against the project directory.
+pub fn profile_launch_options(
+    project_dir: &Path,
+    profile: &RunProfile,
+    script_override: Option<PathBuf>,
+    nogui: bool,
+) -> Result<LaunchOptions> {
+    let resolve = |p: &PathBuf| {
+        if p.is_absolute() {
+            p.clone()
+        } else {
+            project_dir.join(p)
+        }
+    };
+
+    let scripts = match script_override {
+        Some(script) => vec![script],
+        None => profile.all_scripts().iter().map(resolve).collect(),
+    };
+
+    let mut env: Vec<(String, String)> = profile
+        .env
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+
+    if profile.isolated {
+        let home = isolated_profile_dir(project_dir, &profile.name);
+        std::fs::create_dir_all(&home)?;
+        env.extend(isolated_env(&home));
+    }
+
+    Ok(LaunchOptions {
+        nogui: nogui || profile.nogui,
+        files: profile.open.iter().map(resolve).collect(),
+        scripts,
+        extra_args: profile.args.clone(),
+        env,
+        ..Default::default()
+    })
+}
+
+/// Directory used as the user home of an isolated profile.
+fn isolated_profile_dir(project_dir: &Path, name: &str) -> PathBuf {
+    project_dir.join(ISOLATED_PROFILE_DIR).join(name)
+}
+
+/// Environment that redirects ChimeraX's per-user directories into `home`.
+///
+/// ChimeraX locates settings, preferences, and caches through the platform's
+/// user directories, so pointing those at a private directory gives the run a
+/// clean profile without touching the user's real one.
+fn isolated_env(home: &Path) -> Vec<(String, String)> {
+    let dir = |sub: &str| home.join(sub).to_string_lossy().to_string();
+    vec![
+        ("HOME".to_string(), home.to_string_lossy().to_string()),
+        (
+            "USERPROFILE".to_string(),
+            home.to_string_lossy().to_string(),
+        ),
+        ("XDG_CONFIG_HOME".to_string(), dir(".config")),
+        ("XDG_DATA_HOME".to_string(), dir(".local/share")),
+        ("XDG_CACHE_HOME".to_string(), dir(".cache")),
+        ("APPDATA".to_string(), dir("AppData/Roaming")),
+        ("LOCALAPPDATA".to_string(), dir("AppData/Local")),
+    ]
+}
+
+/// Print the run profiles defined in echidna.toml.
+pub fn list_profiles(run_config: &RunConfig) -> Result<()> {
+    if run_config.profiles.is_empty() {
+        println!("No run profiles defined.");
+        println!();
+        println!("Add profiles to echidna.toml:");
+        println!("  [run.profiles.<name>]");
+        println!("  script = \"scripts/smoke.cxc\"");
+        return Ok(());
+    }
+
+    println!("Run profiles:");
+    for (name, profile) in &run_config.profiles {
+        let mut flags = Vec::new();
+        if profile.nogui {
+            flags.push("nogui");
+        }
+        if profile.isolated {
+            flags.push("isolated");
+        }
+        let flags = if flags.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", flags.join(", "))
+        };
+
+        match &profile.description {
+            Some(desc) => println!("  {}{} - {}", name, flags, desc),
+            None => println!("  {}{}", name, flags),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_profile_launch_options_resolves_paths() {
+        let temp = TempDir::new().unwrap();
+        let profile = RunProfile {
+            name: "density".to_string(),
+            script: Some(PathBuf::from("scripts/map.cxc")),
+            open: vec![PathBuf::from("data/emd_1234.map")],
+            args: vec!["--nostatus".to_string()],
+            ..Default::default()
+        };
+
+        let options = profile_launch_options(temp.path(), &profile, None, false).unwrap();
+        assert!(!options.nogui);
+        assert_eq!(options.files, vec![temp.path().join("data/emd_1234.map")]);
+        assert_eq!(options.scripts, vec![temp.path().join("scripts/map.cxc")]);
+        assert_eq!(options.extra_args, vec!["--nostatus".to_string()]);
+        assert!(options.env.is_empty());
+    }
+
+    #[test]
+    fn test_profile_launch_options_script_override() {
+        let temp = TempDir::new().unwrap();
+        let profile = RunProfile {
+            name: "batch".to_string(),
+            scripts: vec![PathBuf::from("a.cxc"), PathBuf::from("b.cxc")],
+            ..Default::default()
+        };
+
+        let options =
+            profile_launch_options(temp.path(), &profile, Some(PathBuf::from("c.cxc")), true)
+                .unwrap();
+        assert!(options.nogui);
+        assert_eq!(options.scripts, vec![PathBuf::from("c.cxc")]);
+    }
+
+    #[test]
+    fn test_profile_launch_options_isolated() {
+        let temp = TempDir::new().unwrap();
+        let mut env = BTreeMap::new();
+        env.insert("FOO".to_string(), "bar".to_string());
+        let profile = RunProfile {
+            name: "clean".to_string(),
+            nogui: true,
+            isolated: true,
+            env,
+            ..Default::default()
+        };
+
+        let options = profile_launch_options(temp.path(), &profile, None, false).unwrap();
+        let home = temp.path().join(ISOLATED_PROFILE_DIR).join("clean");
+        assert!(options.nogui);
+        assert!(home.is_dir());
+        assert!(options
+            .env
+            .contains(&("FOO".to_string(), "bar".to_string())));
+        assert!(options
+            .env
+            .contains(&("HOME".to_string(), home.to_string_lossy().to_string())));
+    }
+}
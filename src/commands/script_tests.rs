@@ -0,0 +1,291 @@
+//! ChimeraX command script tests for `echidna test --scripts`.
+//!
+//! Every `tests/**/*.cxc` (except visual tests) is run in nogui mode and
+//! fails on ChimeraX errors, tracebacks, or warnings. If a sidecar
+//! `<name>.expected` file exists, each of its lines must appear in the
+//! captured log, in order. Lines prefixed with `re:` are regular expressions
+//! that must match a whole log line.
+
+use crate::chimerax::{ChimeraXExecutor, LaunchOptions};
+use crate::commands::visual::VISUAL_DIR;
+use crate::error::{EchidnaError, Result};
+use crate::test_report::{TestCase, TestStatus, TestSuite};
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Instant;
+
+/// Directory searched for script tests (relative to the project).
+pub const SCRIPTS_DIR: &str = "tests";
+
+/// Extension of expected-log sidecar files.
+pub const EXPECTED_EXTENSION: &str = "expected";
+
+/// Log line prefixes that fail a script test unless listed in its `.expected` file.
+const PROBLEM_PREFIXES: &[&str] = &[
+    "Traceback (most recent call last)",
+    "Error",
+    "Warning",
+    "Unknown command",
+];
+
+/// A line of an `.expected` file.
+#[derive(Debug)]
+enum ExpectedLine {
+    /// Log line must equal this text (ignoring surrounding whitespace)
+    Literal(String),
+    /// Log line must fully match this pattern
+    Pattern { source: String, regex: Regex },
+}
+
+impl ExpectedLine {
+    fn matches(&self, line: &str) -> bool {
+        match self {
+            Self::Literal(text) => line.trim() == text,
+            Self::Pattern { regex, .. } => regex.is_match(line.trim()),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Literal(text) => text.clone(),
+            Self::Pattern { source, .. } => format!("re:{}", source),
+        }
+    }
+}
+
+/// Run the script tests of a project and collect them into a report suite.
+pub fn execute(executor: &ChimeraXExecutor, project_dir: &Path) -> Result<TestSuite> {
+    let tests_dir = project_dir.join(SCRIPTS_DIR);
+    let scripts = find_scripts(&tests_dir, &project_dir.join(VISUAL_DIR))?;
+    if scripts.is_empty() {
+        return Err(EchidnaError::ConfigError(format!(
+            "No script tests found. Add .cxc scripts to {}/",
+            SCRIPTS_DIR
+        )));
+    }
+
+    let mut suite = TestSuite::new("scripts");
+    for script in &scripts {
+        let name = script
+            .strip_prefix(project_dir)
+            .unwrap_or(script)
+            .to_string_lossy()
+            .to_string();
+
+        let start = Instant::now();
+        let output = run_script(executor, script)?;
+        let time = start.elapsed().as_secs_f64();
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let log = format!("{}{}", stdout, stderr);
+
+        let expected_path = script.with_extension(EXPECTED_EXTENSION);
+        let expected = if expected_path.exists() {
+            Some(parse_expected(&fs::read_to_string(&expected_path)?)?)
+        } else {
+            None
+        };
+
+        let mut failure = check_log(&log, expected.as_deref());
+        if failure.is_none() && !output.status.success() {
+            failure = Some(format!(
+                "ChimeraX exited with code {}",
+                output.status.code().unwrap_or(-1)
+            ));
+        }
+
+        let mut case = TestCase::new(&name, SCRIPTS_DIR, TestStatus::Passed);
+        case.time = time;
+        match failure {
+            None => println!("  ✓ {}", name),
+            Some(message) => {
+                println!("  ✗ {}: {}", name, message);
+                case.status = TestStatus::Failed;
+                case.message = Some(message);
+                case.details = Some(log);
+            }
+        }
+        suite.tests.push(case);
+    }
+
+    Ok(suite)
+}
+
+/// Run a single script in nogui mode, returning its output regardless of exit code.
+fn run_script(executor: &ChimeraXExecutor, script: &Path) -> Result<std::process::Output> {
+    let args = LaunchOptions {
+        nogui: true,
+        scripts: vec![script.to_path_buf()],
+        ..Default::default()
+    }
+    .to_args();
+
+    let output = Command::new(executor.executable())
+        .args(&args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()?;
+
+    Ok(output)
+}
+
+/// Find `*.cxc` scripts under `dir` recursively, skipping `exclude` (sorted).
+fn find_scripts(dir: &Path, exclude: &Path) -> Result<Vec<PathBuf>> {
+    let mut scripts = Vec::new();
+    if dir.is_dir() && dir != exclude {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                scripts.extend(find_scripts(&path, exclude)?);
+            } else if path.extension().map(|e| e == "cxc").unwrap_or(false) {
+                scripts.push(path);
+            }
+        }
+    }
+    scripts.sort();
+    Ok(scripts)
+}
+
+/// Parse an `.expected` file; blank lines are ignored.
+fn parse_expected(content: &str) -> Result<Vec<ExpectedLine>> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.strip_prefix("re:") {
+            Some(pattern) => {
+                let source = pattern.trim().to_string();
+                Regex::new(&format!("^(?:{})$", source))
+                    .map(|regex| ExpectedLine::Pattern { source, regex })
+                    .map_err(|e| {
+                        EchidnaError::ConfigError(format!(
+                            "Invalid pattern in .expected file: {}",
+                            e
+                        ))
+                    })
+            }
+            None => Ok(ExpectedLine::Literal(line.to_string())),
+        })
+        .collect()
+}
+
+/// Check a captured log, returning a failure message if the test failed.
+///
+/// Expected lines are matched in order; log lines they match may contain
+/// errors or warnings without failing the test.
+fn check_log(log: &str, expected: Option<&[ExpectedLine]>) -> Option<String> {
+    let lines: Vec<&str> = log.lines().collect();
+    let mut matched = vec![false; lines.len()];
+
+    if let Some(expected) = expected {
+        let mut next = 0;
+        for (n, exp) in expected.iter().enumerate() {
+            match (next..lines.len()).find(|&i| exp.matches(lines[i])) {
+                Some(i) => {
+                    matched[i] = true;
+                    next = i + 1;
+                }
+                None => {
+                    return Some(format!(
+                        "expected line {} not found in log: {}",
+                        n + 1,
+                        exp.describe()
+                    ))
+                }
+            }
+        }
+    }
+
+    lines
+        .iter()
+        .zip(&matched)
+        .find(|(line, matched)| {
+            !**matched
+                && PROBLEM_PREFIXES
+                    .iter()
+                    .any(|prefix| line.trim_start().starts_with(prefix))
+        })
+        .map(|(line, _)| format!("unexpected log output: {}", line.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_clean_log_passes() {
+        let log = "open 1abc\nChain information for 1abc #1\n";
+        assert_eq!(check_log(log, None), None);
+    }
+
+    #[test]
+    fn test_errors_and_warnings_fail() {
+        let failure = check_log("open x\nError: file not found\n", None).unwrap();
+        assert!(failure.contains("Error: file not found"));
+
+        assert!(check_log("Warning: ignored residue\n", None).is_some());
+        assert!(check_log("Unknown command: fooo\n", None).is_some());
+        assert!(check_log(
+            "Traceback (most recent call last):\n  File \"x.py\"\nValueError\n",
+            None
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_expected_lines_in_order() {
+        let expected = parse_expected("open 1abc\n\nre:\\d+ atoms, \\d+ bonds\n").unwrap();
+        let log = "startup\nopen 1abc\nsomething\n1024 atoms, 1040 bonds\n";
+        assert_eq!(check_log(log, Some(&expected)), None);
+
+        let reversed = "1024 atoms, 1040 bonds\nopen 1abc\n";
+        let failure = check_log(reversed, Some(&expected)).unwrap();
+        assert!(failure.contains("expected line 2"));
+        assert!(failure.contains("re:\\d+ atoms, \\d+ bonds"));
+    }
+
+    #[test]
+    fn test_regex_must_match_whole_line() {
+        let expected = parse_expected("re:\\d+ atoms").unwrap();
+        assert!(check_log("1024 atoms, 1040 bonds\n", Some(&expected)).is_some());
+    }
+
+    #[test]
+    fn test_expected_warning_allowed() {
+        let expected = parse_expected("Warning: ignored residue").unwrap();
+        assert_eq!(
+            check_log("Warning: ignored residue\n", Some(&expected)),
+            None
+        );
+        // Other warnings still fail
+        assert!(check_log(
+            "Warning: ignored residue\nWarning: other\n",
+            Some(&expected)
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(parse_expected("re:(unclosed").is_err());
+    }
+
+    #[test]
+    fn test_find_scripts_skips_visual() {
+        let temp = TempDir::new().unwrap();
+        let tests = temp.path().join("tests");
+        fs::create_dir_all(tests.join("cmds")).unwrap();
+        fs::create_dir_all(tests.join("visual")).unwrap();
+        fs::write(tests.join("b.cxc"), "").unwrap();
+        fs::write(tests.join("cmds/a.cxc"), "").unwrap();
+        fs::write(tests.join("cmds/a.expected"), "").unwrap();
+        fs::write(tests.join("visual/render.cxc"), "").unwrap();
+
+        let scripts = find_scripts(&tests, &tests.join("visual")).unwrap();
+        assert_eq!(scripts, vec![tests.join("b.cxc"), tests.join("cmds/a.cxc")]);
+    }
+}
@@ -0,0 +1,377 @@
+//! Check that stateful bundle classes survive session save and restore.
+//!
+//! The static check scans `src/**/*.py` for classes derived from ChimeraX's
+//! `ToolInstance`, `Model`, or `State` (imported from a `chimerax` module,
+//! directly or through other bundle classes) and checks their snapshot methods
+//! and the bundle API's `get_class()`. The dynamic check saves and restores a
+//! session in ChimeraX.
+
+use crate::chimerax::ChimeraXExecutor;
+use crate::commands::python_source::{remove_docstrings, PythonClass, Sources};
+use crate::commands::validate::ValidationResult;
+use crate::diagnostics::{rules, Diagnostic};
+use crate::error::Result;
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// ChimeraX base classes whose instances are saved in sessions.
+const STATEFUL_BASES: &[&str] = &["ToolInstance", "Model", "State"];
+
+/// Setup script run before the session roundtrip, relative to the project.
+pub const SESSION_SETUP: &str = "tests/session.cxc";
+
+/// Check snapshot methods and `get_class()` coverage of stateful classes.
+pub fn check(project_dir: &Path, result: &mut ValidationResult) -> Result<()> {
+    let sources = Sources::load(project_dir)?;
+    let classes = sources.classes();
+    let not_saved = Regex::new(r"(?m)^\s*SESSION_SAVE\s*=\s*False\b").unwrap();
+
+    let mut saved = Vec::new();
+    for class in &classes {
+        let Some(base) = stateful_base(class, &classes, &mut HashSet::new()) else {
+            continue;
+        };
+        if class.inherits(&classes, &|c| {
+            not_saved.is_match(&remove_docstrings(&c.body))
+        }) {
+            continue;
+        }
+        saved.push(class);
+
+        let missing: Vec<&str> = ["take_snapshot", "restore_snapshot"]
+            .into_iter()
+            .filter(|method| !class.inherits(&classes, &|c| c.methods.iter().any(|m| m == method)))
+            .collect();
+        if !missing.is_empty() {
+            result.add(
+                Diagnostic::new(
+                    &rules::MISSING_SNAPSHOT_METHODS,
+                    format!(
+                        "{} class '{}' in {} does not implement {}(), so its state is not saved \
+                         in sessions",
+                        base,
+                        class.name,
+                        class.file.display(),
+                        missing.join("() or ")
+                    ),
+                )
+                .at(class.location.clone()),
+            );
+        }
+    }
+
+    check_get_class(&sources, &saved, result);
+    Ok(())
+}
+
+/// Check that `get_class()` returns every class saved in sessions.
+///
+/// A `get_class()` without string literals (e.g. `return globals()[name]`)
+/// is assumed to handle every class.
+fn check_get_class(sources: &Sources, saved: &[&PythonClass], result: &mut ValidationResult) {
+    let functions = sources.functions("get_class");
+    let Some(first) = functions.first() else {
+        for class in saved {
+            result.add(
+                Diagnostic::new(
+                    &rules::GET_CLASS_COVERAGE,
+                    format!(
+                        "Class '{}' in {} is saved in sessions but the bundle API has no \
+                         get_class(), so sessions cannot restore it",
+                        class.name,
+                        class.file.display()
+                    ),
+                )
+                .at(class.location.clone()),
+            );
+        }
+        return;
+    };
+
+    let literals: Vec<String> = functions.iter().flat_map(|f| f.string_literals()).collect();
+    if literals.is_empty() {
+        return;
+    }
+    for class in saved {
+        if !literals.contains(&class.name) {
+            result.add(
+                Diagnostic::new(
+                    &rules::GET_CLASS_COVERAGE,
+                    format!(
+                        "get_class() in {} does not return '{}' ({}), so sessions cannot \
+                         restore it",
+                        first.file.display(),
+                        class.name,
+                        class.file.display()
+                    ),
+                )
+                .at(first.location.clone()),
+            );
+        }
+    }
+}
+
+/// The ChimeraX base class a class derives from, if it is stateful.
+fn stateful_base<'a>(
+    class: &'a PythonClass,
+    classes: &'a [PythonClass],
+    visited: &mut HashSet<&'a str>,
+) -> Option<&'a str> {
+    if !visited.insert(&class.name) {
+        return None;
+    }
+    for base in &class.bases {
+        if let Some(parent) = classes.iter().find(|c| &c.name == base) {
+            if let Some(found) = stateful_base(parent, classes, visited) {
+                return Some(found);
+            }
+        } else if STATEFUL_BASES.contains(&base.as_str()) && class.chimerax_bases.contains(base) {
+            return Some(base);
+        }
+    }
+    None
+}
+
+/// Save and restore a session in ChimeraX (nogui) and compare the open
+/// models and tools.
+///
+/// [`SESSION_SETUP`] is run first, if it exists, to create the bundle's
+/// models and tools. The bundle must be installed in ChimeraX.
+pub fn check_roundtrip(
+    executor: &ChimeraXExecutor,
+    project_dir: &Path,
+    result: &mut ValidationResult,
+) -> Result<()> {
+    let setup = project_dir.join(SESSION_SETUP);
+    let script_path =
+        std::env::temp_dir().join(format!("echidna-session-{}.py", std::process::id()));
+    let session_path =
+        std::env::temp_dir().join(format!("echidna-session-{}.cxs", std::process::id()));
+    fs::write(
+        &script_path,
+        roundtrip_script(setup.exists().then_some(setup.as_path()), &session_path),
+    )?;
+
+    let output = executor.run_script(&script_path);
+    let _ = fs::remove_file(&script_path);
+    let _ = fs::remove_file(&session_path);
+    let output = output?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    for problem in roundtrip_problems(&stdout, &stderr) {
+        result.add(Diagnostic::new(&rules::SESSION_ROUNDTRIP, problem));
+    }
+    Ok(())
+}
+
+/// Python script that runs the session roundtrip and prints its findings.
+fn roundtrip_script(setup: Option<&Path>, session_path: &Path) -> String {
+    // JSON strings are valid Python string literals
+    let quote = |path: &Path| serde_json::to_string(&path.to_string_lossy()).unwrap();
+    format!(
+        r#"# Generated by echidna validate --session
+from chimerax.core.commands import run
+
+def report(stage):
+    for m in session.models.list():
+        print("ECHIDNA_SESSION_%s:model #%s %s.%s" % (stage, m.id_string, type(m).__module__, type(m).__name__))
+    for t in session.tools.list():
+        print("ECHIDNA_SESSION_%s:tool %s.%s" % (stage, type(t).__module__, type(t).__name__))
+
+setup = {setup}
+path = {path}
+try:
+    if setup:
+        run(session, 'open "%s"' % setup, log=False)
+    report("BEFORE")
+    run(session, 'save "%s"' % path, log=False)
+    run(session, "close session", log=False)
+    run(session, 'open "%s"' % path, log=False)
+    report("AFTER")
+except Exception as e:
+    print("ECHIDNA_SESSION_ERROR:%s: %s" % (type(e).__name__, e))
+"#,
+        setup = setup.map(quote).unwrap_or_else(|| "None".into()),
+        path = quote(session_path),
+    )
+}
+
+/// Problems reported by the roundtrip script's output.
+fn roundtrip_problems(stdout: &str, stderr: &str) -> Vec<String> {
+    let entries = |prefix: &str| -> Vec<&str> {
+        stdout
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix(prefix))
+            .collect()
+    };
+
+    let mut problems: Vec<String> = entries("ECHIDNA_SESSION_ERROR:")
+        .into_iter()
+        .map(|error| format!("Session roundtrip failed: {}", error))
+        .collect();
+    if !problems.is_empty() {
+        return problems;
+    }
+
+    let mut after = entries("ECHIDNA_SESSION_AFTER:");
+    for entry in entries("ECHIDNA_SESSION_BEFORE:") {
+        match after.iter().position(|a| *a == entry) {
+            Some(i) => {
+                after.remove(i);
+            }
+            None => problems.push(format!("{} is missing after restoring the session", entry)),
+        }
+    }
+    if stdout.contains("Traceback") || stderr.contains("Traceback") {
+        problems.push("ChimeraX reported a Python traceback during the session roundtrip".into());
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn check_sources(files: &[(&str, &str)]) -> ValidationResult {
+        let temp = TempDir::new().unwrap();
+        for (name, content) in files {
+            let path = temp.path().join("src").join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        let mut result = ValidationResult::default();
+        check(temp.path(), &mut result).unwrap();
+        result
+    }
+
+    const INIT: &str = r#"
+from chimerax.core.toolshed import BundleAPI
+
+class _API(BundleAPI):
+    @staticmethod
+    def get_class(class_name):
+        if class_name == "MyTool":
+            from .tool import MyTool
+            return MyTool
+
+bundle_api = _API()
+"#;
+
+    #[test]
+    fn test_complete_tool() {
+        let result = check_sources(&[
+            ("__init__.py", INIT),
+            (
+                "tool.py",
+                r#"
+from chimerax.core.tools import ToolInstance
+
+class MyTool(ToolInstance):
+    def take_snapshot(self, session, flags):
+        return {"version": 1}
+
+    @classmethod
+    def restore_snapshot(cls, session, data):
+        return cls(session, "My Tool")
+"#,
+            ),
+        ]);
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+    }
+
+    #[test]
+    fn test_missing_snapshot_and_get_class() {
+        let result = check_sources(&[
+            ("__init__.py", INIT),
+            (
+                "model.py",
+                r#"
+from chimerax.core import models
+
+class Base(models.Model):
+    def take_snapshot(self, session, flags):
+        return {}
+
+class Blob(Base):
+    pass
+
+class Panel(ToolInstance):
+    SESSION_SAVE = False
+"#,
+            ),
+        ]);
+
+        let snapshot: Vec<_> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.rule.code == "CX080")
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(snapshot.len(), 2);
+        assert!(snapshot[0].starts_with("Model class 'Base'"));
+        assert!(snapshot[0].contains("restore_snapshot()"));
+        assert!(!snapshot[0].contains("take_snapshot()"));
+
+        let coverage: Vec<_> = result
+            .diagnostics
+            .iter()
+            .filter(|d| d.rule.code == "CX081")
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(coverage.len(), 2);
+        assert!(coverage[0].contains("'Base'"));
+        assert!(coverage[1].contains("'Blob'"));
+    }
+
+    #[test]
+    fn test_no_get_class() {
+        let result = check_sources(&[(
+            "state.py",
+            "from chimerax.core.state import State\n\nclass Settings(State):\n    def take_snapshot(self, s, f): pass\n    def restore_snapshot(cls, s, d): pass\n",
+        )]);
+        assert_eq!(result.diagnostics.len(), 1);
+        assert!(result.diagnostics[0].message.contains("has no get_class()"));
+    }
+
+    #[test]
+    fn test_unrelated_bases() {
+        let result = check_sources(&[(
+            "data.py",
+            r#"
+from django.db.models import Model
+import enum
+
+class Record(Model):
+    pass
+
+class State(enum.Enum):
+    IDLE = 1
+
+class Machine(State):
+    pass
+"#,
+        )]);
+        assert!(result.diagnostics.is_empty(), "{:?}", result.diagnostics);
+    }
+
+    #[test]
+    fn test_roundtrip_problems() {
+        let stdout = "ECHIDNA_SESSION_BEFORE:model #1 chimerax.blob.Blob\n\
+                      ECHIDNA_SESSION_BEFORE:tool chimerax.blob.BlobTool\n\
+                      ECHIDNA_SESSION_AFTER:model #1 chimerax.blob.Blob\n";
+        assert_eq!(
+            roundtrip_problems(stdout, ""),
+            vec!["tool chimerax.blob.BlobTool is missing after restoring the session"]
+        );
+
+        let stdout = "ECHIDNA_SESSION_ERROR:UserError: No bundle found for class 'Blob'\n";
+        assert_eq!(
+            roundtrip_problems(stdout, ""),
+            vec!["Session roundtrip failed: UserError: No bundle found for class 'Blob'"]
+        );
+    }
+}
@@ -0,0 +1,362 @@
+//! `echidna snapshot` command implementation.
+//!
+//! Renders images of what a bundle produces using ChimeraX's offscreen
+//! (OSMesa) mode, so it works on machines without a display.
+
+use crate::chimerax::{validate_path_for_command, ChimeraXExecutor, LaunchOptions, Verbosity};
+use crate::commands::{build, install};
+use crate::error::{EchidnaError, Result};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default image size when `--size` is not given.
+pub const DEFAULT_SIZE: ImageSize = ImageSize {
+    width: 1024,
+    height: 768,
+};
+
+/// Arguments for the snapshot command.
+pub struct SnapshotArgs {
+    /// Project directory
+    pub path: PathBuf,
+    /// Scripts to render (.cxc or .py)
+    pub scripts: Vec<PathBuf>,
+    /// Output image (one script) or gallery directory (several scripts)
+    pub out: PathBuf,
+    /// Image size
+    pub size: ImageSize,
+    /// Skip build step
+    pub no_build: bool,
+    /// Skip install step
+    pub no_install: bool,
+    /// Path to ChimeraX executable
+    pub chimerax: PathBuf,
+    /// Verbosity level
+    pub verbosity: Verbosity,
+}
+
+/// Rendered image size in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl std::str::FromStr for ImageSize {
+    type Err = String;
+
+    /// Parse a `WxH` size string (e.g., "800x600").
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (w, h) = s
+            .split_once(['x', 'X'])
+            .ok_or_else(|| format!("invalid size '{}' (expected WxH, e.g. 800x600)", s))?;
+        let width: u32 = w
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid width in '{}'", s))?;
+        let height: u32 = h
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid height in '{}'", s))?;
+        if width == 0 || height == 0 {
+            return Err(format!("size '{}' must be non-zero", s));
+        }
+        Ok(Self { width, height })
+    }
+}
+
+impl std::fmt::Display for ImageSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+/// A script and the image it renders to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotJob {
+    pub script: PathBuf,
+    pub image: PathBuf,
+}
+
+/// Execute the snapshot command.
+pub fn execute(args: SnapshotArgs) -> Result<()> {
+    let project_dir = args.path.canonicalize().unwrap_or(args.path.clone());
+
+    if args.scripts.is_empty() {
+        return Err(EchidnaError::ConfigError(
+            "No scripts given to snapshot".into(),
+        ));
+    }
+
+    let cwd = std::env::current_dir()?;
+    let scripts = args
+        .scripts
+        .iter()
+        .map(|s| {
+            s.canonicalize().map_err(|e| {
+                EchidnaError::ConfigError(format!("Cannot access script '{}': {}", s.display(), e))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let out = if args.out.is_absolute() {
+        args.out.clone()
+    } else {
+        cwd.join(&args.out)
+    };
+
+    // Build if not skipped
+    if !args.no_build {
+        println!("=== Building ===");
+        build::execute(build::BuildArgs {
+            path: project_dir.clone(),
+            clean: false,
+            chimerax: args.chimerax.clone(),
+            verbosity: args.verbosity,
+        })?;
+        println!();
+    }
+
+    // Install if not skipped
+    if !args.no_install {
+        println!("=== Installing ===");
+        install::execute(install::InstallArgs {
+            path: project_dir.clone(),
+            wheel: None,
+            user: false,
+            index: None,
+            chimerax: args.chimerax.clone(),
+            verbosity: args.verbosity,
+        })?;
+        println!();
+    }
+
+    let gallery = scripts.len() > 1;
+    let jobs = if gallery {
+        fs::create_dir_all(&out)?;
+        gallery_jobs(&scripts, &out)
+    } else {
+        if let Some(parent) = out.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        vec![SnapshotJob {
+            script: scripts[0].clone(),
+            image: out.clone(),
+        }]
+    };
+
+    println!("=== Rendering ({}, offscreen) ===", args.size);
+    let executor = ChimeraXExecutor::new(args.chimerax, args.verbosity);
+    render(&executor, &jobs, args.size)?;
+
+    for job in &jobs {
+        println!("  {} -> {}", job.script.display(), job.image.display());
+    }
+
+    if gallery {
+        let index = out.join("index.html");
+        fs::write(&index, gallery_index_html(&jobs, &out))?;
+        println!();
+        println!("Gallery: {}", index.display());
+    }
+
+    Ok(())
+}
+
+/// Render every job in a single offscreen ChimeraX session.
+///
+/// The session is closed between scripts so each image only shows what its
+/// own script produced.
+pub fn render(executor: &ChimeraXExecutor, jobs: &[SnapshotJob], size: ImageSize) -> Result<()> {
+    let driver_script = driver_script(jobs, size)?;
+    let driver_path =
+        std::env::temp_dir().join(format!("echidna-snapshot-{}.cxc", std::process::id()));
+    fs::write(&driver_path, driver_script)?;
+
+    // Remove stale images so a failed render can't pass as a fresh one
+    for job in jobs {
+        if job.image.exists() {
+            fs::remove_file(&job.image)?;
+        }
+    }
+
+    let result = executor.launch_with(&LaunchOptions {
+        offscreen: true,
+        scripts: vec![driver_path.clone()],
+        ..Default::default()
+    });
+    let _ = fs::remove_file(&driver_path); // Best effort cleanup
+    result?;
+
+    let missing: Vec<String> = jobs
+        .iter()
+        .filter(|job| !job.image.exists())
+        .map(|job| job.script.display().to_string())
+        .collect();
+    if !missing.is_empty() {
+        return Err(EchidnaError::ChimeraXCommandFailed(format!(
+            "no image was rendered for: {} (is ChimeraX built with OSMesa offscreen support?)",
+            missing.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+/// Generate the ChimeraX command script that runs each job and saves its image.
+fn driver_script(jobs: &[SnapshotJob], size: ImageSize) -> Result<String> {
+    let mut lines = vec!["# Generated by echidna snapshot".to_string()];
+    for (i, job) in jobs.iter().enumerate() {
+        validate_path_for_command(&job.script)?;
+        validate_path_for_command(&job.image)?;
+        if i > 0 {
+            lines.push("close session".to_string());
+        }
+        lines.push(format!("open \"{}\"", job.script.display()));
+        lines.push(format!(
+            "save \"{}\" width {} height {} supersample 3",
+            job.image.display(),
+            size.width,
+            size.height
+        ));
+    }
+    lines.push(String::new());
+    Ok(lines.join("\n"))
+}
+
+/// Assign a unique `<script-stem>.png` in `out_dir` to every script.
+fn gallery_jobs(scripts: &[PathBuf], out_dir: &Path) -> Vec<SnapshotJob> {
+    let mut used = HashSet::new();
+    scripts
+        .iter()
+        .map(|script| {
+            let stem = script
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| "snapshot".to_string());
+            let mut name = stem.clone();
+            let mut n = 2;
+            while !used.insert(name.clone()) {
+                name = format!("{}-{}", stem, n);
+                n += 1;
+            }
+            SnapshotJob {
+                script: script.clone(),
+                image: out_dir.join(format!("{}.png", name)),
+            }
+        })
+        .collect()
+}
+
+/// Generate a gallery index page listing every rendered image.
+fn gallery_index_html(jobs: &[SnapshotJob], out_dir: &Path) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>echidna snapshots</title>\n\
+         <style>body{font-family:sans-serif}figure{display:inline-block;margin:1em}\
+         img{max-width:480px;border:1px solid #ccc}</style>\n</head>\n<body>\n<h1>Snapshots</h1>\n",
+    );
+    for job in jobs {
+        let image = job
+            .image
+            .strip_prefix(out_dir)
+            .unwrap_or(&job.image)
+            .to_string_lossy()
+            .to_string();
+        let caption = job
+            .script
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        html.push_str(&format!(
+            "<figure><a href=\"{0}\"><img src=\"{0}\" alt=\"{1}\"></a><figcaption>{1}</figcaption></figure>\n",
+            html_escape(&image),
+            html_escape(&caption)
+        ));
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Escape text for inclusion in HTML.
+pub(crate) fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        let size: ImageSize = "800x600".parse().unwrap();
+        assert_eq!(size.width, 800);
+        assert_eq!(size.height, 600);
+        assert_eq!(size.to_string(), "800x600");
+
+        assert!("800".parse::<ImageSize>().is_err());
+        assert!("0x600".parse::<ImageSize>().is_err());
+        assert!("axb".parse::<ImageSize>().is_err());
+    }
+
+    #[test]
+    fn test_driver_script() {
+        let jobs = vec![
+            SnapshotJob {
+                script: PathBuf::from("/p/a.cxc"),
+                image: PathBuf::from("/out/a.png"),
+            },
+            SnapshotJob {
+                script: PathBuf::from("/p/b.cxc"),
+                image: PathBuf::from("/out/b.png"),
+            },
+        ];
+        let script = driver_script(&jobs, DEFAULT_SIZE).unwrap();
+        let lines: Vec<&str> = script.lines().skip(1).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "open \"/p/a.cxc\"",
+                "save \"/out/a.png\" width 1024 height 768 supersample 3",
+                "close session",
+                "open \"/p/b.cxc\"",
+                "save \"/out/b.png\" width 1024 height 768 supersample 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_driver_script_rejects_unsafe_paths() {
+        let jobs = vec![SnapshotJob {
+            script: PathBuf::from("/p/a\";exit.cxc"),
+            image: PathBuf::from("/out/a.png"),
+        }];
+        assert!(driver_script(&jobs, DEFAULT_SIZE).is_err());
+    }
+
+    #[test]
+    fn test_gallery_jobs_unique_names() {
+        let scripts = vec![
+            PathBuf::from("/p/views/cartoon.cxc"),
+            PathBuf::from("/p/other/cartoon.cxc"),
+            PathBuf::from("/p/surface.py"),
+        ];
+        let jobs = gallery_jobs(&scripts, Path::new("/out"));
+        assert_eq!(jobs[0].image, PathBuf::from("/out/cartoon.png"));
+        assert_eq!(jobs[1].image, PathBuf::from("/out/cartoon-2.png"));
+        assert_eq!(jobs[2].image, PathBuf::from("/out/surface.png"));
+    }
+
+    #[test]
+    fn test_gallery_index_html() {
+        let jobs = vec![SnapshotJob {
+            script: PathBuf::from("/p/a<b>.cxc"),
+            image: PathBuf::from("/out/a.png"),
+        }];
+        let html = gallery_index_html(&jobs, Path::new("/out"));
+        assert!(html.contains("src=\"a.png\""));
+        assert!(html.contains("a&lt;b&gt;.cxc"));
+    }
+}
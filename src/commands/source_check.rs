@@ -0,0 +1,354 @@
+//! Cross-check pyproject.toml declarations against the bundle's Python source.
+//!
+//! A lightweight scan of `src/**/*.py` (no Python parser) that finds the bundle
+//! API methods and checks that every declared command, tool, and data format
+//! is handled, and that commands registered in code are declared. A mismatch
+//! typically works in a development session but breaks after installation,
+//! when ChimeraX only knows the declared names.
+
+use crate::commands::python_source::{PythonFunction, Sources};
+use crate::commands::validate::ValidationResult;
+use crate::diagnostics::{rules, Diagnostic, Location, Rule};
+use crate::error::Result;
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// Check declared commands, tools, and formats against the Python source.
+pub fn check(
+    project_dir: &Path,
+    chimerax: &toml::Value,
+    result: &mut ValidationResult,
+) -> Result<()> {
+    let sources = Sources::load(project_dir)?;
+
+    let commands = declared_names(chimerax, "command");
+    check_handled(
+        &sources,
+        &commands,
+        "register_command",
+        ("Command", "command", &rules::UNHANDLED_COMMAND),
+        result,
+    );
+    check_registered_commands(&sources, &commands, result);
+
+    let tools = declared_names(chimerax, "tool");
+    check_handled(
+        &sources,
+        &tools,
+        "start_tool",
+        ("Tool", "tool", &rules::UNHANDLED_TOOL),
+        result,
+    );
+
+    check_formats(&sources, chimerax, result);
+    Ok(())
+}
+
+/// Names of the entries of `[chimerax.<table>]`.
+fn declared_names(chimerax: &toml::Value, table: &str) -> Vec<String> {
+    chimerax
+        .get(table)
+        .and_then(|t| t.as_table())
+        .map(|t| t.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Check that each declared name is handled by a bundle API method.
+///
+/// A method without string literals (e.g. `register(command_info.name, ...)`
+/// or delegation to another module) handles every name as far as a static
+/// scan can tell; otherwise each name must appear as a literal in it.
+fn check_handled(
+    sources: &Sources,
+    names: &[String],
+    method: &str,
+    (kind, table, rule): (&str, &str, &'static Rule),
+    result: &mut ValidationResult,
+) {
+    if names.is_empty() {
+        return;
+    }
+
+    let functions = sources.functions(method);
+    if functions.is_empty() {
+        result.add(
+            Diagnostic::new(
+                rule,
+                format!(
+                    "{}s are declared in [chimerax.{}] but no {}() is defined in src/",
+                    kind, table, method
+                ),
+            )
+            .at_key(&["chimerax", table]),
+        );
+        return;
+    }
+
+    let literals: Vec<String> = functions
+        .iter()
+        .flat_map(PythonFunction::string_literals)
+        .collect();
+    if literals.is_empty() {
+        return;
+    }
+
+    for name in names {
+        if !literals.contains(name) {
+            result.add(
+                Diagnostic::new(
+                    rule,
+                    format!(
+                        "{} '{}' is declared in [chimerax.{}] but not handled by {}() in {}",
+                        kind,
+                        name,
+                        table,
+                        method,
+                        functions[0].file.display()
+                    ),
+                )
+                .at(functions[0].location.clone()),
+            );
+        }
+    }
+}
+
+/// A `register("name", ...)` call with a string literal name.
+static REGISTER_CALL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\bregister\(\s*(?:"([^"\n]+)"|'([^'\n]+)')"#).unwrap());
+
+/// Warn about `register("name", ...)` calls for commands that are not declared.
+///
+/// Subcommands of a declared command (`"mytool sub"` for `"mytool"`) are
+/// fine, since ChimeraX loads the bundle when the declared prefix is used.
+fn check_registered_commands(
+    sources: &Sources,
+    declared: &[String],
+    result: &mut ValidationResult,
+) {
+    for (file, content) in &sources.files {
+        for caps in REGISTER_CALL.captures_iter(content) {
+            let literal = caps.get(1).or_else(|| caps.get(2)).unwrap();
+            let name = literal.as_str();
+            let is_declared = declared.iter().any(|d| {
+                name == d
+                    || name
+                        .strip_prefix(d.as_str())
+                        .map(|rest| rest.starts_with(' '))
+                        .unwrap_or(false)
+            });
+            if !is_declared {
+                let call = caps.get(0).unwrap();
+                result.add(
+                    Diagnostic::new(
+                        &rules::UNDECLARED_COMMAND,
+                        format!(
+                            "{} registers command '{}' which is not declared in \
+                             [chimerax.command] (it will not exist until the bundle is loaded)",
+                            file.display(),
+                            name
+                        ),
+                    )
+                    .at(Location::from_span(
+                        file,
+                        content,
+                        call.start()..literal.end() + 1,
+                    )),
+                );
+            }
+        }
+    }
+}
+
+/// Check that formats with `open`/`save` support have an opener/saver.
+///
+/// Either the legacy `open_file()`/`save_file()` methods or a
+/// `run_provider()` that handles `session.open_command`/`session.save_command`
+/// count as handling.
+fn check_formats(sources: &Sources, chimerax: &toml::Value, result: &mut ValidationResult) {
+    let Some(formats) = chimerax.get("data-format").and_then(|f| f.as_table()) else {
+        return;
+    };
+
+    let providers = sources.functions("run_provider");
+    for (action, legacy, manager) in [
+        ("open", "open_file", "open_command"),
+        ("save", "save_file", "save_command"),
+    ] {
+        let handled = !sources.functions(legacy).is_empty()
+            || providers.iter().any(|f| f.body.contains(manager));
+        if handled {
+            continue;
+        }
+        for (name, format) in formats {
+            if format.get(action).is_some() {
+                result.add(
+                    Diagnostic::new(
+                        &rules::UNHANDLED_FORMAT,
+                        format!(
+                            "Data format '{}' declares {} support but src/ has no {}() or \
+                             run_provider() handling session.{}",
+                            name, action, legacy, manager
+                        ),
+                    )
+                    .at_key(&["chimerax", "data-format", name, action]),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn messages<'a>(diagnostics: impl Iterator<Item = &'a Diagnostic>) -> Vec<String> {
+        diagnostics.map(|d| d.message.clone()).collect()
+    }
+
+    fn check_project(pyproject: &str, files: &[(&str, &str)]) -> ValidationResult {
+        let temp = TempDir::new().unwrap();
+        for (name, content) in files {
+            let path = temp.path().join("src").join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        let pyproject: toml::Value = toml::from_str(pyproject).unwrap();
+        let mut result = ValidationResult::default();
+        check(temp.path(), pyproject.get("chimerax").unwrap(), &mut result).unwrap();
+        result
+    }
+
+    const DISPATCH_INIT: &str = r#"
+class _API(BundleAPI):
+    @staticmethod
+    def register_command(bundle_info, command_info, logger):
+        """Register the 'hello' command."""
+        from . import cmd
+        if command_info.name == "hello":
+            register(command_info.name, cmd.hello_desc, cmd.hello)
+
+    @staticmethod
+    def start_tool(session, bundle_info, tool_info):
+        from .tool import MyTool
+        return MyTool(session, tool_info.name)
+"#;
+
+    #[test]
+    fn test_declared_commands_handled() {
+        let result = check_project(
+            r#"
+[chimerax.command.hello]
+[chimerax.tool."My Tool"]
+"#,
+            &[("__init__.py", DISPATCH_INIT)],
+        );
+        assert!(
+            result.errors().next().is_none(),
+            "{:?}",
+            messages(result.errors())
+        );
+        assert!(
+            result.warnings().next().is_none(),
+            "{:?}",
+            messages(result.warnings())
+        );
+    }
+
+    #[test]
+    fn test_declared_command_not_handled() {
+        let result = check_project(
+            r#"
+[chimerax.command.hello]
+[chimerax.command.goodbye]
+"#,
+            &[("__init__.py", DISPATCH_INIT)],
+        );
+        assert_eq!(
+            messages(result.errors()),
+            vec![
+                "Command 'goodbye' is declared in [chimerax.command] but not handled by \
+                 register_command() in src/__init__.py"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_bundle_api_methods() {
+        let result = check_project(
+            r#"
+[chimerax.command.hello]
+[chimerax.tool.viewer]
+"#,
+            &[("__init__.py", "bundle_api = None\n")],
+        );
+        assert!(result
+            .errors()
+            .any(|e| e.message.contains("no register_command() is defined")));
+        assert!(result
+            .errors()
+            .any(|e| e.message.contains("no start_tool() is defined")));
+    }
+
+    #[test]
+    fn test_undeclared_registration() {
+        let cmd = r#"
+def register_all(logger):
+    register("hello", hello_desc, hello)
+    register("hello more", more_desc, more)
+    register('secret', secret_desc, secret)
+"#;
+        let result = check_project(
+            "[chimerax.command.hello]\n",
+            &[
+                (
+                    "__init__.py",
+                    "def register_command(bi, ci, logger):\n    cmd.register_all(logger)\n",
+                ),
+                ("cmd.py", cmd),
+            ],
+        );
+        assert!(
+            result.errors().next().is_none(),
+            "{:?}",
+            messages(result.errors())
+        );
+        let warnings: Vec<_> = result.warnings().collect();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("'secret'"));
+        let location = warnings[0].location.as_ref().unwrap();
+        assert_eq!(location.file, PathBuf::from("src/cmd.py"));
+        assert_eq!((location.line, location.column), (5, 5));
+    }
+
+    #[test]
+    fn test_format_handlers() {
+        let pyproject = r#"
+[chimerax.data-format.XYZ.open]
+[chimerax.data-format.XYZ.save]
+"#;
+        let init = r#"
+def run_provider(session, name, mgr):
+    if mgr == session.open_command:
+        return Opener()
+    # elif mgr == session.save_command:
+    #     return Saver()
+"#;
+        let result = check_project(pyproject, &[("__init__.py", init)]);
+        let errors = messages(result.errors());
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("declares save support"));
+
+        let legacy =
+            "def open_file(session, path):\n    pass\ndef save_file(session, path):\n    pass\n";
+        let result = check_project(pyproject, &[("__init__.py", legacy)]);
+        assert!(
+            result.errors().next().is_none(),
+            "{:?}",
+            messages(result.errors())
+        );
+    }
+}
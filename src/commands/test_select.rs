@@ -0,0 +1,263 @@
+//! Select the tests affected by changed files (used by `echidna watch --test`).
+//!
+//! Changed files under `src/` are mapped to module names, and test modules are
+//! matched by a lightweight scan of their `import` statements. Tests that
+//! failed in the previous pytest run are always included.
+
+use crate::commands::python_source::parse_imports;
+use crate::error::Result;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// pytest's record of the tests that failed in the last run.
+const LAST_FAILED_CACHE: &str = ".pytest_cache/v/cache/lastfailed";
+
+/// Files whose change affects every test.
+const GLOBAL_FILES: &[&str] = &["pyproject.toml", "conftest.py"];
+
+/// Select the test files to run for a set of changed files.
+///
+/// Returns `None` if the whole suite should run: a project-wide file
+/// changed, or no test could be mapped to the changes.
+pub fn select_tests(
+    project_dir: &Path,
+    package: &str,
+    changed: &[PathBuf],
+) -> Result<Option<Vec<PathBuf>>> {
+    let src_dir = project_dir.join("src");
+    let tests_dir = project_dir.join("tests");
+
+    let mut selected = BTreeSet::new();
+    let mut modules = Vec::new();
+    for path in changed {
+        let is_global = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .map(|n| GLOBAL_FILES.contains(&n))
+            .unwrap_or(false);
+        if is_global {
+            return Ok(None);
+        }
+        if path.starts_with(&tests_dir) {
+            if is_test_file(path) && path.exists() {
+                selected.insert(path.clone());
+            }
+        } else if let Some(module) = module_name(&src_dir, package, path) {
+            modules.push(module);
+        }
+    }
+
+    if !modules.is_empty() {
+        for test in find_test_files(&tests_dir)? {
+            let source = fs::read_to_string(&test)?;
+            if parse_imports(&source)
+                .iter()
+                .any(|import| modules.iter().any(|m| imports_module(&import.name, m)))
+            {
+                selected.insert(test);
+            }
+        }
+    }
+
+    if selected.is_empty() {
+        return Ok(None);
+    }
+
+    selected.extend(last_failed_files(project_dir));
+    Ok(Some(selected.into_iter().collect()))
+}
+
+/// Map a source file under `src/` to its module name in the installed bundle.
+///
+/// `src/` is installed as the bundle package, so `src/cmd.py` becomes
+/// `<package>.cmd` and `src/__init__.py` becomes `<package>`. Sources laid
+/// out as `src/chimerax/<pkg>/` (C++ bundles) map to `chimerax.<pkg>...`.
+pub fn module_name(src_dir: &Path, package: &str, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(src_dir).ok()?;
+    if relative.extension()? != "py" {
+        return None;
+    }
+
+    let mut parts: Vec<String> = relative
+        .with_extension("")
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    if parts.last().map(|p| p == "__init__").unwrap_or(false) {
+        parts.pop();
+    }
+
+    if parts.first().map(|p| p == "chimerax").unwrap_or(false) {
+        Some(parts.join("."))
+    } else if parts.is_empty() {
+        Some(package.to_string())
+    } else {
+        Some(format!("{}.{}", package, parts.join(".")))
+    }
+}
+
+/// Whether importing `import` depends on `module` (itself or a submodule of it).
+fn imports_module(import: &str, module: &str) -> bool {
+    import == module
+        || import
+            .strip_prefix(module)
+            .map(|rest| rest.starts_with('.'))
+            .unwrap_or(false)
+}
+
+/// Test files that had failures in the last pytest run.
+fn last_failed_files(project_dir: &Path) -> Vec<PathBuf> {
+    let Ok(content) = fs::read_to_string(project_dir.join(LAST_FAILED_CACHE)) else {
+        return Vec::new();
+    };
+    let Ok(serde_json::Value::Object(map)) = serde_json::from_str(&content) else {
+        return Vec::new();
+    };
+    map.keys()
+        .filter_map(|node_id| node_id.split("::").next())
+        .map(|file| project_dir.join(file))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// Find pytest test modules (`test_*.py` or `*_test.py`) recursively.
+fn find_test_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if dir.is_dir() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                files.extend(find_test_files(&path)?);
+            } else if is_test_file(&path) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Whether a path looks like a pytest test module.
+fn is_test_file(path: &Path) -> bool {
+    path.extension().map(|e| e == "py").unwrap_or(false)
+        && path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| s.starts_with("test_") || s.ends_with("_test"))
+            .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_module_name() {
+        let src = Path::new("/p/src");
+        let pkg = "chimerax.mytool";
+        assert_eq!(
+            module_name(src, pkg, Path::new("/p/src/cmd.py")).as_deref(),
+            Some("chimerax.mytool.cmd")
+        );
+        assert_eq!(
+            module_name(src, pkg, Path::new("/p/src/__init__.py")).as_deref(),
+            Some("chimerax.mytool")
+        );
+        assert_eq!(
+            module_name(src, pkg, Path::new("/p/src/io/reader.py")).as_deref(),
+            Some("chimerax.mytool.io.reader")
+        );
+        assert_eq!(
+            module_name(src, pkg, Path::new("/p/src/chimerax/ext/cmd.py")).as_deref(),
+            Some("chimerax.ext.cmd")
+        );
+        assert_eq!(module_name(src, pkg, Path::new("/p/src/data.json")), None);
+        assert_eq!(module_name(src, pkg, Path::new("/p/tests/test_a.py")), None);
+    }
+
+    #[test]
+    fn test_imports_module() {
+        assert!(imports_module("chimerax.mytool.cmd", "chimerax.mytool.cmd"));
+        assert!(imports_module(
+            "chimerax.mytool.io.reader",
+            "chimerax.mytool.io"
+        ));
+        assert!(!imports_module("chimerax.mytool", "chimerax.mytool.cmd"));
+        assert!(!imports_module("chimerax.mytoolkit", "chimerax.mytool"));
+    }
+
+    fn project() -> TempDir {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("src")).unwrap();
+        fs::create_dir_all(temp.path().join("tests")).unwrap();
+        fs::write(
+            temp.path().join("tests/test_cmd.py"),
+            "from chimerax.mytool.cmd import mytool\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("tests/test_other.py"),
+            "import chimerax.mytool.other\nfrom chimerax.mytool.io import (\n    reader,\n)\n",
+        )
+        .unwrap();
+        temp
+    }
+
+    #[test]
+    fn test_select_tests_by_import() {
+        let temp = project();
+        let root = temp.path();
+        let selected = select_tests(root, "chimerax.mytool", &[root.join("src/cmd.py")])
+            .unwrap()
+            .unwrap();
+        assert_eq!(selected, vec![root.join("tests/test_cmd.py")]);
+
+        let selected = select_tests(root, "chimerax.mytool", &[root.join("src/io/reader.py")])
+            .unwrap()
+            .unwrap();
+        assert_eq!(selected, vec![root.join("tests/test_other.py")]);
+    }
+
+    #[test]
+    fn test_select_changed_test_and_last_failed() {
+        let temp = project();
+        let root = temp.path();
+        fs::create_dir_all(root.join(".pytest_cache/v/cache")).unwrap();
+        fs::write(
+            root.join(LAST_FAILED_CACHE),
+            r#"{"tests/test_other.py::test_x": true}"#,
+        )
+        .unwrap();
+
+        let selected = select_tests(root, "chimerax.mytool", &[root.join("tests/test_cmd.py")])
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            selected,
+            vec![
+                root.join("tests/test_cmd.py"),
+                root.join("tests/test_other.py")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_runs_everything() {
+        let temp = project();
+        let root = temp.path();
+        // Project-wide file
+        assert!(
+            select_tests(root, "chimerax.mytool", &[root.join("pyproject.toml")])
+                .unwrap()
+                .is_none()
+        );
+        // No test imports the changed module
+        assert!(
+            select_tests(root, "chimerax.mytool", &[root.join("src/unused.py")])
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -2,15 +2,38 @@
 //!
 //! Note: This module is named `testing` because `test` is a reserved keyword in Rust.
 
-use crate::chimerax::{ChimeraXExecutor, Verbosity};
-use crate::commands::{build, install};
+use crate::chimerax::{validate_path_for_command, ChimeraXExecutor, Verbosity};
+use crate::commands::{build, flaky, install, script_tests, visual};
+use crate::config::{CoverageConfig, VisualConfig};
 use crate::error::{EchidnaError, Result};
-use std::path::PathBuf;
+use crate::test_report::{ReportSpec, TestCounts, TestReport};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// pytest's JUnit XML output (relative to the project).
+const PYTEST_RESULTS: &str = "build/test-results/pytest.xml";
+
+/// Coverage data file written by pytest-cov in the project directory.
+const COVERAGE_DATA_FILE: &str = ".coverage";
+
+/// Prefix of the line reporting the combined coverage total.
+const COVERAGE_TOTAL_MARKER: &str = "ECHIDNA_COVERAGE_TOTAL=";
+
+/// Directory the pytest plugin is written to (relative to the project).
+const PYTEST_PLUGIN_DIR: &str = "build/pytest-plugin";
+
+/// Module name of the pytest plugin providing ChimeraX session fixtures.
+const PYTEST_PLUGIN_NAME: &str = "echidna_chimerax";
+
+/// Source of the pytest plugin.
+const PYTEST_PLUGIN: &str = include_str!("../../templates/pytest/echidna_chimerax.py");
 
 /// Arguments for the test command.
 pub struct TestArgs {
     /// Project directory
     pub path: PathBuf,
+    /// Test files to run (defaults to all of tests/)
+    pub paths: Vec<PathBuf>,
     /// Test filter expression (-k)
     pub filter: Option<String>,
     /// Increase pytest verbosity
@@ -21,16 +44,255 @@ pub struct TestArgs {
     pub no_install: bool,
     /// Generate coverage report
     pub coverage: bool,
+    /// Coverage threshold, branch coverage, and extra report formats
+    pub coverage_config: CoverageConfig,
+    /// Fail a single test that runs longer than this many seconds
+    pub timeout: Option<f64>,
+    /// Rerun failing tests up to this many times
+    pub retries: u32,
     /// Additional pytest arguments
     pub pytest_args: Vec<String>,
+    /// Also run `.cxc` script tests from tests/
+    pub scripts: bool,
+    /// Also run image regression tests from tests/visual/
+    pub visual: bool,
+    /// Accept new renders as visual test baselines
+    pub update_baselines: bool,
+    /// Visual test settings from echidna.toml
+    pub visual_config: VisualConfig,
+    /// Reports to write (JUnit XML or JSON)
+    pub reports: Vec<ReportSpec>,
     /// Path to ChimeraX executable
     pub chimerax: PathBuf,
     /// Verbosity level
     pub verbosity: Verbosity,
 }
 
+/// Results of testing a single project.
+pub struct TestRun {
+    /// Collected test results (pytest, plus visual tests if enabled)
+    pub report: TestReport,
+    /// Overall outcome; an error if any test failed
+    pub result: Result<()>,
+}
+
 /// Execute the test command.
 pub fn execute(args: TestArgs) -> Result<()> {
+    let run = run(&args)?;
+
+    let counts = run.report.counts();
+    if counts.total() > 0 {
+        println!();
+        println!("Summary: {}", counts);
+    }
+    write_reports(&run.report, &args.reports)?;
+
+    run.result
+}
+
+/// Test every workspace member and merge their results into one report.
+///
+/// With coverage enabled, the members' `.coverage` data is combined into a
+/// workspace report in `root`; the coverage threshold applies to the combined
+/// total rather than to each member.
+pub fn execute_workspace(
+    root: &Path,
+    mut members: Vec<TestArgs>,
+    reports: &[ReportSpec],
+) -> Result<()> {
+    println!("Testing {} bundles in workspace...\n", members.len());
+
+    let coverage = members.first().filter(|args| args.coverage).map(|args| {
+        (
+            args.coverage_config.clone(),
+            args.chimerax.clone(),
+            args.verbosity,
+        )
+    });
+    for args in &mut members {
+        args.coverage_config.fail_under = None;
+    }
+
+    let mut merged = TestReport::default();
+    let mut summary: Vec<(String, Option<TestCounts>, bool)> = Vec::new();
+    for args in &members {
+        println!("=== {} ===", args.path.display());
+        let name = member_name(&args.path);
+        let (counts, passed) = match run(args) {
+            Ok(run) => {
+                let counts = run.report.counts();
+                merged.merge(run.report.prefixed(&name));
+                (Some(counts), run.result.is_ok())
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                (None, false)
+            }
+        };
+        if !passed {
+            eprintln!("Tests failed for {}", args.path.display());
+        }
+        println!();
+        summary.push((name, counts, passed));
+    }
+
+    println!("=== Summary ===");
+    let width = summary.iter().map(|(n, _, _)| n.len()).max().unwrap_or(0);
+    for (name, counts, passed) in &summary {
+        let mark = if *passed { "✓" } else { "✗" };
+        match counts {
+            Some(counts) => println!("  {} {:<width$}  {}", mark, name, counts, width = width),
+            None => println!("  {} {:<width$}  did not run", mark, name, width = width),
+        }
+    }
+    println!();
+
+    write_reports(&merged, reports)?;
+
+    let coverage_result = match coverage {
+        Some((config, chimerax, verbosity)) => {
+            println!("=== Combined Coverage ===");
+            let executor = ChimeraXExecutor::new(chimerax, verbosity);
+            let data_files: Vec<PathBuf> = members
+                .iter()
+                .map(|args| args.path.join(COVERAGE_DATA_FILE))
+                .filter(|path| path.exists())
+                .collect();
+            let result = combine_coverage(&executor, root, &data_files, &config);
+            println!();
+            result
+        }
+        None => Ok(()),
+    };
+
+    let failed = summary.iter().filter(|(_, _, passed)| !passed).count();
+    if failed > 0 {
+        return Err(EchidnaError::TestFailed(1));
+    }
+    coverage_result?;
+    println!("All {} bundles passed tests.", members.len());
+    Ok(())
+}
+
+/// Combine coverage data files into a workspace report in `root`.
+///
+/// Writes `.coverage`, `htmlcov/`, and any configured XML/LCOV reports, and
+/// checks the combined total against `fail_under`.
+fn combine_coverage(
+    executor: &ChimeraXExecutor,
+    root: &Path,
+    data_files: &[PathBuf],
+    config: &CoverageConfig,
+) -> Result<()> {
+    if data_files.is_empty() {
+        println!("No coverage data found in workspace members.");
+        return Ok(());
+    }
+
+    let data_file = root.join(COVERAGE_DATA_FILE);
+    let html_dir = root.join("htmlcov");
+    validate_path_for_command(&data_file)?;
+    for path in data_files {
+        validate_path_for_command(path)?;
+    }
+
+    let mut extra_reports = String::new();
+    if let Some(xml) = &config.xml {
+        let path = root.join(xml);
+        validate_path_for_command(&path)?;
+        extra_reports.push_str(&format!("cov.xml_report(outfile=\"{}\")\n", path.display()));
+    }
+    if let Some(lcov) = &config.lcov {
+        let path = root.join(lcov);
+        validate_path_for_command(&path)?;
+        extra_reports.push_str(&format!(
+            "cov.lcov_report(outfile=\"{}\")\n",
+            path.display()
+        ));
+    }
+
+    let data_files_str = data_files
+        .iter()
+        .map(|p| format!("\"{}\"", p.display()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let python_code = format!(
+        r#"
+import sys
+
+try:
+    import coverage
+except ImportError:
+    print("ERROR: coverage is not installed in ChimeraX Python environment")
+    print("Install it with: ChimeraX -m pip install pytest-cov")
+    sys.exit(1)
+
+cov = coverage.Coverage(data_file="{data_file}", branch={branch})
+cov.combine([{data_files}], keep=True)
+cov.save()
+total = cov.report(show_missing=True)
+cov.html_report(directory="{html_dir}")
+{extra_reports}
+print("{marker}%.2f" % total)
+"#,
+        data_file = data_file.display(),
+        branch = if config.branch { "True" } else { "False" },
+        data_files = data_files_str,
+        html_dir = html_dir.display(),
+        extra_reports = extra_reports,
+        marker = COVERAGE_TOTAL_MARKER,
+    );
+
+    let output = run_pytest_command(executor, &runscript_command(&python_code))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    print!(
+        "{}",
+        stdout
+            .lines()
+            .filter(|line| !line.starts_with(COVERAGE_TOTAL_MARKER))
+            .map(|line| format!("{}\n", line))
+            .collect::<String>()
+    );
+
+    let total = parse_coverage_total(&stdout).ok_or_else(|| {
+        EchidnaError::ChimeraXCommandFailed(format!(
+            "combining coverage data failed\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    })?;
+
+    println!();
+    println!("Combined coverage: {:.1}%", total);
+    println!("  HTML: {}/index.html", html_dir.display());
+    if let Some(xml) = &config.xml {
+        println!("  XML:  {}", root.join(xml).display());
+    }
+    if let Some(lcov) = &config.lcov {
+        println!("  LCOV: {}", root.join(lcov).display());
+    }
+
+    match config.fail_under {
+        Some(required) if total < required => {
+            Err(EchidnaError::CoverageBelowThreshold { total, required })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Extract the total percentage printed by the coverage combine script.
+fn parse_coverage_total(stdout: &str) -> Option<f64> {
+    stdout
+        .lines()
+        .find_map(|line| line.strip_prefix(COVERAGE_TOTAL_MARKER))
+        .and_then(|total| total.trim().parse().ok())
+}
+
+/// Build, install, and run the tests of a single project.
+///
+/// Setup problems (missing tests/, build or install failure) are returned as
+/// errors; test failures are reported through [`TestRun::result`].
+pub fn run(args: &TestArgs) -> Result<TestRun> {
     let project_dir = args.path.canonicalize().unwrap_or(args.path.clone());
 
     // Check if tests directory exists
@@ -60,37 +322,165 @@ pub fn execute(args: TestArgs) -> Result<()> {
             path: project_dir.clone(),
             wheel: None,
             user: false,
+            index: None,
             chimerax: args.chimerax.clone(),
             verbosity: args.verbosity,
         })?;
         println!();
     }
 
+    let executor = ChimeraXExecutor::new(args.chimerax.clone(), args.verbosity);
+    let mut report = TestReport::default();
+
     println!("=== Running Tests ===");
+    let junit_path = project_dir.join(PYTEST_RESULTS);
+    if junit_path.exists() {
+        fs::remove_file(&junit_path)?;
+    }
+    if let Some(parent) = junit_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut result = run_pytest(args, &executor, &project_dir, &tests_dir, &junit_path);
+
+    // pytest may not write results at all (e.g., if it is not installed)
+    if junit_path.exists() {
+        let xml = fs::read_to_string(&junit_path)?;
+        match TestReport::parse_junit_xml(&xml, "pytest") {
+            Ok(suite) => report.suites.push(suite),
+            Err(e) => eprintln!("Warning: could not read pytest results: {}", e),
+        }
+    }
+
+    if args.retries > 0 {
+        record_flaky_tests(&project_dir, &report);
+    }
+
+    if args.scripts {
+        println!();
+        println!("=== Running Script Tests ===");
+        let suite = script_tests::execute(&executor, &project_dir)?;
+        let failures = suite.counts().failed;
+        report.suites.push(suite);
+        if result.is_ok() && failures > 0 {
+            result = Err(EchidnaError::ScriptTestFailed(failures));
+        }
+    }
+
+    if args.visual || args.update_baselines {
+        println!();
+        println!("=== Running Visual Tests ===");
+        let results = visual::execute(
+            &executor,
+            &project_dir,
+            &args.visual_config,
+            args.update_baselines,
+        )?;
+        report.suites.push(visual::to_suite(&results));
+
+        let failures = visual::failure_count(&results);
+        if result.is_ok() && failures > 0 {
+            result = Err(EchidnaError::VisualRegression(failures));
+        }
+    }
+
+    Ok(TestRun { report, result })
+}
+
+/// Update the flaky test history and list this run's flaky tests.
+fn record_flaky_tests(project_dir: &Path, report: &TestReport) {
+    let mut history = flaky::FlakyHistory::load(project_dir).unwrap_or_else(|e| {
+        eprintln!(
+            "Warning: resetting unreadable {}: {}",
+            flaky::HISTORY_FILE,
+            e
+        );
+        flaky::FlakyHistory::default()
+    });
+    let flaky_tests = history.record(report);
+    if let Err(e) = history.save(project_dir) {
+        eprintln!("Warning: could not save {}: {}", flaky::HISTORY_FILE, e);
+    }
+
+    if !flaky_tests.is_empty() {
+        println!();
+        println!("Flaky tests (passed on retry):");
+        for (id, record) in &flaky_tests {
+            println!(
+                "  ~ {} (flaky in {} of {} runs)",
+                id, record.flaky_runs, history.runs
+            );
+        }
+    }
+}
+
+/// Write every requested report.
+fn write_reports(report: &TestReport, specs: &[ReportSpec]) -> Result<()> {
+    for spec in specs {
+        report.write(spec)?;
+        println!("Report written: {}", spec.path.display());
+    }
+    Ok(())
+}
+
+/// Suite name prefix for a workspace member (its directory name).
+fn member_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Run the pytest suite through ChimeraX's Python.
+fn run_pytest(
+    args: &TestArgs,
+    executor: &ChimeraXExecutor,
+    project_dir: &Path,
+    tests_dir: &Path,
+    junit_path: &Path,
+) -> Result<()> {
+    validate_path_for_command(junit_path)?;
+
+    let plugin_dir = write_pytest_plugin(project_dir)?;
+
     if args.coverage {
         println!("  (coverage enabled)");
     }
 
-    let executor = ChimeraXExecutor::new(args.chimerax, args.verbosity);
-
     // Build pytest arguments
-    let mut pytest_args = vec![format!("\"{}\"", tests_dir.display())];
+    let mut pytest_args = if args.paths.is_empty() {
+        vec![format!("\"{}\"", tests_dir.display())]
+    } else {
+        args.paths
+            .iter()
+            .map(|path| {
+                validate_path_for_command(path)?;
+                Ok(format!("\"{}\"", path.display()))
+            })
+            .collect::<Result<Vec<_>>>()?
+    };
+    pytest_args.push(format!("\"--junitxml={}\"", junit_path.display()));
+    pytest_args.push(format!("\"-p\", \"{}\"", PYTEST_PLUGIN_NAME));
 
     if args.verbose {
         pytest_args.push("-v".to_string());
     }
 
+    if let Some(timeout) = args.timeout {
+        pytest_args.push(format!("\"--echidna-timeout={}\"", timeout));
+    }
+    if args.retries > 0 {
+        pytest_args.push(format!("\"--echidna-retries={}\"", args.retries));
+    }
+
     // Add coverage arguments
     if args.coverage {
         // Get the package name from pyproject.toml for coverage source
-        let package_name = get_package_name(&project_dir);
+        let package_name = get_package_name(project_dir);
         if let Some(pkg) = package_name {
             pytest_args.push(format!("\"--cov={}\"", pkg));
         } else {
             pytest_args.push("\"--cov=src\"".to_string());
         }
-        pytest_args.push("\"--cov-report=term-missing\"".to_string());
-        pytest_args.push("\"--cov-report=html:htmlcov\"".to_string());
+        pytest_args.extend(coverage_args(&args.coverage_config, project_dir)?);
     }
 
     if let Some(filter) = &args.filter {
@@ -148,20 +538,24 @@ except ImportError:
     print("Install it with: ChimeraX -m pip install pytest")
     sys.exit(1)
 {coverage_check}
+# Make the echidna plugin importable and hand it the session
+sys.path.insert(0, "{plugin_dir}")
+import {plugin_name}
+{plugin_name}.set_session(globals().get("session"))
+
 # Run pytest
 exit_code = pytest.main([{pytest_args}])
 sys.exit(exit_code)
 "#,
         project_dir = project_dir.display(),
         coverage_check = coverage_check,
+        plugin_dir = plugin_dir.display(),
+        plugin_name = PYTEST_PLUGIN_NAME,
         pytest_args = pytest_args_str
     );
 
-    let escaped = python_code.replace('\n', "\\n").replace('"', "\\\"");
-    let cmd = format!("runscript python -c \"exec(\\\"{}\\\")\"", escaped);
-
     // Run the command and capture output
-    let output = run_pytest_command(&executor, &cmd)?;
+    let output = run_pytest_command(executor, &runscript_command(&python_code))?;
 
     // Parse and display results
     let stdout = String::from_utf8_lossy(&output.stdout);
@@ -181,16 +575,93 @@ sys.exit(exit_code)
             println!();
             println!("Coverage report generated:");
             println!("  HTML: {}/htmlcov/index.html", project_dir.display());
+            if let Some(xml) = &args.coverage_config.xml {
+                println!("  XML:  {}", project_dir.join(xml).display());
+            }
+            if let Some(lcov) = &args.coverage_config.lcov {
+                println!("  LCOV: {}", project_dir.join(lcov).display());
+            }
         }
         Ok(())
+    } else if let Some((total, required)) = parse_coverage_failure(&stdout) {
+        Err(EchidnaError::CoverageBelowThreshold { total, required })
     } else {
         let exit_code = output.status.code().unwrap_or(-1);
         Err(EchidnaError::TestFailed(exit_code))
     }
 }
 
+/// The total and required coverage of a pytest run that failed only because
+/// of `--cov-fail-under`.
+fn parse_coverage_failure(stdout: &str) -> Option<(f64, f64)> {
+    // pytest's summary line, e.g. "==== 1 failed, 2 passed in 0.12s ===="
+    let tests_failed = stdout
+        .lines()
+        .any(|line| line.starts_with('=') && (line.contains(" failed") || line.contains(" error")));
+    if tests_failed {
+        return None;
+    }
+    // "FAIL Required test coverage of 80% not reached. Total coverage: 72.35%"
+    let line = stdout
+        .lines()
+        .find(|line| line.contains("Required test coverage of"))?;
+    let required = line
+        .split("coverage of ")
+        .nth(1)?
+        .split('%')
+        .next()?
+        .trim()
+        .parse()
+        .ok()?;
+    let total = line
+        .split("Total coverage: ")
+        .nth(1)?
+        .trim()
+        .trim_end_matches('%')
+        .parse()
+        .ok()?;
+    Some((total, required))
+}
+
+/// Build the pytest-cov report and threshold arguments (already quoted).
+fn coverage_args(config: &CoverageConfig, project_dir: &Path) -> Result<Vec<String>> {
+    let mut args = vec![
+        "\"--cov-report=term-missing\"".to_string(),
+        "\"--cov-report=html:htmlcov\"".to_string(),
+    ];
+    if config.branch {
+        args.push("\"--cov-branch\"".to_string());
+    }
+    if let Some(xml) = &config.xml {
+        let path = project_dir.join(xml);
+        validate_path_for_command(&path)?;
+        args.push(format!("\"--cov-report=xml:{}\"", path.display()));
+    }
+    if let Some(lcov) = &config.lcov {
+        let path = project_dir.join(lcov);
+        validate_path_for_command(&path)?;
+        args.push(format!("\"--cov-report=lcov:{}\"", path.display()));
+    }
+    if let Some(fail_under) = config.fail_under {
+        args.push(format!("\"--cov-fail-under={}\"", fail_under));
+    }
+    Ok(args)
+}
+
+/// Write the pytest plugin module and return its directory.
+fn write_pytest_plugin(project_dir: &Path) -> Result<PathBuf> {
+    let plugin_dir = project_dir.join(PYTEST_PLUGIN_DIR);
+    validate_path_for_command(&plugin_dir)?;
+    fs::create_dir_all(&plugin_dir)?;
+    fs::write(
+        plugin_dir.join(format!("{}.py", PYTEST_PLUGIN_NAME)),
+        PYTEST_PLUGIN,
+    )?;
+    Ok(plugin_dir)
+}
+
 /// Get the package name from pyproject.toml for coverage.
-fn get_package_name(project_dir: &std::path::Path) -> Option<String> {
+pub(crate) fn get_package_name(project_dir: &std::path::Path) -> Option<String> {
     let pyproject_path = project_dir.join("pyproject.toml");
     let content = std::fs::read_to_string(pyproject_path).ok()?;
     let pyproject: toml::Value = toml::from_str(&content).ok()?;
@@ -221,6 +692,12 @@ fn is_valid_pytest_filter(filter: &str) -> bool {
     true
 }
 
+/// Wrap Python code in a ChimeraX `runscript` command.
+fn runscript_command(python_code: &str) -> String {
+    let escaped = python_code.replace('\n', "\\n").replace('"', "\\\"");
+    format!("runscript python -c \"exec(\\\"{}\\\")\"", escaped)
+}
+
 /// Run pytest command and return output (without checking exit code).
 fn run_pytest_command(executor: &ChimeraXExecutor, cmd: &str) -> Result<std::process::Output> {
     // We need to run the command without the automatic exit code check
@@ -278,6 +755,84 @@ package = "chimerax.mytest"
         assert_eq!(pkg, Some("chimerax.mytest".to_string()));
     }
 
+    #[test]
+    fn test_write_pytest_plugin() {
+        use tempfile::TempDir;
+
+        let temp = TempDir::new().unwrap();
+        let dir = write_pytest_plugin(temp.path()).unwrap();
+        let plugin = std::fs::read_to_string(dir.join("echidna_chimerax.py")).unwrap();
+        assert!(plugin.contains("def set_session(session)"));
+        assert!(plugin.contains("def open_structure("));
+        assert!(plugin.contains("def chimerax_log("));
+    }
+
+    #[test]
+    fn test_coverage_args_default() {
+        let args = coverage_args(&CoverageConfig::default(), Path::new("/p")).unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "\"--cov-report=term-missing\"",
+                "\"--cov-report=html:htmlcov\""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_coverage_args_configured() {
+        let config = CoverageConfig {
+            fail_under: Some(80.5),
+            branch: true,
+            xml: Some(PathBuf::from("build/coverage.xml")),
+            lcov: Some(PathBuf::from("/abs/coverage.lcov")),
+        };
+        let args = coverage_args(&config, Path::new("/p")).unwrap();
+        assert!(args.contains(&"\"--cov-branch\"".to_string()));
+        assert!(args.contains(&"\"--cov-report=xml:/p/build/coverage.xml\"".to_string()));
+        assert!(args.contains(&"\"--cov-report=lcov:/abs/coverage.lcov\"".to_string()));
+        assert!(args.contains(&"\"--cov-fail-under=80.5\"".to_string()));
+    }
+
+    #[test]
+    fn test_parse_coverage_total() {
+        let stdout = "Name  Stmts  Miss  Cover\nTOTAL  10  2  80%\nECHIDNA_COVERAGE_TOTAL=80.00\n";
+        assert_eq!(parse_coverage_total(stdout), Some(80.0));
+        assert_eq!(parse_coverage_total("TOTAL 80%"), None);
+    }
+
+    #[test]
+    fn test_parse_coverage_failure() {
+        let fail = "FAIL Required test coverage of 80% not reached. Total coverage: 72.35%
+";
+        assert_eq!(
+            parse_coverage_failure(&format!("{}==== 3 passed in 0.12s ====\n", fail)),
+            Some((72.35, 80.0))
+        );
+        assert_eq!(
+            parse_coverage_failure(&format!("{}==== 1 failed, 2 passed in 0.12s ====\n", fail)),
+            None
+        );
+        assert_eq!(
+            parse_coverage_failure("==== 1 failed in 0.12s ====\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_runscript_command() {
+        let cmd = runscript_command("print(\"hi\")\nx = 1");
+        assert_eq!(
+            cmd,
+            "runscript python -c \"exec(\\\"print(\\\"hi\\\")\\nx = 1\\\")\""
+        );
+    }
+
+    #[test]
+    fn test_member_name() {
+        assert_eq!(member_name(Path::new("/ws/bundles/my-tool")), "my-tool");
+    }
+
     #[test]
     fn test_get_package_name_missing() {
         use tempfile::TempDir;
@@ -1,6 +1,13 @@
 //! `echidna validate` command implementation.
 
+use crate::chimerax::{ChimeraXExecutor, Verbosity};
+use crate::commands::{
+    bundle_schema, extension_check, fix, package_data_check, session_check, source_check,
+};
+use crate::config::{RuleLevel, ValidateConfig};
+use crate::diagnostics::{self, find_rule, rules, Diagnostic, Location, OutputFormat, Severity};
 use crate::error::{EchidnaError, Result};
+use regex::Regex;
 use std::path::{Path, PathBuf};
 
 /// Arguments for the validate command.
@@ -9,31 +16,58 @@ pub struct ValidateArgs {
     pub path: PathBuf,
     /// Treat warnings as errors
     pub strict: bool,
+    /// Output format
+    pub format: OutputFormat,
+    /// Apply automatic fixes to pyproject.toml
+    pub fix: bool,
+    /// Also apply unsafe fixes
+    pub unsafe_fixes: bool,
+    /// Show fixes as a diff without writing them
+    pub dry_run: bool,
+    /// Save and restore a session in ChimeraX
+    pub session: bool,
+    /// Check in ChimeraX that extension modules are importable
+    pub check_imports: bool,
+    /// ChimeraX executable, used by the session and import checks
+    pub chimerax: Option<PathBuf>,
+    /// Verbosity level
+    pub verbosity: Verbosity,
 }
 
 /// Validation result with issues found.
 #[derive(Debug, Default)]
 pub struct ValidationResult {
-    pub errors: Vec<String>,
-    pub warnings: Vec<String>,
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 impl ValidationResult {
     pub fn is_valid(&self) -> bool {
-        self.errors.is_empty()
+        self.errors().next().is_none()
     }
 
     /// Check if valid considering strict mode.
     pub fn is_valid_strict(&self) -> bool {
-        self.errors.is_empty() && self.warnings.is_empty()
+        self.diagnostics.is_empty()
     }
 
-    pub fn add_error(&mut self, msg: impl Into<String>) {
-        self.errors.push(msg.into());
+    pub fn add(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
     }
 
-    pub fn add_warning(&mut self, msg: impl Into<String>) {
-        self.warnings.push(msg.into());
+    /// Diagnostics with error severity.
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.with_severity(Severity::Error)
+    }
+
+    /// Diagnostics with warning severity.
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.with_severity(Severity::Warning)
+    }
+
+    fn with_severity(&self, severity: Severity) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(move |d| d.severity == severity)
     }
 }
 
@@ -41,69 +75,273 @@ impl ValidationResult {
 pub fn execute(args: ValidateArgs) -> Result<()> {
     let project_dir = args.path.canonicalize().unwrap_or(args.path.clone());
 
-    println!("Validating bundle in {}...", project_dir.display());
-    if args.strict {
-        println!("  (strict mode: warnings are errors)");
+    if args.fix {
+        if args.dry_run && args.format != OutputFormat::Text {
+            return Err(EchidnaError::ConfigError(
+                "--fix --dry-run prints a diff and cannot be combined with --format".into(),
+            ));
+        }
+        apply_fixes(&args, &project_dir)?;
+        if args.dry_run {
+            return Ok(());
+        }
     }
-    println!();
 
-    let result = validate_bundle(&project_dir)?;
+    let executor = args
+        .chimerax
+        .clone()
+        .map(|path| ChimeraXExecutor::new(path, args.verbosity));
+    if (args.session || args.check_imports) && executor.is_none() {
+        return Err(EchidnaError::ChimeraXNotFound);
+    }
+    let checks = ChimeraXChecks {
+        executor: executor.as_ref(),
+        session: args.session,
+        imports: args.check_imports,
+    };
+    let result = validate_bundle_with(&project_dir, &checks)?;
+    let is_valid = if args.strict {
+        result.is_valid_strict()
+    } else {
+        result.is_valid()
+    };
 
-    // Print warnings
-    for warning in &result.warnings {
-        if args.strict {
-            println!("  ✗ {}", warning);
-        } else {
-            println!("  ⚠ {}", warning);
+    match args.format {
+        OutputFormat::Text => print_text(&args, &project_dir, &result, is_valid),
+        OutputFormat::Json => println!(
+            "{}",
+            diagnostics::to_json(&result.diagnostics, &args.path, args.strict)?
+        ),
+        OutputFormat::Sarif => println!(
+            "{}",
+            diagnostics::to_sarif(&result.diagnostics, &args.path, args.strict)?
+        ),
+    }
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(EchidnaError::ConfigError("bundle validation failed".into()))
+    }
+}
+
+/// Fix pyproject.toml, or print the fixes as a diff in dry-run mode.
+fn apply_fixes(args: &ValidateArgs, project_dir: &Path) -> Result<()> {
+    let result = validate_bundle(project_dir)?;
+    if !result
+        .diagnostics
+        .iter()
+        .any(|d| fix::applicability(d.rule).is_some())
+    {
+        if args.format == OutputFormat::Text {
+            println!("No automatic fixes available.");
+            println!();
         }
+        return Ok(());
     }
 
-    // Print errors
-    for error in &result.errors {
-        println!("  ✗ {}", error);
+    let pyproject_path = project_dir.join("pyproject.toml");
+    let content = std::fs::read_to_string(&pyproject_path)?;
+    let outcome = fix::apply(&content, &result.diagnostics, args.unsafe_fixes)?;
+
+    if args.dry_run {
+        print!("{}", outcome.diff(&content));
+        println!();
+        for applied in &outcome.applied {
+            println!(
+                "Would fix [{}] ({}): {}",
+                applied.rule.code, applied.applicability, applied.description
+            );
+        }
+    } else {
+        if !outcome.applied.is_empty() {
+            std::fs::write(&pyproject_path, &outcome.content)?;
+        }
+        if args.format == OutputFormat::Text {
+            for applied in &outcome.applied {
+                println!(
+                    "Fixed [{}] ({}): {}",
+                    applied.rule.code, applied.applicability, applied.description
+                );
+            }
+        }
     }
 
+    if args.format == OutputFormat::Text {
+        for rule in &outcome.skipped {
+            println!(
+                "Skipped [{}] {}: unsafe fix, rerun with --unsafe-fixes to apply",
+                rule.code, rule.name
+            );
+        }
+        println!();
+    }
+    Ok(())
+}
+
+/// Print diagnostics in rustc style with a summary.
+fn print_text(args: &ValidateArgs, project_dir: &Path, result: &ValidationResult, is_valid: bool) {
+    println!("Validating bundle in {}...", project_dir.display());
+    if args.strict {
+        println!("  (strict mode: warnings are errors)");
+    }
     println!();
 
-    let is_valid = if args.strict {
-        result.is_valid_strict()
-    } else {
-        result.is_valid()
-    };
+    for diagnostic in &result.diagnostics {
+        println!("{}", diagnostic.render(project_dir, args.strict));
+    }
 
+    let warning_count = result.warnings().count();
     if is_valid {
         println!("✓ Bundle is valid");
-        if !args.strict && !result.warnings.is_empty() {
+        if !args.strict && warning_count > 0 {
             println!(
                 "  ({} warning{})",
-                result.warnings.len(),
-                if result.warnings.len() == 1 { "" } else { "s" }
+                warning_count,
+                if warning_count == 1 { "" } else { "s" }
             );
         }
-        Ok(())
     } else {
         let error_count = if args.strict {
-            result.errors.len() + result.warnings.len()
+            result.diagnostics.len()
         } else {
-            result.errors.len()
+            result.errors().count()
         };
         println!(
             "✗ Validation failed with {} error{}",
             error_count,
             if error_count == 1 { "" } else { "s" }
         );
-        Err(EchidnaError::ConfigError("bundle validation failed".into()))
+    }
+
+    let (safe, needs_unsafe): (Vec<_>, Vec<_>) = result
+        .diagnostics
+        .iter()
+        .filter_map(|d| fix::applicability(d.rule))
+        .partition(|a| *a == fix::Applicability::Safe);
+    if !safe.is_empty() {
+        println!("  {} fixable with `echidna validate --fix`", safe.len());
+    }
+    if !needs_unsafe.is_empty() {
+        println!(
+            "  {} more fixable with `echidna validate --fix --unsafe-fixes`",
+            needs_unsafe.len()
+        );
+    }
+    if !result.diagnostics.is_empty() {
+        println!("  For more information about a diagnostic, run `echidna explain <CODE>`");
     }
 }
 
 /// Validate a bundle directory structure and configuration.
+///
+/// Rule levels from `[validate]` in echidna.toml and inline suppressions in
+/// pyproject.toml are applied to the diagnostics.
 pub fn validate_bundle(project_dir: &Path) -> Result<ValidationResult> {
+    validate_bundle_with(project_dir, &ChimeraXChecks::default())
+}
+
+/// Checks that run in ChimeraX.
+#[derive(Default)]
+pub struct ChimeraXChecks<'a> {
+    /// ChimeraX to run the checks in (no checks run without it)
+    pub executor: Option<&'a ChimeraXExecutor>,
+    /// Save and restore a session
+    pub session: bool,
+    /// Check that extension modules are importable
+    pub imports: bool,
+}
+
+/// Validate a bundle, also running the given checks in ChimeraX.
+pub fn validate_bundle_with(
+    project_dir: &Path,
+    checks: &ChimeraXChecks,
+) -> Result<ValidationResult> {
+    let mut result = check_bundle(project_dir, checks)?;
+
+    let pyproject_path = project_dir.join("pyproject.toml");
+    if pyproject_path.exists() {
+        let content = std::fs::read_to_string(&pyproject_path)?;
+        apply_suppressions(&mut result, &content);
+    }
+    apply_rule_levels(&mut result, &ValidateConfig::load(project_dir)?)?;
+
+    Ok(result)
+}
+
+/// Apply `[validate]` rule levels: drop allowed diagnostics and override severities.
+pub(crate) fn apply_rule_levels(
+    result: &mut ValidationResult,
+    config: &ValidateConfig,
+) -> Result<()> {
+    // Already keyed by code when loaded, but not when built by hand
+    let levels = config.clone().by_code()?.rules;
+    result
+        .diagnostics
+        .retain_mut(|diagnostic| match levels.get(diagnostic.rule.code) {
+            Some(RuleLevel::Allow) => false,
+            Some(RuleLevel::Warn) => {
+                diagnostic.severity = Severity::Warning;
+                true
+            }
+            Some(RuleLevel::Deny) => {
+                diagnostic.severity = Severity::Error;
+                true
+            }
+            None => true,
+        });
+    Ok(())
+}
+
+/// Drop pyproject.toml diagnostics suppressed by an `# echidna: allow(...)`
+/// comment on the same line or the line above.
+fn apply_suppressions(result: &mut ValidationResult, content: &str) {
+    let pattern = Regex::new(r"#\s*echidna:\s*allow\(([^)]*)\)").unwrap();
+    let suppressions: Vec<(usize, &'static str)> = content
+        .lines()
+        .enumerate()
+        .flat_map(|(i, line)| {
+            pattern
+                .captures_iter(line)
+                .flat_map(|c| {
+                    c.get(1)
+                        .map_or("", |m| m.as_str())
+                        .split(',')
+                        .filter_map(|name| find_rule(name.trim()))
+                        .map(|rule| (i + 1, rule.code))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    if suppressions.is_empty() {
+        return;
+    }
+
+    result.diagnostics.retain(|diagnostic| {
+        let Some(location) = &diagnostic.location else {
+            return true;
+        };
+        if location.file != Path::new("pyproject.toml") {
+            return true;
+        }
+        !suppressions.iter().any(|(line, code)| {
+            *code == diagnostic.rule.code && (*line == location.line || *line + 1 == location.line)
+        })
+    });
+}
+
+/// Run all checks on a bundle.
+fn check_bundle(project_dir: &Path, checks: &ChimeraXChecks) -> Result<ValidationResult> {
     let mut result = ValidationResult::default();
 
     // Check pyproject.toml exists
     let pyproject_path = project_dir.join("pyproject.toml");
     if !pyproject_path.exists() {
-        result.add_error("pyproject.toml not found");
+        result.add(Diagnostic::new(
+            &rules::MISSING_PYPROJECT,
+            "pyproject.toml not found",
+        ));
         return Ok(result);
     }
 
@@ -112,7 +350,14 @@ pub fn validate_bundle(project_dir: &Path) -> Result<ValidationResult> {
     let pyproject: toml::Value = match toml::from_str(&content) {
         Ok(v) => v,
         Err(e) => {
-            result.add_error(format!("Failed to parse pyproject.toml: {}", e));
+            let mut diagnostic = Diagnostic::new(
+                &rules::INVALID_TOML,
+                format!("Failed to parse pyproject.toml: {}", e.message()),
+            );
+            if let Some(span) = e.span() {
+                diagnostic = diagnostic.at(Location::from_span("pyproject.toml", &content, span));
+            }
+            result.add(diagnostic);
             return Ok(result);
         }
     };
@@ -127,7 +372,31 @@ pub fn validate_bundle(project_dir: &Path) -> Result<ValidationResult> {
     validate_chimerax_section(&pyproject, &mut result);
 
     // Validate source directory structure
-    validate_source_structure(project_dir, &pyproject, &mut result);
+    validate_source_structure(project_dir, &pyproject, &mut result)?;
+
+    // Validate C/C++ extensions and libraries
+    if let Some(chimerax) = pyproject.get("chimerax") {
+        extension_check::check(project_dir, chimerax, &mut result)?;
+        if let (Some(executor), true) = (checks.executor, checks.imports) {
+            extension_check::check_modules(executor, chimerax, &mut result)?;
+        }
+
+        // Validate package-data, extra-files, and data-files patterns
+        package_data_check::check(project_dir, chimerax, &mut result)?;
+    }
+
+    // Validate session support of stateful classes
+    session_check::check(project_dir, &mut result)?;
+    if let (Some(executor), true) = (checks.executor, checks.session) {
+        session_check::check_roundtrip(executor, project_dir, &mut result)?;
+    }
+
+    // Resolve key paths to lines in pyproject.toml
+    diagnostics::locate_keys(
+        &mut result.diagnostics,
+        Path::new("pyproject.toml"),
+        &content,
+    );
 
     Ok(result)
 }
@@ -137,7 +406,10 @@ fn validate_build_system(pyproject: &toml::Value, result: &mut ValidationResult)
     let build_system = match pyproject.get("build-system") {
         Some(bs) => bs,
         None => {
-            result.add_error("[build-system] section missing");
+            result.add(Diagnostic::new(
+                &rules::BUILD_SYSTEM,
+                "[build-system] section missing",
+            ));
             return;
         }
     };
@@ -151,27 +423,54 @@ fn validate_build_system(pyproject: &toml::Value, result: &mut ValidationResult)
                     .unwrap_or(false)
             });
             if !has_bundle_builder {
-                result.add_error("[build-system].requires must include 'ChimeraX-BundleBuilder'");
+                result.add(
+                    Diagnostic::new(
+                        &rules::BUILD_SYSTEM,
+                        "[build-system].requires must include 'ChimeraX-BundleBuilder'",
+                    )
+                    .at_key(&["build-system", "requires"]),
+                );
             }
         } else {
-            result.add_error("[build-system].requires must be an array");
+            result.add(
+                Diagnostic::new(
+                    &rules::BUILD_SYSTEM,
+                    "[build-system].requires must be an array",
+                )
+                .at_key(&["build-system", "requires"]),
+            );
         }
     } else {
-        result.add_error("[build-system].requires is missing");
+        result.add(
+            Diagnostic::new(&rules::BUILD_SYSTEM, "[build-system].requires is missing")
+                .at_key(&["build-system", "requires"]),
+        );
     }
 
     // Check build-backend
     if let Some(backend) = build_system.get("build-backend") {
         if let Some(backend_str) = backend.as_str() {
             if backend_str != "chimerax.bundle_builder.cx_pep517" {
-                result.add_warning(format!(
-                    "Unexpected build-backend: '{}' (expected 'chimerax.bundle_builder.cx_pep517')",
-                    backend_str
-                ));
+                result.add(
+                    Diagnostic::new(
+                        &rules::UNEXPECTED_BUILD_BACKEND,
+                        format!(
+                            "Unexpected build-backend: '{}' (expected 'chimerax.bundle_builder.cx_pep517')",
+                            backend_str
+                        ),
+                    )
+                    .at_key(&["build-system", "build-backend"]),
+                );
             }
         }
     } else {
-        result.add_error("[build-system].build-backend is missing");
+        result.add(
+            Diagnostic::new(
+                &rules::MISSING_BUILD_BACKEND,
+                "[build-system].build-backend is missing",
+            )
+            .at_key(&["build-system", "build-backend"]),
+        );
     }
 }
 
@@ -180,7 +479,10 @@ fn validate_project_section(pyproject: &toml::Value, result: &mut ValidationResu
     let project = match pyproject.get("project") {
         Some(p) => p,
         None => {
-            result.add_error("[project] section missing");
+            result.add(Diagnostic::new(
+                &rules::MISSING_PROJECT,
+                "[project] section missing",
+            ));
             return;
         }
     };
@@ -189,26 +491,47 @@ fn validate_project_section(pyproject: &toml::Value, result: &mut ValidationResu
     if let Some(name) = project.get("name") {
         if let Some(name_str) = name.as_str() {
             if !name_str.starts_with("ChimeraX-") {
-                result.add_warning(format!(
-                    "Bundle name '{}' doesn't follow convention (should start with 'ChimeraX-')",
-                    name_str
-                ));
+                result.add(
+                    Diagnostic::new(
+                        &rules::BUNDLE_NAME_CONVENTION,
+                        format!(
+                            "Bundle name '{}' doesn't follow convention (should start with 'ChimeraX-')",
+                            name_str
+                        ),
+                    )
+                    .at_key(&["project", "name"]),
+                );
             }
         } else {
-            result.add_error("[project].name must be a string");
+            result.add(
+                Diagnostic::new(&rules::PROJECT_NAME, "[project].name must be a string")
+                    .at_key(&["project", "name"]),
+            );
         }
     } else {
-        result.add_error("[project].name is missing");
+        result.add(
+            Diagnostic::new(&rules::PROJECT_NAME, "[project].name is missing")
+                .at_key(&["project", "name"]),
+        );
     }
 
     // Check version
     if project.get("version").is_none() {
-        result.add_error("[project].version is missing");
+        result.add(
+            Diagnostic::new(&rules::MISSING_VERSION, "[project].version is missing")
+                .at_key(&["project", "version"]),
+        );
     }
 
     // Check description (recommended)
     if project.get("description").is_none() {
-        result.add_warning("[project].description is not set (recommended for Toolshed)");
+        result.add(
+            Diagnostic::new(
+                &rules::MISSING_DESCRIPTION,
+                "[project].description is not set (recommended for Toolshed)",
+            )
+            .at_key(&["project", "description"]),
+        );
     }
 
     // Check classifiers for Python version
@@ -220,52 +543,109 @@ fn validate_project_section(pyproject: &toml::Value, result: &mut ValidationResu
                     .unwrap_or(false)
             });
             if !has_python_classifier {
-                result
-                    .add_warning("[project].classifiers should include Python version classifier");
+                result.add(
+                    Diagnostic::new(
+                        &rules::MISSING_PYTHON_CLASSIFIER,
+                        "[project].classifiers should include Python version classifier",
+                    )
+                    .at_key(&["project", "classifiers"]),
+                );
             }
         }
     }
 }
 
+/// A session version given as an integer or a numeric string.
+fn session_version(value: &toml::Value) -> Option<i64> {
+    value
+        .as_integer()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
 /// Validate [chimerax] section.
 fn validate_chimerax_section(pyproject: &toml::Value, result: &mut ValidationResult) {
     let chimerax = match pyproject.get("chimerax") {
         Some(c) => c,
         None => {
-            result.add_error("[chimerax] section missing");
+            result.add(Diagnostic::new(
+                &rules::MISSING_CHIMERAX_SECTION,
+                "[chimerax] section missing",
+            ));
             return;
         }
     };
 
-    // Check package
-    if let Some(package) = chimerax.get("package") {
-        if let Some(package_str) = package.as_str() {
-            if !package_str.starts_with("chimerax.") {
-                result.add_warning(format!(
-                    "Package '{}' doesn't follow convention (should start with 'chimerax.')",
-                    package_str
-                ));
-            }
-        } else {
-            result.add_error("[chimerax].package must be a string");
+    // Check keys, types, and values against the BundleBuilder schema
+    bundle_schema::validate(chimerax, result);
+
+    // Check package naming convention
+    if let Some(package_str) = chimerax.get("package").and_then(|p| p.as_str()) {
+        if !package_str.starts_with("chimerax.") {
+            result.add(
+                Diagnostic::new(
+                    &rules::PACKAGE_CONVENTION,
+                    format!(
+                        "Package '{}' doesn't follow convention (should start with 'chimerax.')",
+                        package_str
+                    ),
+                )
+                .at_key(&["chimerax", "package"]),
+            );
         }
-    } else {
-        result.add_error("[chimerax].package is missing");
     }
 
     // Check categories (optional but recommended)
     if chimerax.get("categories").is_none() {
-        result.add_warning("[chimerax].categories is not set");
+        result.add(
+            Diagnostic::new(
+                &rules::MISSING_CATEGORIES,
+                "[chimerax].categories is not set",
+            )
+            .at_key(&["chimerax", "categories"]),
+        );
     }
 
     // Check min-session-version (recommended)
     if chimerax.get("min-session-version").is_none() {
-        result.add_warning("[chimerax].min-session-version is not set (recommended)");
+        result.add(
+            Diagnostic::new(
+                &rules::MISSING_MIN_SESSION_VERSION,
+                "[chimerax].min-session-version is not set (recommended)",
+            )
+            .at_key(&["chimerax", "min-session-version"]),
+        );
+    }
+
+    // Check that the session version range is not empty
+    let session_version = |key| chimerax.get(key).and_then(session_version);
+    if let (Some(min), Some(max)) = (
+        session_version("min-session-version"),
+        session_version("max-session-version"),
+    ) {
+        if min > max {
+            result.add(
+                Diagnostic::new(
+                    &rules::SESSION_VERSION_RANGE,
+                    format!(
+                        "[chimerax].min-session-version ({}) is greater than \
+                         max-session-version ({})",
+                        min, max
+                    ),
+                )
+                .at_key(&["chimerax", "max-session-version"]),
+            );
+        }
     }
 
     // Check min-chimerax-version (recommended)
     if chimerax.get("min-chimerax-version").is_none() {
-        result.add_warning("[chimerax].min-chimerax-version is not set (recommended)");
+        result.add(
+            Diagnostic::new(
+                &rules::MISSING_MIN_CHIMERAX_VERSION,
+                "[chimerax].min-chimerax-version is not set (recommended)",
+            )
+            .at_key(&["chimerax", "min-chimerax-version"]),
+        );
     }
 }
 
@@ -274,20 +654,31 @@ fn validate_source_structure(
     project_dir: &Path,
     pyproject: &toml::Value,
     result: &mut ValidationResult,
-) {
+) -> Result<()> {
     let src_dir = project_dir.join("src");
 
     if !src_dir.exists() {
-        result.add_error("src/ directory not found");
-        return;
+        result.add(Diagnostic::new(
+            &rules::MISSING_SRC,
+            "src/ directory not found",
+        ));
+        return Ok(());
     }
 
-    // Check __init__.py exists
-    let init_py = src_dir.join("__init__.py");
-    if !init_py.exists() {
-        result.add_error("src/__init__.py not found");
-        return;
-    }
+    // Check __init__.py exists (in src/ or, for C++ bundles, src/<package path>/)
+    let chimerax = pyproject.get("chimerax");
+    let Some(package_dir) = package_source_dir(project_dir, chimerax) else {
+        result.add(Diagnostic::new(
+            &rules::MISSING_INIT,
+            "src/__init__.py not found",
+        ));
+        return Ok(());
+    };
+    let init_py = package_dir.join("__init__.py");
+    let init_file = init_py
+        .strip_prefix(project_dir)
+        .unwrap_or(&init_py)
+        .to_path_buf();
 
     // Check for bundle_api or get_class in __init__.py
     if let Ok(init_content) = std::fs::read_to_string(&init_py) {
@@ -296,35 +687,48 @@ fn validate_source_structure(
             || init_content.contains("BundleAPI");
 
         if !has_bundle_api {
-            result.add_warning(
-                "src/__init__.py should define bundle_api or get_class() for bundle registration",
+            result.add(
+                Diagnostic::new(
+                    &rules::MISSING_BUNDLE_API,
+                    format!(
+                        "{} should define bundle_api or get_class() for bundle registration",
+                        report_file(&init_file)
+                    ),
+                )
+                .at(Location::line(&init_file, 1, &init_content)),
             );
         }
     }
 
-    // Check for declared commands/tools matching files
-    if let Some(chimerax) = pyproject.get("chimerax") {
-        // Check commands
-        if let Some(commands) = chimerax.get("commands") {
-            if commands.as_table().is_some() || commands.as_array().is_some() {
-                let cmd_py = src_dir.join("cmd.py");
-                if !cmd_py.exists() {
-                    result
-                        .add_warning("Commands declared but src/cmd.py not found (common pattern)");
-                }
-            }
-        }
+    // Check declared commands, tools, and formats against the source
+    if let Some(chimerax) = chimerax {
+        source_check::check(project_dir, chimerax, result)?;
+    }
 
-        // Check tools
-        if let Some(tools) = chimerax.get("tools") {
-            if tools.as_table().is_some() || tools.as_array().is_some() {
-                let tool_py = src_dir.join("tool.py");
-                if !tool_py.exists() {
-                    result.add_warning("Tools declared but src/tool.py not found (common pattern)");
-                }
-            }
-        }
+    Ok(())
+}
+
+/// Directory holding the bundle's `__init__.py`: `src/`, or
+/// `src/<package path>/` (e.g. `src/chimerax/mytool/`) as used by C++ bundles.
+pub(crate) fn package_source_dir(
+    project_dir: &Path,
+    chimerax: Option<&toml::Value>,
+) -> Option<PathBuf> {
+    let src_dir = project_dir.join("src");
+    if src_dir.join("__init__.py").is_file() {
+        return Some(src_dir);
     }
+    let package = chimerax?.get("package")?.as_str()?;
+    let nested = package.split('.').fold(src_dir, |dir, part| dir.join(part));
+    nested.join("__init__.py").is_file().then_some(nested)
+}
+
+/// A relative path with `/` separators, as shown in messages.
+fn report_file(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
 }
 
 #[cfg(test)]
@@ -362,7 +766,7 @@ min-chimerax-version = "1.0"
 
         let result = validate_bundle(temp.path()).unwrap();
         assert!(result.is_valid());
-        assert!(result.errors.is_empty());
+        assert!(result.errors().next().is_none());
     }
 
     #[test]
@@ -371,7 +775,9 @@ min-chimerax-version = "1.0"
 
         let result = validate_bundle(temp.path()).unwrap();
         assert!(!result.is_valid());
-        assert!(result.errors.iter().any(|e| e.contains("pyproject.toml")));
+        assert!(result
+            .errors()
+            .any(|e| e.message.contains("pyproject.toml")));
     }
 
     #[test]
@@ -393,7 +799,7 @@ package = "chimerax.test"
 
         let result = validate_bundle(temp.path()).unwrap();
         assert!(!result.is_valid());
-        assert!(result.errors.iter().any(|e| e.contains("src/")));
+        assert!(result.errors().any(|e| e.message.contains("src/")));
     }
 
     #[test]
@@ -416,7 +822,7 @@ package = "chimerax.test"
 
         let result = validate_bundle(temp.path()).unwrap();
         assert!(!result.is_valid());
-        assert!(result.errors.iter().any(|e| e.contains("__init__.py")));
+        assert!(result.errors().any(|e| e.message.contains("__init__.py")));
     }
 
     #[test]
@@ -441,8 +847,8 @@ categories = ["General"]
 
         let result = validate_bundle(temp.path()).unwrap();
         assert!(result.is_valid()); // Warnings don't fail validation
-        assert!(!result.warnings.is_empty());
-        assert!(result.warnings.iter().any(|w| w.contains("ChimeraX-")));
+        assert!(result.warnings().next().is_some());
+        assert!(result.warnings().any(|w| w.message.contains("ChimeraX-")));
     }
 
     #[test]
@@ -491,7 +897,7 @@ categories = ["General"]
 
         let result = validate_bundle(temp.path()).unwrap();
         assert!(result.is_valid());
-        assert!(result.warnings.iter().any(|w| w.contains("description")));
+        assert!(result.warnings().any(|w| w.message.contains("description")));
     }
 
     #[test]
@@ -519,11 +925,11 @@ min-chimerax-version = "1.0"
 
         let result = validate_bundle(temp.path()).unwrap();
         assert!(result.is_valid());
-        assert!(result.warnings.iter().any(|w| w.contains("bundle_api")));
+        assert!(result.warnings().any(|w| w.message.contains("bundle_api")));
     }
 
     #[test]
-    fn test_validate_commands_without_cmd_py() {
+    fn test_validate_command_without_register_command() {
         let temp = TempDir::new().unwrap();
         let pyproject = r#"
 [build-system]
@@ -541,14 +947,234 @@ categories = ["General"]
 min-session-version = "1"
 min-chimerax-version = "1.0"
 
-[chimerax.commands.mycommand]
+[chimerax.command.mycommand]
 "#;
         fs::write(temp.path().join("pyproject.toml"), pyproject).unwrap();
         fs::create_dir_all(temp.path().join("src")).unwrap();
         fs::write(temp.path().join("src/__init__.py"), "bundle_api = None").unwrap();
 
+        let result = validate_bundle(temp.path()).unwrap();
+        assert!(!result.is_valid());
+        assert!(result
+            .errors()
+            .any(|e| e.message.contains("no register_command() is defined")));
+    }
+
+    #[test]
+    fn test_validate_chimerax_schema() {
+        let temp = TempDir::new().unwrap();
+        create_valid_bundle(temp.path());
+        let mut pyproject = fs::read_to_string(temp.path().join("pyproject.toml")).unwrap();
+        pyproject.push_str(
+            r#"
+[chimerax.tool."My Tool"]
+category = "General"
+descripton = "Typo"
+
+[chimerax.selector.sel]
+atomic = "yes"
+"#,
+        );
+        fs::write(temp.path().join("pyproject.toml"), pyproject).unwrap();
+
+        let result = validate_bundle(temp.path()).unwrap();
+        assert!(!result.is_valid());
+        assert!(result
+            .errors()
+            .any(|e| e.message == "[chimerax.selector.sel].atomic must be a boolean"));
+        assert!(result.warnings().any(|w| w.message.contains("descripton")
+            && w.message.contains("did you mean 'description'")));
+    }
+
+    #[test]
+    fn test_diagnostics_have_codes_and_locations() {
+        let temp = TempDir::new().unwrap();
+        create_valid_bundle(temp.path());
+        fs::write(temp.path().join("src/__init__.py"), "# empty\n").unwrap();
+
+        let result = validate_bundle(temp.path()).unwrap();
+        let bundle_api = result.warnings().find(|w| w.rule.code == "CX042").unwrap();
+        let location = bundle_api.location.as_ref().unwrap();
+        assert_eq!(location.file, PathBuf::from("src/__init__.py"));
+        assert_eq!(location.line, 1);
+
+        let content = fs::read_to_string(temp.path().join("pyproject.toml"))
+            .unwrap()
+            .replace("name = \"ChimeraX-Test\"", "name = \"Test\"");
+        fs::write(temp.path().join("pyproject.toml"), content).unwrap();
+        let result = validate_bundle(temp.path()).unwrap();
+        let name = result
+            .warnings()
+            .find(|w| w.rule.name == "bundle-name-convention")
+            .unwrap();
+        let location = name.location.as_ref().unwrap();
+        assert_eq!(location.file, PathBuf::from("pyproject.toml"));
+        assert_eq!((location.line, location.column), (7, 1));
+    }
+
+    #[test]
+    fn test_validate_invalid_toml_location() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("pyproject.toml"), "[project]\nname = \n").unwrap();
+
+        let result = validate_bundle(temp.path()).unwrap();
+        let error = result.errors().next().unwrap();
+        assert_eq!(error.rule.code, "CX002");
+        assert_eq!(error.location.as_ref().unwrap().line, 2);
+    }
+
+    #[test]
+    fn test_validate_rule_levels() {
+        let temp = TempDir::new().unwrap();
+        create_valid_bundle(temp.path());
+        let pyproject = fs::read_to_string(temp.path().join("pyproject.toml"))
+            .unwrap()
+            .replace("description = \"Test bundle\"\n", "")
+            .replace("min-chimerax-version = \"1.0\"\n", "");
+        fs::write(temp.path().join("pyproject.toml"), pyproject).unwrap();
+
         let result = validate_bundle(temp.path()).unwrap();
         assert!(result.is_valid());
-        assert!(result.warnings.iter().any(|w| w.contains("cmd.py")));
+        assert_eq!(result.warnings().count(), 2);
+
+        fs::write(
+            temp.path().join("echidna.toml"),
+            "[validate]\nmin-chimerax-version = \"allow\"\nCX014 = \"deny\"\n",
+        )
+        .unwrap();
+        let result = validate_bundle(temp.path()).unwrap();
+        assert!(!result.is_valid());
+        assert_eq!(result.warnings().count(), 0);
+        assert_eq!(result.errors().next().unwrap().rule.code, "CX014");
+
+        fs::write(
+            temp.path().join("echidna.toml"),
+            "[validate]\nno-such-rule = \"allow\"\n",
+        )
+        .unwrap();
+        let err = validate_bundle(temp.path()).unwrap_err().to_string();
+        assert!(err.contains("no-such-rule"));
+    }
+
+    #[test]
+    fn test_validate_inline_suppressions() {
+        let temp = TempDir::new().unwrap();
+        create_valid_bundle(temp.path());
+        let pyproject = fs::read_to_string(temp.path().join("pyproject.toml"))
+            .unwrap()
+            .replace(
+                "name = \"ChimeraX-Test\"",
+                "name = \"Test\" # echidna: allow(bundle-name-convention)",
+            )
+            .replace(
+                "[chimerax]\n",
+                "# echidna: allow(CX024, CX023)\n[chimerax]\nprovides = 1\n",
+            )
+            .replace("min-chimerax-version = \"1.0\"\n", "")
+            .replace("min-session-version = \"1\"\n", "");
+        fs::write(temp.path().join("pyproject.toml"), pyproject).unwrap();
+
+        let result = validate_bundle(temp.path()).unwrap();
+        let codes: Vec<_> = result.diagnostics.iter().map(|d| d.rule.code).collect();
+        assert_eq!(codes, vec!["CX030"]);
+    }
+
+    #[test]
+    fn test_validate_cpp_bundle() {
+        use crate::templates::{BundleTemplate, BundleType};
+
+        let temp = TempDir::new().unwrap();
+        BundleTemplate::with_type("my-ext", BundleType::Cpp)
+            .unwrap()
+            .generate(temp.path())
+            .unwrap();
+
+        // The package lives in src/chimerax/myext/
+        let result = validate_bundle(temp.path()).unwrap();
+        assert!(result.is_valid(), "{:?}", result.diagnostics);
+
+        fs::write(temp.path().join("src/chimerax/myext/_myext.py"), "").unwrap();
+        let pyproject = fs::read_to_string(temp.path().join("pyproject.toml"))
+            .unwrap()
+            .replace("pure = false", "pure = true");
+        fs::write(temp.path().join("pyproject.toml"), pyproject).unwrap();
+
+        let result = validate_bundle(temp.path()).unwrap();
+        let codes: Vec<_> = result.errors().map(|d| d.rule.code).collect();
+        assert_eq!(codes, vec!["CX063", "CX065"]);
+    }
+
+    #[test]
+    fn test_validate_session_version_range() {
+        let temp = TempDir::new().unwrap();
+        create_valid_bundle(temp.path());
+        let pyproject = fs::read_to_string(temp.path().join("pyproject.toml"))
+            .unwrap()
+            .replace(
+                "min-session-version = \"1\"\n",
+                "min-session-version = \"2\"\nmax-session-version = 1\n",
+            );
+        fs::write(temp.path().join("pyproject.toml"), pyproject).unwrap();
+
+        let result = validate_bundle(temp.path()).unwrap();
+        let error = result.errors().next().unwrap();
+        assert_eq!(error.rule.code, "CX082");
+        assert_eq!(
+            error.message,
+            "[chimerax].min-session-version (2) is greater than max-session-version (1)"
+        );
+    }
+
+    #[test]
+    fn test_validate_tool_bundle_sessions() {
+        use crate::templates::{BundleTemplate, BundleType};
+
+        let temp = TempDir::new().unwrap();
+        BundleTemplate::with_type("my-tool", BundleType::Tool)
+            .unwrap()
+            .generate(temp.path())
+            .unwrap();
+        let result = validate_bundle(temp.path()).unwrap();
+        assert!(
+            !result
+                .diagnostics
+                .iter()
+                .any(|d| d.rule.code.starts_with("CX08")),
+            "{:?}",
+            result.diagnostics
+        );
+
+        // Drop the get_class() mapping
+        let init = temp.path().join("src/__init__.py");
+        let content = fs::read_to_string(&init).unwrap();
+        let start = content
+            .find("    @staticmethod\n    def get_class")
+            .unwrap();
+        let end = content.find("\n\nbundle_api").unwrap();
+        fs::write(&init, format!("{}{}", &content[..start], &content[end..])).unwrap();
+
+        let result = validate_bundle(temp.path()).unwrap();
+        let codes: Vec<_> = result.errors().map(|d| d.rule.code).collect();
+        assert_eq!(codes, vec!["CX081"]);
+    }
+
+    #[test]
+    fn test_validate_package_data() {
+        let temp = TempDir::new().unwrap();
+        create_valid_bundle(temp.path());
+        fs::create_dir_all(temp.path().join("src/icons")).unwrap();
+        fs::write(temp.path().join("src/icons/logo.png"), "").unwrap();
+        fs::write(temp.path().join("src/tool.html"), "").unwrap();
+        let mut pyproject = fs::read_to_string(temp.path().join("pyproject.toml")).unwrap();
+        pyproject.push_str("\n[chimerax.package-data]\n\"src/\" = [\"icons/*.svg\", \"*.html\"]\n");
+        fs::write(temp.path().join("pyproject.toml"), pyproject).unwrap();
+
+        let result = validate_bundle(temp.path()).unwrap();
+        let error = result.errors().next().unwrap();
+        assert_eq!(error.rule.code, "CX053");
+        assert_eq!(error.location.as_ref().unwrap().line, 18);
+        let warnings: Vec<_> = result.warnings().map(|d| d.message.as_str()).collect();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("src/icons/logo.png is not matched"));
     }
 }
@@ -1,15 +1,17 @@
 //! `echidna version` command implementation.
 
+use crate::commands::version_sync;
 use crate::error::{EchidnaError, Result};
+use crate::pep440::{PreRelease, Version};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use toml::Value;
 
 /// Arguments for the version command.
 pub struct VersionArgs {
     /// Project directory
     pub path: PathBuf,
-    /// Version action (show, bump, or set)
+    /// Version action (show, bump, set, or check)
     pub action: VersionAction,
 }
 
@@ -24,69 +26,18 @@ pub enum VersionAction {
     BumpMinor,
     /// Bump major version (X.0.0)
     BumpMajor,
+    /// Next pre-release of a phase (X.Y.ZaN, bN, rcN)
+    Pre(PreRelease),
+    /// Next development release (.devN)
+    Dev,
+    /// Next post-release (.postN)
+    Post,
+    /// Drop the pre-release and development segments
+    Release,
     /// Set specific version
     Set(String),
-}
-
-/// Parsed semantic version.
-#[derive(Debug, Clone, PartialEq)]
-pub struct SemVer {
-    pub major: u32,
-    pub minor: u32,
-    pub patch: u32,
-}
-
-impl SemVer {
-    /// Parse a version string.
-    pub fn parse(version: &str) -> Option<Self> {
-        let parts: Vec<&str> = version.split('.').collect();
-        if parts.len() != 3 {
-            return None;
-        }
-
-        let major = parts[0].parse().ok()?;
-        let minor = parts[1].parse().ok()?;
-        let patch = parts[2].parse().ok()?;
-
-        Some(Self {
-            major,
-            minor,
-            patch,
-        })
-    }
-
-    /// Bump patch version.
-    pub fn bump_patch(&self) -> Self {
-        Self {
-            major: self.major,
-            minor: self.minor,
-            patch: self.patch + 1,
-        }
-    }
-
-    /// Bump minor version.
-    pub fn bump_minor(&self) -> Self {
-        Self {
-            major: self.major,
-            minor: self.minor + 1,
-            patch: 0,
-        }
-    }
-
-    /// Bump major version.
-    pub fn bump_major(&self) -> Self {
-        Self {
-            major: self.major + 1,
-            minor: 0,
-            patch: 0,
-        }
-    }
-}
-
-impl std::fmt::Display for SemVer {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
-    }
+    /// Check that all version locations agree
+    Check,
 }
 
 /// Execute the version command.
@@ -99,75 +50,116 @@ pub fn execute(args: VersionArgs) -> Result<()> {
         ))
     })?;
 
-    let pyproject_path = project_dir.join("pyproject.toml");
-    if !pyproject_path.exists() {
-        return Err(EchidnaError::NotBundleDirectory(project_dir));
-    }
-
-    // Read and parse pyproject.toml
-    let content = fs::read_to_string(&pyproject_path)?;
-    let toml_value: Value = content.parse().map_err(|e: toml::de::Error| {
-        EchidnaError::ConfigError(format!("Invalid TOML in pyproject.toml: {}", e))
-    })?;
-
-    // Extract version from [project] or [tool.poetry] section
-    let current_version = extract_version(&toml_value)
-        .ok_or_else(|| EchidnaError::ConfigError("Cannot find version in pyproject.toml".into()))?;
+    let current_version = read_version(&project_dir)?;
 
     match args.action {
         VersionAction::Show => {
             println!("{}", current_version);
             Ok(())
         }
-        VersionAction::BumpPatch
-        | VersionAction::BumpMinor
-        | VersionAction::BumpMajor
-        | VersionAction::Set(_) => {
+        VersionAction::Check => check(&project_dir, &current_version),
+        _ => {
             let new_version = compute_new_version(&args.action, &current_version)?;
-            update_version_in_file(&pyproject_path, &content, &current_version, &new_version)?;
+            let updated = write_version(&project_dir, &current_version, &new_version)?;
             println!("{} -> {}", current_version, new_version);
+            for file in updated.iter().skip(1) {
+                println!("  updated {}", file.display());
+            }
             Ok(())
         }
     }
 }
 
-/// Compute new version based on action.
-fn compute_new_version(action: &VersionAction, current_version: &str) -> Result<String> {
-    match action {
-        VersionAction::Show => unreachable!(),
-        VersionAction::BumpPatch => {
-            let semver = parse_semver(current_version)?;
-            Ok(semver.bump_patch().to_string())
-        }
-        VersionAction::BumpMinor => {
-            let semver = parse_semver(current_version)?;
-            Ok(semver.bump_minor().to_string())
-        }
-        VersionAction::BumpMajor => {
-            let semver = parse_semver(current_version)?;
-            Ok(semver.bump_major().to_string())
-        }
-        VersionAction::Set(version) => {
-            // Validate the new version format
-            if SemVer::parse(version).is_none() {
-                return Err(EchidnaError::ConfigError(format!(
-                    "Invalid version format '{}'. Expected X.Y.Z",
-                    version
-                )));
-            }
-            Ok(version.clone())
-        }
+/// Compare every version location with pyproject.toml.
+fn check(project_dir: &Path, current_version: &str) -> Result<()> {
+    let locations = version_sync::locate(project_dir)?;
+    let width = locations.iter().map(|l| l.place.len()).max().unwrap_or(0);
+    let mut mismatches = 0;
+    for location in &locations {
+        let mark = if version_sync::same_version(&location.version, current_version) {
+            "✓"
+        } else {
+            mismatches += 1;
+            "✗"
+        };
+        println!(
+            "  {} {:<width$}  {}",
+            mark,
+            location.place,
+            location.version,
+            width = width
+        );
     }
+    if mismatches > 0 {
+        return Err(EchidnaError::VersionMismatch {
+            count: mismatches,
+            version: current_version.to_string(),
+        });
+    }
+    println!(
+        "✓ {} version location(s) agree on {}",
+        locations.len(),
+        current_version
+    );
+    Ok(())
 }
 
-/// Parse version string to SemVer with error.
-fn parse_semver(version: &str) -> Result<SemVer> {
-    SemVer::parse(version).ok_or_else(|| {
-        EchidnaError::ConfigError(format!(
-            "Cannot parse version '{}' as semantic version (X.Y.Z)",
-            version
-        ))
-    })
+/// Read the version from a project's pyproject.toml.
+pub fn read_version(project_dir: &Path) -> Result<String> {
+    let pyproject_path = project_dir.join("pyproject.toml");
+    if !pyproject_path.exists() {
+        return Err(EchidnaError::NotBundleDirectory(project_dir.to_path_buf()));
+    }
+
+    // Read and parse pyproject.toml
+    let content = fs::read_to_string(&pyproject_path)?;
+    let toml_value: Value = toml::from_str(&content).map_err(|e: toml::de::Error| {
+        EchidnaError::ConfigError(format!("Invalid TOML in pyproject.toml: {}", e))
+    })?;
+
+    // Extract version from [project] or [tool.poetry] section
+    extract_version(&toml_value)
+        .ok_or_else(|| EchidnaError::ConfigError("Cannot find version in pyproject.toml".into()))
+}
+
+/// Replace the version in pyproject.toml and the `[version]` files of a
+/// project; returns the updated files.
+pub fn write_version(
+    project_dir: &Path,
+    old_version: &str,
+    new_version: &str,
+) -> Result<Vec<PathBuf>> {
+    version_sync::update(project_dir, old_version, new_version)
+}
+
+/// Compute new version based on action.
+pub fn compute_new_version(action: &VersionAction, current_version: &str) -> Result<String> {
+    let current = || -> Result<Version> {
+        current_version.parse().map_err(|_| {
+            EchidnaError::ConfigError(format!(
+                "Cannot parse version '{}' as a PEP 440 version",
+                current_version
+            ))
+        })
+    };
+    let new_version = match action {
+        VersionAction::Show => return Ok(current_version.to_string()),
+        VersionAction::Check => {
+            return Err(EchidnaError::ConfigError(
+                "'check' compares the version locations and sets no version".into(),
+            ))
+        }
+        // Validate the new version format
+        VersionAction::Set(version) => version.parse::<Version>()?,
+        VersionAction::BumpPatch => current()?.bump_release(2),
+        VersionAction::BumpMinor => current()?.bump_release(1),
+        VersionAction::BumpMajor => current()?.bump_release(0),
+        VersionAction::Pre(phase) => current()?.bump_pre(*phase)?,
+        VersionAction::Dev => current()?.bump_dev(),
+        VersionAction::Post => current()?.bump_post()?,
+        VersionAction::Release => current()?.finalize()?,
+    };
+    Ok(new_version.to_string())
 }
 
 /// Extract version from parsed TOML value.
@@ -194,132 +186,52 @@ fn extract_version(toml: &Value) -> Option<String> {
     None
 }
 
-/// Update version in pyproject.toml file.
-///
-/// Uses regex-based replacement to preserve formatting and comments.
-fn update_version_in_file(
-    path: &PathBuf,
-    content: &str,
-    old_version: &str,
-    new_version: &str,
-) -> Result<()> {
-    // Find and replace version in [project] section first, then [tool.poetry]
-    let new_content = if let Some(updated) =
-        replace_version_in_section(content, "[project]", old_version, new_version)
-    {
-        updated
-    } else if let Some(updated) =
-        replace_version_in_section(content, "[tool.poetry]", old_version, new_version)
-    {
-        updated
-    } else {
-        return Err(EchidnaError::ConfigError(
-            "Cannot find version field to update in pyproject.toml".into(),
-        ));
-    };
-
-    // Write atomically by writing to temp file first
-    let temp_path = path.with_extension("toml.tmp");
-    fs::write(&temp_path, &new_content)?;
-
-    // Clean up temp file on rename failure
-    if let Err(e) = fs::rename(&temp_path, path) {
-        let _ = fs::remove_file(&temp_path); // Best effort cleanup
-        return Err(e.into());
-    }
-
-    Ok(())
-}
-
-/// Replace version in a specific TOML section.
-fn replace_version_in_section(
-    content: &str,
-    section_header: &str,
-    old_version: &str,
-    new_version: &str,
-) -> Option<String> {
-    // Find section start
-    let section_start = content.find(section_header)?;
-
-    // Find section end (next section or EOF)
-    let section_content = &content[section_start + section_header.len()..];
-    let section_end = section_content
-        .find("\n[")
-        .map(|i| section_start + section_header.len() + i)
-        .unwrap_or(content.len());
-
-    let section_text = &content[section_start..section_end];
-
-    // Find version line within section
-    // Match patterns: version = "X.Y.Z", version = 'X.Y.Z', version="X.Y.Z", etc.
-    for line_start in section_text
-        .match_indices('\n')
-        .map(|(i, _)| section_start + i + 1)
-        .chain(std::iter::once(section_start))
-    {
-        let line_end = content[line_start..]
-            .find('\n')
-            .map(|i| line_start + i)
-            .unwrap_or(content.len());
-
-        let line = &content[line_start..line_end];
-        let trimmed = line.trim();
-
-        // Check if this is a version line
-        if trimmed.starts_with("version") {
-            // Check if it contains our old version
-            if trimmed.contains(old_version) {
-                // Replace old version with new version in this line
-                let new_line = line.replace(old_version, new_version);
-                let mut result = content[..line_start].to_string();
-                result.push_str(&new_line);
-                result.push_str(&content[line_end..]);
-                return Some(result);
-            }
-        }
-    }
-
-    None
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_semver_parse() {
-        let v = SemVer::parse("1.2.3").unwrap();
-        assert_eq!(v.major, 1);
-        assert_eq!(v.minor, 2);
-        assert_eq!(v.patch, 3);
+    fn test_read_write_version() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            "[build-system]\nrequires = []\n\n[project]\nname = \"x\"\nversion = \"1.2.3\"\n",
+        )
+        .unwrap();
+        assert_eq!(read_version(temp.path()).unwrap(), "1.2.3");
+
+        write_version(temp.path(), "1.2.3", "1.3.0").unwrap();
+        assert_eq!(read_version(temp.path()).unwrap(), "1.3.0");
     }
 
     #[test]
-    fn test_semver_parse_invalid() {
-        assert!(SemVer::parse("1.2").is_none());
-        assert!(SemVer::parse("1.2.3.4").is_none());
-        assert!(SemVer::parse("abc").is_none());
+    fn test_compute_new_version() {
+        let bump = |action, current| compute_new_version(&action, current).unwrap();
+        assert_eq!(bump(VersionAction::BumpPatch, "1.2.3"), "1.2.4");
+        assert_eq!(bump(VersionAction::BumpMinor, "1.2.3"), "1.3.0");
+        assert_eq!(bump(VersionAction::BumpMajor, "1.2.3"), "2.0.0");
+        assert_eq!(bump(VersionAction::BumpPatch, "1.2.0rc1"), "1.2.1");
+        assert_eq!(
+            bump(VersionAction::Pre(PreRelease::Beta), "1.2.0b1"),
+            "1.2.0b2"
+        );
+        assert_eq!(bump(VersionAction::Dev, "1.2.0"), "1.2.1.dev1");
+        assert_eq!(bump(VersionAction::Post, "1.2.0"), "1.2.0.post1");
+        assert_eq!(bump(VersionAction::Release, "1.2.0rc2"), "1.2.0");
+        assert_eq!(bump(VersionAction::Show, "1.2.0rc2"), "1.2.0rc2");
+        assert_eq!(
+            bump(VersionAction::Set("2.0.0-alpha.1".into()), "1.2.0"),
+            "2.0.0a1"
+        );
     }
 
     #[test]
-    fn test_semver_bump_patch() {
-        let v = SemVer::parse("1.2.3").unwrap();
-        let bumped = v.bump_patch();
-        assert_eq!(bumped.to_string(), "1.2.4");
-    }
-
-    #[test]
-    fn test_semver_bump_minor() {
-        let v = SemVer::parse("1.2.3").unwrap();
-        let bumped = v.bump_minor();
-        assert_eq!(bumped.to_string(), "1.3.0");
-    }
-
-    #[test]
-    fn test_semver_bump_major() {
-        let v = SemVer::parse("1.2.3").unwrap();
-        let bumped = v.bump_major();
-        assert_eq!(bumped.to_string(), "2.0.0");
+    fn test_compute_new_version_invalid() {
+        assert!(compute_new_version(&VersionAction::BumpPatch, "abc").is_err());
+        assert!(compute_new_version(&VersionAction::Set("1.2.x".into()), "1.0.0").is_err());
+        let err = compute_new_version(&VersionAction::Release, "1.2.0").unwrap_err();
+        assert!(err.to_string().contains("already a final release"));
+        assert!(compute_new_version(&VersionAction::Check, "1.2.0").is_err());
     }
 
     #[test]
@@ -344,39 +256,38 @@ mod tests {
     }
 
     #[test]
-    fn test_replace_version_in_section() {
-        let content = r#"[project]
-name = "mypackage"
-version = "1.0.0"
-description = "A package"
-"#;
-        let result = replace_version_in_section(content, "[project]", "1.0.0", "1.0.1").unwrap();
-        assert!(result.contains("version = \"1.0.1\""));
-        assert!(!result.contains("1.0.0"));
-    }
-
-    #[test]
-    fn test_replace_version_no_spaces() {
-        let content = r#"[project]
-name = "mypackage"
-version="1.0.0"
-"#;
-        let result = replace_version_in_section(content, "[project]", "1.0.0", "1.0.1").unwrap();
-        assert!(result.contains("version=\"1.0.1\""));
-    }
-
-    #[test]
-    fn test_replace_version_preserves_other_sections() {
-        let content = r#"[tool.some-tool]
+    fn test_write_version_preserves_formatting() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            r#"[tool.some-tool]
 version = "0.0.0"
 
 [project]
 name = "mypackage"
-version = "1.0.0"
-"#;
-        let result = replace_version_in_section(content, "[project]", "1.0.0", "1.0.1").unwrap();
+version="1.0.0" # keep
+"#,
+        )
+        .unwrap();
+
+        write_version(temp.path(), "1.0.0", "1.0.1").unwrap();
+        let content = fs::read_to_string(temp.path().join("pyproject.toml")).unwrap();
         // Ensure tool section is not modified
-        assert!(result.contains("version = \"0.0.0\""));
-        assert!(result.contains("version = \"1.0.1\""));
+        assert!(content.contains("version = \"0.0.0\""));
+        assert!(content.contains("version=\"1.0.1\" # keep"));
+    }
+
+    #[test]
+    fn test_write_version_poetry() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            "[tool.poetry]\nname = \"mypackage\"\nversion = \"2.0.0\"\n",
+        )
+        .unwrap();
+
+        write_version(temp.path(), "2.0.0", "2.1.0").unwrap();
+        assert_eq!(read_version(temp.path()).unwrap(), "2.1.0");
+        assert!(write_version(temp.path(), "2.0.0", "2.2.0").is_err());
     }
 }
@@ -0,0 +1,422 @@
+//! Version locations: pyproject.toml plus the files listed in `[version]` of
+//! the project's echidna.toml.
+//!
+//! A bump reads and rewrites every location before anything is written; the
+//! new contents go to temporary files that replace the originals only after
+//! all of them were written, so a failed bump leaves every file untouched.
+
+use crate::config::{Config, VersionConfig, CONFIG_FILE_NAME};
+use crate::error::{EchidnaError, Result};
+use crate::pep440::Version;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::{Document, DocumentMut};
+
+/// A place in a file that holds the version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// File, relative to the project directory
+    pub file: PathBuf,
+    /// `file:line` or `file (key)`
+    pub place: String,
+    /// Version text found there
+    pub version: String,
+}
+
+/// How the version is found in a file.
+enum Locator {
+    /// Value at a dotted TOML key
+    Key(Vec<String>),
+    /// `version` group (or first group) of every regex match
+    Pattern(Regex),
+}
+
+/// A file that carries the version.
+struct Source {
+    file: PathBuf,
+    locator: Locator,
+}
+
+/// Files that carry the version, relative to the project directory:
+/// pyproject.toml first, then the `[version]` files.
+pub fn files(project_dir: &Path) -> Result<Vec<PathBuf>> {
+    Ok(sources(project_dir)?.into_iter().map(|s| s.file).collect())
+}
+
+/// Every version location of a project.
+pub fn locate(project_dir: &Path) -> Result<Vec<Location>> {
+    let mut locations = Vec::new();
+    for source in sources(project_dir)? {
+        let content = read(project_dir, &source.file)?;
+        locations.extend(source.find(&content)?);
+    }
+    Ok(locations)
+}
+
+/// Replace `old` with `new` at every version location; returns the updated
+/// files. Fails without writing anything if a location holds another version.
+pub fn update(project_dir: &Path, old: &str, new: &str) -> Result<Vec<PathBuf>> {
+    let mut changes = Vec::new();
+    for source in sources(project_dir)? {
+        let content = read(project_dir, &source.file)?;
+        let updated = source.replace(&content, old, new)?;
+        changes.push((project_dir.join(&source.file), content, updated));
+    }
+    write_all(&changes)?;
+    Ok(changes
+        .into_iter()
+        .filter_map(|(path, _, _)| path.strip_prefix(project_dir).ok().map(Path::to_path_buf))
+        .collect())
+}
+
+/// Whether two version strings name the same PEP 440 version (`1.0-rc1` and
+/// `1.0rc1`), or are equal if either is not a valid version.
+pub fn same_version(a: &str, b: &str) -> bool {
+    match (Version::parse(a), Version::parse(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// The `[version]` table of the project's own echidna.toml; tables of parent
+/// directories (a workspace root) do not apply, as their paths name other files.
+fn config(project_dir: &Path) -> Result<VersionConfig> {
+    let path = project_dir.join(CONFIG_FILE_NAME);
+    if !path.exists() {
+        return Ok(VersionConfig::default());
+    }
+    Ok(Config::from_toml(&fs::read_to_string(path)?)?.version)
+}
+
+fn sources(project_dir: &Path) -> Result<Vec<Source>> {
+    let pyproject = read(project_dir, Path::new("pyproject.toml"))?;
+    let doc: DocumentMut = pyproject.parse()?;
+    let key = if doc.get("project").and_then(|p| p.get("version")).is_some() {
+        "project.version"
+    } else {
+        "tool.poetry.version"
+    };
+    let mut sources = vec![Source {
+        file: PathBuf::from("pyproject.toml"),
+        locator: Locator::Key(split_key(key)),
+    }];
+
+    for file in config(project_dir)?.files {
+        let name = file.path.display();
+        let locator = match (&file.pattern, &file.key) {
+            (Some(pattern), None) => {
+                let regex = Regex::new(pattern).map_err(|e| {
+                    EchidnaError::ConfigError(format!(
+                        "Invalid version pattern for {}: {}",
+                        name, e
+                    ))
+                })?;
+                if regex.captures_len() < 2 {
+                    return Err(EchidnaError::ConfigError(format!(
+                        "Version pattern for {} needs a group around the version, e.g. '__version__ = \"(.+)\"'",
+                        name
+                    )));
+                }
+                Locator::Pattern(regex)
+            }
+            (None, Some(key)) => Locator::Key(split_key(key)),
+            _ => {
+                return Err(EchidnaError::ConfigError(format!(
+                    "[[version.files]] entry for {} needs either 'pattern' or 'key'",
+                    name
+                )))
+            }
+        };
+        sources.push(Source {
+            file: file.path,
+            locator,
+        });
+    }
+    Ok(sources)
+}
+
+impl Source {
+    /// Version locations in the file's content.
+    fn find(&self, content: &str) -> Result<Vec<Location>> {
+        let location = |place: String, version: &str| Location {
+            file: self.file.clone(),
+            place,
+            version: version.to_string(),
+        };
+        match &self.locator {
+            Locator::Key(key) => {
+                let doc: DocumentMut = content.parse()?;
+                let mut item = doc.as_item();
+                for part in key {
+                    item = item.get(part).ok_or_else(|| self.missing())?;
+                }
+                let version = item.as_str().ok_or_else(|| self.missing())?;
+                Ok(vec![location(self.key_place(key), version)])
+            }
+            Locator::Pattern(regex) => {
+                let found: Vec<Location> = regex
+                    .captures_iter(content)
+                    .filter_map(|captures| version_group(&captures))
+                    .map(|m| location(self.line_place(content, m.start()), m.as_str()))
+                    .collect();
+                if found.is_empty() {
+                    return Err(self.missing());
+                }
+                Ok(found)
+            }
+        }
+    }
+
+    /// The content with every location changed from `old` to `new`.
+    fn replace(&self, content: &str, old: &str, new: &str) -> Result<String> {
+        for location in self.find(content)? {
+            if !same_version(&location.version, old) {
+                return Err(EchidnaError::ConfigError(format!(
+                    "{} has version {}, not {}; run 'echidna version check'",
+                    location.place, location.version, old
+                )));
+            }
+        }
+        match &self.locator {
+            Locator::Key(key) => {
+                // Splice into the value's source to keep its quote style
+                let doc = Document::parse(content)?;
+                let mut item = doc.as_item();
+                for part in key {
+                    item = item.get(part).ok_or_else(|| self.missing())?;
+                }
+                let span = item.span().ok_or_else(|| self.missing())?;
+                let quote = ["\"\"\"", "'''", "\"", "'"]
+                    .into_iter()
+                    .find(|quote| content[span.clone()].starts_with(quote))
+                    .ok_or_else(|| self.missing())?;
+                Ok(format!(
+                    "{}{}{}",
+                    &content[..span.start + quote.len()],
+                    new,
+                    &content[span.end - quote.len()..]
+                ))
+            }
+            Locator::Pattern(regex) => {
+                let mut updated = String::with_capacity(content.len());
+                let mut last = 0;
+                for captures in regex.captures_iter(content) {
+                    if let Some(m) = version_group(&captures) {
+                        updated.push_str(&content[last..m.start()]);
+                        updated.push_str(new);
+                        last = m.end();
+                    }
+                }
+                updated.push_str(&content[last..]);
+                Ok(updated)
+            }
+        }
+    }
+
+    fn key_place(&self, key: &[String]) -> String {
+        format!("{} ({})", self.file.display(), key.join("."))
+    }
+
+    fn line_place(&self, content: &str, offset: usize) -> String {
+        let line = content[..offset].matches('\n').count() + 1;
+        format!("{}:{}", self.file.display(), line)
+    }
+
+    fn missing(&self) -> EchidnaError {
+        let what = match &self.locator {
+            Locator::Key(key) => format!("no string at '{}'", key.join(".")),
+            Locator::Pattern(regex) => format!("no match for '{}'", regex.as_str()),
+        };
+        EchidnaError::ConfigError(format!(
+            "Cannot find the version in {}: {}",
+            self.file.display(),
+            what
+        ))
+    }
+}
+
+fn split_key(key: &str) -> Vec<String> {
+    key.split('.').map(|part| part.trim().to_string()).collect()
+}
+
+fn version_group<'a>(captures: &regex::Captures<'a>) -> Option<regex::Match<'a>> {
+    captures.name("version").or_else(|| captures.get(1))
+}
+
+fn read(project_dir: &Path, file: &Path) -> Result<String> {
+    let path = project_dir.join(file);
+    if file == Path::new("pyproject.toml") && !path.exists() {
+        return Err(EchidnaError::NotBundleDirectory(project_dir.to_path_buf()));
+    }
+    fs::read_to_string(&path)
+        .map_err(|e| EchidnaError::ConfigError(format!("Cannot read {}: {}", path.display(), e)))
+}
+
+/// Write `(path, original, new)` contents: all to temporary files first, then
+/// rename them over the originals, restoring renamed files if a rename fails.
+fn write_all(changes: &[(PathBuf, String, String)]) -> Result<()> {
+    let temp_path = |path: &Path| {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(".tmp");
+        path.with_file_name(name)
+    };
+
+    let mut written = Vec::new();
+    for (path, _, content) in changes {
+        let temp = temp_path(path);
+        if let Err(e) = fs::write(&temp, content) {
+            let _ = fs::remove_file(&temp);
+            for temp in &written {
+                let _ = fs::remove_file(temp);
+            }
+            return Err(e.into());
+        }
+        written.push(temp);
+    }
+
+    for (i, (path, _, _)) in changes.iter().enumerate() {
+        if let Err(e) = fs::rename(&written[i], path) {
+            for (path, original, _) in &changes[..i] {
+                let _ = fs::write(path, original); // Best effort rollback
+            }
+            for temp in &written[i..] {
+                let _ = fs::remove_file(temp);
+            }
+            return Err(e.into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn project(echidna_toml: &str) -> TempDir {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("pyproject.toml"),
+            "[project]\nname = \"x\"\nversion = \"1.2.0\"  # bumped by echidna\n",
+        )
+        .unwrap();
+        fs::write(temp.path().join(CONFIG_FILE_NAME), echidna_toml).unwrap();
+        fs::create_dir_all(temp.path().join("src")).unwrap();
+        fs::write(
+            temp.path().join("src/__init__.py"),
+            "\"\"\"Tool.\"\"\"\n__version__ = \"1.2.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("src/version.h"),
+            "#define TOOL_VERSION \"1.2.0\"\n#define TOOL_API 3\n",
+        )
+        .unwrap();
+        temp
+    }
+
+    const FILES: &str = r#"
+[[version.files]]
+path = "src/__init__.py"
+pattern = '__version__ = "(.+)"'
+
+[[version.files]]
+path = "src/version.h"
+pattern = '#define TOOL_VERSION "(?P<version>[^"]+)"'
+"#;
+
+    #[test]
+    fn test_locate() {
+        let temp = project(FILES);
+        let locations = locate(temp.path()).unwrap();
+        let places: Vec<&str> = locations.iter().map(|l| l.place.as_str()).collect();
+        assert_eq!(
+            places,
+            [
+                "pyproject.toml (project.version)",
+                "src/__init__.py:2",
+                "src/version.h:1"
+            ]
+        );
+        assert!(locations.iter().all(|l| l.version == "1.2.0"));
+    }
+
+    #[test]
+    fn test_update_preserves_formatting() {
+        let temp = project(FILES);
+        let updated = update(temp.path(), "1.2.0", "1.3.0rc1").unwrap();
+        assert_eq!(updated.len(), 3);
+
+        let read = |file| fs::read_to_string(temp.path().join(file)).unwrap();
+        assert_eq!(
+            read("pyproject.toml"),
+            "[project]\nname = \"x\"\nversion = \"1.3.0rc1\"  # bumped by echidna\n"
+        );
+        assert_eq!(
+            read("src/__init__.py"),
+            "\"\"\"Tool.\"\"\"\n__version__ = \"1.3.0rc1\"\n"
+        );
+        assert_eq!(
+            read("src/version.h"),
+            "#define TOOL_VERSION \"1.3.0rc1\"\n#define TOOL_API 3\n"
+        );
+    }
+
+    #[test]
+    fn test_update_toml_key() {
+        let temp =
+            project("[[version.files]]\npath = \"docs/meta.toml\"\nkey = \"package.version\"\n");
+        fs::create_dir_all(temp.path().join("docs")).unwrap();
+        fs::write(
+            temp.path().join("docs/meta.toml"),
+            "[package]\nversion = '1.2.0'\nother = \"1.2.0\"\n",
+        )
+        .unwrap();
+
+        update(temp.path(), "1.2.0", "1.2.1").unwrap();
+        let meta = fs::read_to_string(temp.path().join("docs/meta.toml")).unwrap();
+        assert_eq!(meta, "[package]\nversion = '1.2.1'\nother = \"1.2.0\"\n");
+    }
+
+    #[test]
+    fn test_update_out_of_sync_writes_nothing() {
+        let temp = project(FILES);
+        fs::write(
+            temp.path().join("src/version.h"),
+            "#define TOOL_VERSION \"1.1.0\"\n",
+        )
+        .unwrap();
+
+        let err = update(temp.path(), "1.2.0", "1.3.0").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("src/version.h:1 has version 1.1.0"));
+        let pyproject = fs::read_to_string(temp.path().join("pyproject.toml")).unwrap();
+        assert!(pyproject.contains("\"1.2.0\""));
+        assert!(!temp.path().join("pyproject.toml.tmp").exists());
+    }
+
+    #[test]
+    fn test_invalid_version_files() {
+        let temp = project("[[version.files]]\npath = \"src/__init__.py\"\npattern = '1.2.0'\n");
+        assert!(locate(temp.path())
+            .unwrap_err()
+            .to_string()
+            .contains("needs a group"));
+
+        let temp = project("[[version.files]]\npath = \"src/__init__.py\"\n");
+        assert!(locate(temp.path())
+            .unwrap_err()
+            .to_string()
+            .contains("either 'pattern' or 'key'"));
+
+        let temp = project(
+            "[[version.files]]\npath = \"src/__init__.py\"\npattern = 'VERSION = \"(.+)\"'\n",
+        );
+        assert!(locate(temp.path())
+            .unwrap_err()
+            .to_string()
+            .contains("Cannot find the version in src/__init__.py"));
+    }
+}
@@ -0,0 +1,643 @@
+//! Image regression tests for `echidna test --visual`.
+//!
+//! Each script in `tests/visual/*.cxc` is rendered offscreen and compared
+//! against `tests/visual/baseline/<name>.png`. Renders, diff images, and an
+//! HTML report are written to `build/visual/`.
+
+use crate::chimerax::ChimeraXExecutor;
+use crate::commands::snapshot::{self, html_escape, ImageSize, SnapshotJob};
+use crate::config::VisualConfig;
+use crate::error::{EchidnaError, Result};
+use crate::test_report::{TestCase, TestStatus, TestSuite};
+use std::fs;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+/// Directory containing visual test scripts (relative to the project).
+pub const VISUAL_DIR: &str = "tests/visual";
+
+/// Baseline image directory (relative to [`VISUAL_DIR`]).
+pub const BASELINE_DIR: &str = "baseline";
+
+/// Output directory for renders, diffs, and the report (relative to the project).
+pub const OUTPUT_DIR: &str = "build/visual";
+
+/// Outcome of a single visual test.
+#[derive(Debug)]
+pub enum VisualOutcome {
+    /// Render matches the baseline within tolerances
+    Passed(ImageComparison),
+    /// Render differs from the baseline
+    Failed(ImageComparison),
+    /// Render and baseline have different dimensions
+    SizeMismatch {
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    /// No baseline image exists yet
+    MissingBaseline,
+    /// Baseline was written from the new render
+    Updated { created: bool },
+}
+
+impl VisualOutcome {
+    /// Whether this outcome counts as a failure.
+    pub fn is_failure(&self) -> bool {
+        matches!(
+            self,
+            Self::Failed(_) | Self::SizeMismatch { .. } | Self::MissingBaseline
+        )
+    }
+}
+
+/// Result of a single visual test.
+#[derive(Debug)]
+pub struct VisualTestResult {
+    /// Test name (script file stem)
+    pub name: String,
+    /// Path to the rendered image
+    pub actual: PathBuf,
+    /// Path to the baseline image
+    pub baseline: PathBuf,
+    /// Comparison outcome
+    pub outcome: VisualOutcome,
+}
+
+/// Run the visual tests of a project.
+///
+/// If any render differs from its baseline, diff images and
+/// `build/visual/report.html` are written; callers decide whether the
+/// failures are fatal (see [`failure_count`]).
+pub fn execute(
+    executor: &ChimeraXExecutor,
+    project_dir: &Path,
+    config: &VisualConfig,
+    update_baselines: bool,
+) -> Result<Vec<VisualTestResult>> {
+    let visual_dir = project_dir.join(VISUAL_DIR);
+    let scripts = find_visual_scripts(&visual_dir)?;
+    if scripts.is_empty() {
+        return Err(EchidnaError::ConfigError(format!(
+            "No visual tests found. Add .cxc scripts to {}/",
+            VISUAL_DIR
+        )));
+    }
+
+    let size = match &config.size {
+        Some(size) => size
+            .parse::<ImageSize>()
+            .map_err(|e| EchidnaError::ConfigError(format!("[test.visual].size: {}", e)))?,
+        None => snapshot::DEFAULT_SIZE,
+    };
+
+    let output_dir = project_dir.join(OUTPUT_DIR);
+    fs::create_dir_all(&output_dir)?;
+
+    let jobs: Vec<SnapshotJob> = scripts
+        .iter()
+        .map(|script| SnapshotJob {
+            script: script.clone(),
+            image: output_dir.join(format!("{}.png", test_name(script))),
+        })
+        .collect();
+
+    println!("Rendering {} visual test(s) at {}...", jobs.len(), size);
+    snapshot::render(executor, &jobs, size)?;
+
+    let baseline_dir = visual_dir.join(BASELINE_DIR);
+    let mut results = Vec::new();
+    for job in &jobs {
+        let name = test_name(&job.script);
+        let baseline = baseline_dir.join(format!("{}.png", name));
+        let outcome = if update_baselines {
+            let created = !baseline.exists();
+            fs::create_dir_all(&baseline_dir)?;
+            fs::copy(&job.image, &baseline)?;
+            VisualOutcome::Updated { created }
+        } else if !baseline.exists() {
+            VisualOutcome::MissingBaseline
+        } else {
+            let expected = RgbaImage::load(&baseline)?;
+            let actual = RgbaImage::load(&job.image)?;
+            match compare_images(&expected, &actual, config) {
+                Some(cmp) if cmp.diff_ratio() <= config.max_diff_ratio => {
+                    VisualOutcome::Passed(cmp)
+                }
+                Some(cmp) => VisualOutcome::Failed(cmp),
+                None => VisualOutcome::SizeMismatch {
+                    expected: (expected.width, expected.height),
+                    actual: (actual.width, actual.height),
+                },
+            }
+        };
+        results.push(VisualTestResult {
+            name,
+            actual: job.image.clone(),
+            baseline,
+            outcome,
+        });
+    }
+
+    print_results(&results);
+
+    let failures = failure_count(&results);
+    if failures > 0 {
+        let report = write_report(&results, &output_dir)?;
+        println!();
+        println!("Report: {}", report.display());
+        println!("Run with --update-baselines to accept the new renders.");
+    }
+
+    Ok(results)
+}
+
+/// Number of failed visual tests.
+pub fn failure_count(results: &[VisualTestResult]) -> usize {
+    results.iter().filter(|r| r.outcome.is_failure()).count()
+}
+
+/// Collect visual test results into a report suite.
+pub fn to_suite(results: &[VisualTestResult]) -> TestSuite {
+    let mut suite = TestSuite::new("visual");
+    for result in results {
+        let (status, message) = match &result.outcome {
+            VisualOutcome::Passed(_) | VisualOutcome::Updated { .. } => (TestStatus::Passed, None),
+            outcome => (TestStatus::Failed, Some(outcome_summary(outcome))),
+        };
+        let mut case = TestCase::new(&result.name, VISUAL_DIR, status);
+        case.message = message;
+        suite.tests.push(case);
+    }
+    suite
+}
+
+/// One-line description of a failed outcome.
+fn outcome_summary(outcome: &VisualOutcome) -> String {
+    match outcome {
+        VisualOutcome::Failed(cmp) => format!(
+            "{} of {} pixels differ ({:.3}%), max delta E {:.1}",
+            cmp.differing_pixels,
+            cmp.total_pixels,
+            cmp.diff_ratio() * 100.0,
+            cmp.max_delta_e
+        ),
+        VisualOutcome::SizeMismatch { expected, actual } => format!(
+            "size {}x{} does not match baseline {}x{}",
+            actual.0, actual.1, expected.0, expected.1
+        ),
+        VisualOutcome::MissingBaseline => "no baseline image".to_string(),
+        VisualOutcome::Passed(_) | VisualOutcome::Updated { .. } => String::new(),
+    }
+}
+
+/// Find visual test scripts (`*.cxc`, sorted by name).
+fn find_visual_scripts(visual_dir: &Path) -> Result<Vec<PathBuf>> {
+    if !visual_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut scripts: Vec<PathBuf> = fs::read_dir(visual_dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().map(|e| e == "cxc").unwrap_or(false))
+        .collect();
+    scripts.sort();
+    Ok(scripts)
+}
+
+/// Test name derived from the script file stem.
+fn test_name(script: &Path) -> String {
+    script
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Print one line per visual test.
+fn print_results(results: &[VisualTestResult]) {
+    for result in results {
+        match &result.outcome {
+            VisualOutcome::Passed(cmp) => println!(
+                "  ✓ {} ({:.3}% of pixels differ)",
+                result.name,
+                cmp.diff_ratio() * 100.0
+            ),
+            VisualOutcome::Failed(cmp) => println!(
+                "  ✗ {}: {:.3}% of pixels differ (max delta E {:.1})",
+                result.name,
+                cmp.diff_ratio() * 100.0,
+                cmp.max_delta_e
+            ),
+            VisualOutcome::SizeMismatch { expected, actual } => println!(
+                "  ✗ {}: size {}x{} does not match baseline {}x{}",
+                result.name, actual.0, actual.1, expected.0, expected.1
+            ),
+            VisualOutcome::MissingBaseline => {
+                println!("  ✗ {}: no baseline image", result.name)
+            }
+            VisualOutcome::Updated { created: true } => {
+                println!("  + {}: baseline created", result.name)
+            }
+            VisualOutcome::Updated { created: false } => {
+                println!("  ↻ {}: baseline updated", result.name)
+            }
+        }
+    }
+}
+
+/// Write diff images and an HTML report for failed tests.
+///
+/// Baselines are copied next to the renders so the output directory is
+/// self-contained (e.g., for upload as a CI artifact).
+fn write_report(results: &[VisualTestResult], output_dir: &Path) -> Result<PathBuf> {
+    let mut rows = String::new();
+    for result in results.iter().filter(|r| r.outcome.is_failure()) {
+        let actual = format!("{}.png", result.name);
+        let mut expected = None;
+        let mut diff = None;
+
+        if result.baseline.exists() {
+            let name = format!("{}.expected.png", result.name);
+            fs::copy(&result.baseline, output_dir.join(&name))?;
+            expected = Some(name);
+        }
+
+        if let VisualOutcome::Failed(cmp) = &result.outcome {
+            let name = format!("{}.diff.png", result.name);
+            cmp.diff.save(&output_dir.join(&name))?;
+            diff = Some(name);
+        }
+        let summary = outcome_summary(&result.outcome);
+
+        let cell = |img: &Option<String>| match img {
+            Some(src) => format!(
+                "<td><a href=\"{0}\"><img src=\"{0}\"></a></td>",
+                html_escape(src)
+            ),
+            None => "<td>(none)</td>".to_string(),
+        };
+        rows.push_str(&format!(
+            "<tr><th colspan=\"3\">{} &mdash; {}</th></tr>\n<tr>{}{}{}</tr>\n",
+            html_escape(&result.name),
+            html_escape(&summary),
+            cell(&expected),
+            cell(&Some(actual)),
+            cell(&diff)
+        ));
+    }
+
+    let html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>echidna visual test report</title>\n\
+         <style>body{{font-family:sans-serif}}th{{text-align:left;padding-top:1em}}\
+         img{{max-width:400px;border:1px solid #ccc}}</style>\n</head>\n<body>\n\
+         <h1>Visual test failures</h1>\n<table>\n\
+         <tr><th>Baseline</th><th>Actual</th><th>Diff</th></tr>\n{}</table>\n</body>\n</html>\n",
+        rows
+    );
+    let report = output_dir.join("report.html");
+    fs::write(&report, html)?;
+    Ok(report)
+}
+
+/// 8-bit RGBA image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    /// Row-major RGBA pixels
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    /// Load a PNG file, converting any color type to 8-bit RGBA.
+    pub fn load(path: &Path) -> Result<Self> {
+        let image_err =
+            |e: png::DecodingError| EchidnaError::Image(format!("{}: {}", path.display(), e));
+
+        let file = fs::File::open(path)?;
+        let mut decoder = png::Decoder::new(BufReader::new(file));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info().map_err(image_err)?;
+        let buffer_size = reader
+            .output_buffer_size()
+            .ok_or_else(|| EchidnaError::Image(format!("{}: image too large", path.display())))?;
+        let mut buf = vec![0; buffer_size];
+        let info = reader.next_frame(&mut buf).map_err(image_err)?;
+        buf.truncate(info.buffer_size());
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => buf,
+            png::ColorType::Rgb => buf
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => buf
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => buf.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+            png::ColorType::Indexed => {
+                return Err(EchidnaError::Image(format!(
+                    "{}: unexpanded indexed color",
+                    path.display()
+                )))
+            }
+        };
+
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    /// Save as an RGBA PNG file.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let image_err =
+            |e: png::EncodingError| EchidnaError::Image(format!("{}: {}", path.display(), e));
+
+        let file = fs::File::create(path)?;
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(image_err)?;
+        writer.write_image_data(&self.pixels).map_err(image_err)?;
+        writer.finish().map_err(image_err)?;
+        Ok(())
+    }
+
+    /// RGB of pixel `i`, composited over a white background.
+    fn rgb(&self, i: usize) -> [u8; 3] {
+        let p = &self.pixels[i * 4..i * 4 + 4];
+        let alpha = p[3] as u32;
+        let over_white = |c: u8| ((c as u32 * alpha + 255 * (255 - alpha)) / 255) as u8;
+        [over_white(p[0]), over_white(p[1]), over_white(p[2])]
+    }
+}
+
+/// Pixel-level comparison of a render against its baseline.
+#[derive(Debug, Clone)]
+pub struct ImageComparison {
+    /// Pixels beyond both the per-pixel tolerance and the perceptual threshold
+    pub differing_pixels: usize,
+    /// Total number of pixels compared
+    pub total_pixels: usize,
+    /// Largest perceptual difference (CIE76 delta E) of any pixel
+    pub max_delta_e: f64,
+    /// Diff image: changed pixels in red over a faded baseline
+    pub diff: RgbaImage,
+}
+
+impl ImageComparison {
+    /// Fraction of pixels that differ.
+    pub fn diff_ratio(&self) -> f64 {
+        if self.total_pixels == 0 {
+            0.0
+        } else {
+            self.differing_pixels as f64 / self.total_pixels as f64
+        }
+    }
+}
+
+/// Compare two images. Returns `None` if their dimensions differ.
+///
+/// A pixel counts as different when some channel changes by more than
+/// `pixel_tolerance` *and* the perceptual color distance exceeds
+/// `perceptual_threshold`, so antialiasing noise and imperceptible shifts
+/// are ignored while real color changes are caught.
+pub fn compare_images(
+    expected: &RgbaImage,
+    actual: &RgbaImage,
+    config: &VisualConfig,
+) -> Option<ImageComparison> {
+    if expected.width != actual.width || expected.height != actual.height {
+        return None;
+    }
+
+    let total_pixels = (expected.width * expected.height) as usize;
+    let mut diff_pixels = Vec::with_capacity(total_pixels * 4);
+    let mut differing_pixels = 0;
+    let mut max_delta_e: f64 = 0.0;
+
+    for i in 0..total_pixels {
+        let a = expected.rgb(i);
+        let b = actual.rgb(i);
+        let channel_delta = (0..3).map(|c| a[c].abs_diff(b[c])).max().unwrap_or(0);
+        let delta_e = delta_e76(a, b);
+        max_delta_e = max_delta_e.max(delta_e);
+
+        if channel_delta > config.pixel_tolerance && delta_e > config.perceptual_threshold {
+            differing_pixels += 1;
+            diff_pixels.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            // Faded grayscale of the baseline for context
+            let luma = (0.299 * a[0] as f64 + 0.587 * a[1] as f64 + 0.114 * a[2] as f64) as u8;
+            let faded = 255 - (255 - luma) / 4;
+            diff_pixels.extend_from_slice(&[faded, faded, faded, 255]);
+        }
+    }
+
+    Some(ImageComparison {
+        differing_pixels,
+        total_pixels,
+        max_delta_e,
+        diff: RgbaImage {
+            width: expected.width,
+            height: expected.height,
+            pixels: diff_pixels,
+        },
+    })
+}
+
+/// Perceptual distance between two sRGB colors (CIE76 delta E).
+fn delta_e76(a: [u8; 3], b: [u8; 3]) -> f64 {
+    let la = srgb_to_lab(a);
+    let lb = srgb_to_lab(b);
+    ((la[0] - lb[0]).powi(2) + (la[1] - lb[1]).powi(2) + (la[2] - lb[2]).powi(2)).sqrt()
+}
+
+/// Convert an sRGB color to CIELAB (D65 white point).
+fn srgb_to_lab(rgb: [u8; 3]) -> [f64; 3] {
+    let linear = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let (r, g, b) = (linear(rgb[0]), linear(rgb[1]), linear(rgb[2]));
+
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+
+    let f = |t: f64| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn solid(width: u32, height: u32, color: [u8; 4]) -> RgbaImage {
+        RgbaImage {
+            width,
+            height,
+            pixels: color.repeat((width * height) as usize),
+        }
+    }
+
+    #[test]
+    fn test_identical_images() {
+        let a = solid(4, 4, [10, 20, 30, 255]);
+        let cmp = compare_images(&a, &a.clone(), &VisualConfig::default()).unwrap();
+        assert_eq!(cmp.differing_pixels, 0);
+        assert_eq!(cmp.total_pixels, 16);
+        assert_eq!(cmp.max_delta_e, 0.0);
+    }
+
+    #[test]
+    fn test_small_noise_within_tolerance() {
+        let a = solid(2, 2, [100, 100, 100, 255]);
+        let b = solid(2, 2, [104, 100, 98, 255]);
+        let cmp = compare_images(&a, &b, &VisualConfig::default()).unwrap();
+        assert_eq!(cmp.differing_pixels, 0);
+    }
+
+    #[test]
+    fn test_color_change_detected() {
+        let a = solid(2, 2, [200, 30, 30, 255]);
+        let mut b = a.clone();
+        // Recolor one pixel from red to blue
+        b.pixels[0..4].copy_from_slice(&[30, 30, 200, 255]);
+        let cmp = compare_images(&a, &b, &VisualConfig::default()).unwrap();
+        assert_eq!(cmp.differing_pixels, 1);
+        assert_eq!(cmp.diff_ratio(), 0.25);
+        assert!(cmp.max_delta_e > 50.0);
+        assert_eq!(&cmp.diff.pixels[0..4], &[255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_perceptual_threshold_ignores_invisible_shift() {
+        let a = solid(1, 1, [0, 0, 0, 255]);
+        let b = solid(1, 1, [12, 12, 12, 255]);
+        let strict = VisualConfig {
+            pixel_tolerance: 0,
+            perceptual_threshold: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(compare_images(&a, &b, &strict).unwrap().differing_pixels, 1);
+
+        let lenient = VisualConfig {
+            pixel_tolerance: 0,
+            perceptual_threshold: 10.0,
+            ..Default::default()
+        };
+        assert_eq!(
+            compare_images(&a, &b, &lenient).unwrap().differing_pixels,
+            0
+        );
+    }
+
+    #[test]
+    fn test_size_mismatch() {
+        let a = solid(2, 2, [0, 0, 0, 255]);
+        let b = solid(3, 2, [0, 0, 0, 255]);
+        assert!(compare_images(&a, &b, &VisualConfig::default()).is_none());
+    }
+
+    #[test]
+    fn test_transparent_composited_over_white() {
+        let a = solid(1, 1, [0, 0, 0, 0]);
+        let b = solid(1, 1, [255, 255, 255, 255]);
+        let cmp = compare_images(&a, &b, &VisualConfig::default()).unwrap();
+        assert_eq!(cmp.differing_pixels, 0);
+    }
+
+    #[test]
+    fn test_png_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("img.png");
+        let mut img = solid(3, 2, [1, 2, 3, 255]);
+        img.pixels[4..8].copy_from_slice(&[200, 100, 50, 128]);
+        img.save(&path).unwrap();
+        assert_eq!(RgbaImage::load(&path).unwrap(), img);
+    }
+
+    #[test]
+    fn test_delta_e_extremes() {
+        assert!(delta_e76([0, 0, 0], [0, 0, 0]) < 1e-9);
+        // Black vs white spans the full lightness range
+        assert!((delta_e76([0, 0, 0], [255, 255, 255]) - 100.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_find_visual_scripts() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join(VISUAL_DIR);
+        fs::create_dir_all(dir.join(BASELINE_DIR)).unwrap();
+        fs::write(dir.join("b_surface.cxc"), "").unwrap();
+        fs::write(dir.join("a_cartoon.cxc"), "").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let scripts = find_visual_scripts(&dir).unwrap();
+        let names: Vec<String> = scripts.iter().map(|s| test_name(s)).collect();
+        assert_eq!(names, vec!["a_cartoon", "b_surface"]);
+    }
+
+    #[test]
+    fn test_write_report() {
+        let temp = TempDir::new().unwrap();
+        let baseline = temp.path().join("base.png");
+        let expected = solid(2, 2, [0, 0, 0, 255]);
+        expected.save(&baseline).unwrap();
+        let actual = solid(2, 2, [255, 0, 0, 255]);
+        let cmp = compare_images(&expected, &actual, &VisualConfig::default()).unwrap();
+
+        let results = vec![VisualTestResult {
+            name: "colors".to_string(),
+            actual: temp.path().join("colors.png"),
+            baseline,
+            outcome: VisualOutcome::Failed(cmp),
+        }];
+        let report = write_report(&results, temp.path()).unwrap();
+
+        let html = fs::read_to_string(report).unwrap();
+        assert!(html.contains("colors.diff.png"));
+        assert!(html.contains("colors.expected.png"));
+        assert!(temp.path().join("colors.diff.png").exists());
+        assert!(temp.path().join("colors.expected.png").exists());
+    }
+
+    #[test]
+    fn test_to_suite() {
+        let results = vec![
+            VisualTestResult {
+                name: "ok".to_string(),
+                actual: PathBuf::from("ok.png"),
+                baseline: PathBuf::from("baseline/ok.png"),
+                outcome: VisualOutcome::Updated { created: true },
+            },
+            VisualTestResult {
+                name: "new".to_string(),
+                actual: PathBuf::from("new.png"),
+                baseline: PathBuf::from("baseline/new.png"),
+                outcome: VisualOutcome::MissingBaseline,
+            },
+        ];
+        let suite = to_suite(&results);
+        assert_eq!(suite.name, "visual");
+        assert_eq!(suite.tests[0].status, TestStatus::Passed);
+        assert_eq!(suite.tests[1].status, TestStatus::Failed);
+        assert_eq!(suite.tests[1].message.as_deref(), Some("no baseline image"));
+        assert_eq!(failure_count(&results), 1);
+    }
+}
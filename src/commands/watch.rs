@@ -1,7 +1,7 @@
 //! `echidna watch` command implementation.
 
 use crate::chimerax::Verbosity;
-use crate::commands::{build, install, run, testing};
+use crate::commands::{build, install, run, test_select, testing};
 use crate::error::{EchidnaError, Result};
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 use std::ffi::OsStr;
@@ -17,6 +17,8 @@ pub struct WatchArgs {
     pub run: bool,
     /// Run tests on changes
     pub test: bool,
+    /// Run the full suite after the affected tests
+    pub full: bool,
     /// Path to ChimeraX executable
     pub chimerax: PathBuf,
     /// Verbosity level
@@ -58,8 +60,10 @@ pub fn execute(args: WatchArgs) -> Result<()> {
 
     if args.run {
         println!("Mode: build + install + run ChimeraX");
+    } else if args.test && args.full {
+        println!("Mode: build + install + affected tests, then full suite");
     } else if args.test {
-        println!("Mode: build + install + test");
+        println!("Mode: build + install + affected tests");
     } else {
         println!("Mode: build + install");
     }
@@ -67,7 +71,7 @@ pub fn execute(args: WatchArgs) -> Result<()> {
 
     // Initial build
     println!("=== Initial Build ===");
-    if let Err(e) = do_build(&args, &project_dir) {
+    if let Err(e) = do_build(&args, &project_dir, &[]) {
         eprintln!("Initial build failed: {}", e);
     }
 
@@ -122,7 +126,7 @@ pub fn execute(args: WatchArgs) -> Result<()> {
                     }
 
                     // Rebuild
-                    if let Err(e) = do_build(&args, &project_dir) {
+                    if let Err(e) = do_build(&args, &project_dir, &event.paths) {
                         eprintln!("Build failed: {}", e);
                     }
 
@@ -194,7 +198,7 @@ fn should_ignore_path(path: &Path) -> bool {
 }
 
 /// Perform the build action.
-fn do_build(args: &WatchArgs, project_dir: &Path) -> Result<()> {
+fn do_build(args: &WatchArgs, project_dir: &Path, changed: &[PathBuf]) -> Result<()> {
     // Build
     build::execute(build::BuildArgs {
         path: project_dir.to_path_buf(),
@@ -208,6 +212,7 @@ fn do_build(args: &WatchArgs, project_dir: &Path) -> Result<()> {
         path: project_dir.to_path_buf(),
         wheel: None,
         user: false,
+        index: None,
         chimerax: args.chimerax.clone(),
         verbosity: args.verbosity,
     })?;
@@ -222,29 +227,12 @@ fn do_build(args: &WatchArgs, project_dir: &Path) -> Result<()> {
             no_build: true,   // Already built
             no_install: true, // Already installed
             nogui: false,
+            profile: None,
             chimerax: args.chimerax.clone(),
             verbosity: args.verbosity,
         })?;
     } else if args.test {
-        // Run tests
-        println!();
-        println!("=== Running Tests ===");
-        let test_result = testing::execute(testing::TestArgs {
-            path: project_dir.to_path_buf(),
-            filter: None,
-            verbose: false,
-            no_build: true,   // Already built
-            no_install: true, // Already installed
-            coverage: false,
-            pytest_args: vec![],
-            chimerax: args.chimerax.clone(),
-            verbosity: args.verbosity,
-        });
-
-        // Don't fail the watch loop on test failures
-        if let Err(e) = test_result {
-            eprintln!("Tests failed: {}", e);
-        }
+        run_tests(args, project_dir, changed);
     }
 
     println!();
@@ -253,6 +241,80 @@ fn do_build(args: &WatchArgs, project_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Run the tests affected by the changed files (all tests if none were mapped).
+///
+/// With `--full`, the full suite runs afterwards once the affected tests pass.
+/// Test failures are reported but don't stop the watch loop.
+fn run_tests(args: &WatchArgs, project_dir: &Path, changed: &[PathBuf]) {
+    let package = testing::get_package_name(project_dir).unwrap_or_else(|| "chimerax".to_string());
+    let selection = if changed.is_empty() {
+        None
+    } else {
+        match test_select::select_tests(project_dir, &package, changed) {
+            Ok(selection) => selection,
+            Err(e) => {
+                eprintln!("Warning: could not select affected tests: {}", e);
+                None
+            }
+        }
+    };
+
+    println!();
+    let Some(paths) = selection else {
+        println!("=== Running Tests ===");
+        if let Err(e) = testing::execute(test_args(args, project_dir, Vec::new())) {
+            eprintln!("Tests failed: {}", e);
+        }
+        return;
+    };
+
+    println!("=== Running Affected Tests ===");
+    for path in &paths {
+        if let Ok(relative) = path.strip_prefix(project_dir) {
+            println!("  {}", relative.display());
+        }
+    }
+    if let Err(e) = testing::execute(test_args(args, project_dir, paths)) {
+        eprintln!("Tests failed: {}", e);
+        if args.full {
+            println!("Skipping full suite until the affected tests pass.");
+        }
+        return;
+    }
+
+    if args.full {
+        println!();
+        println!("=== Running Full Suite ===");
+        if let Err(e) = testing::execute(test_args(args, project_dir, Vec::new())) {
+            eprintln!("Tests failed: {}", e);
+        }
+    }
+}
+
+/// Test arguments for a watch-mode run; previous failures run first.
+fn test_args(args: &WatchArgs, project_dir: &Path, paths: Vec<PathBuf>) -> testing::TestArgs {
+    testing::TestArgs {
+        path: project_dir.to_path_buf(),
+        paths,
+        filter: None,
+        verbose: false,
+        no_build: true,   // Already built
+        no_install: true, // Already installed
+        coverage: false,
+        coverage_config: Default::default(),
+        timeout: None,
+        retries: 0,
+        pytest_args: vec!["--ff".to_string()],
+        scripts: false,
+        visual: false,
+        update_baselines: false,
+        visual_config: Default::default(),
+        reports: Vec::new(),
+        chimerax: args.chimerax.clone(),
+        verbosity: args.verbosity,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
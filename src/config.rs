@@ -1,12 +1,18 @@
 //! Configuration file handling for echidna.
 
-use crate::error::Result;
+use crate::diagnostics::find_rule;
+use crate::error::{EchidnaError, Result};
+use crate::workspace::Workspace;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 /// The configuration file name.
 pub const CONFIG_FILE_NAME: &str = "echidna.toml";
 
+/// Environment variable overriding the user configuration file path.
+pub const USER_CONFIG_ENV: &str = "ECHIDNA_CONFIG";
+
 /// Configuration from echidna.toml.
 #[derive(Debug, Deserialize, Serialize, Default, Clone)]
 pub struct Config {
@@ -25,6 +31,324 @@ pub struct Config {
     /// Install as user bundle by default
     #[serde(default)]
     pub user_install: bool,
+
+    /// Settings for `echidna run`
+    #[serde(default)]
+    pub run: RunConfig,
+
+    /// Settings for `echidna test`
+    #[serde(default)]
+    pub test: TestConfig,
+
+    /// Diagnostic levels for `echidna validate`
+    #[serde(default)]
+    pub validate: ValidateConfig,
+
+    /// Files besides pyproject.toml that carry the version
+    #[serde(default)]
+    pub version: VersionConfig,
+}
+
+/// `[toolshed]` section of the user configuration.
+///
+/// ```toml
+/// [toolshed]
+/// url = "https://toolshed.example.org"
+/// token = "..."
+/// ```
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct ToolshedConfig {
+    /// Base URL of the Toolshed (e.g. a mirror)
+    pub url: Option<String>,
+    /// API token, sent only to `url` or the `--toolshed-url` given
+    pub token: Option<String>,
+}
+
+/// Per-user configuration, e.g. `~/.config/echidna/config.toml` on Linux.
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct UserConfig {
+    #[serde(default)]
+    pub toolshed: ToolshedConfig,
+}
+
+impl UserConfig {
+    /// Path of the user configuration file (`$ECHIDNA_CONFIG` if set).
+    pub fn path() -> Option<PathBuf> {
+        match std::env::var_os(USER_CONFIG_ENV) {
+            Some(path) => Some(PathBuf::from(path)),
+            None => dirs::config_dir().map(|dir| dir.join("echidna").join("config.toml")),
+        }
+    }
+
+    /// Load the user configuration; a missing file gives the defaults.
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) if path.exists() => {
+                let content = std::fs::read_to_string(&path)?;
+                Ok(toml::from_str(&content)?)
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+}
+
+/// `[version]` section: files whose version `echidna version` keeps in sync
+/// with pyproject.toml.
+///
+/// ```toml
+/// [[version.files]]
+/// path = "src/__init__.py"
+/// pattern = '__version__ = "(.+)"'
+///
+/// [[version.files]]
+/// path = "docs/meta.toml"
+/// key = "package.version"
+/// ```
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq)]
+pub struct VersionConfig {
+    #[serde(default)]
+    pub files: Vec<VersionFile>,
+}
+
+/// A file listed in `[[version.files]]`.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct VersionFile {
+    /// File path, relative to the project directory
+    pub path: PathBuf,
+
+    /// Regex whose `version` group (or first group) is the version
+    pub pattern: Option<String>,
+
+    /// Dotted key of the version in a TOML file
+    pub key: Option<String>,
+}
+
+/// `[validate]` section: per-rule levels keyed by diagnostic code or name.
+///
+/// ```toml
+/// [validate]
+/// min-chimerax-version = "allow"
+/// CX014 = "deny"
+/// ```
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq)]
+pub struct ValidateConfig {
+    #[serde(flatten)]
+    pub rules: BTreeMap<String, RuleLevel>,
+}
+
+/// Level of a validation rule.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    /// Drop the diagnostic
+    Allow,
+    /// Report as a warning
+    Warn,
+    /// Report as an error
+    Deny,
+}
+
+impl ValidateConfig {
+    /// Load the `[validate]` levels for a project, keyed by rule code.
+    ///
+    /// Inside a workspace, the levels in the workspace root's echidna.toml are
+    /// defaults that the member's own echidna.toml overrides, rule by rule
+    /// whether a level is keyed by code or by name.
+    pub fn load(project_dir: &Path) -> Result<Self> {
+        let project_dir = project_dir
+            .canonicalize()
+            .unwrap_or_else(|_| project_dir.to_path_buf());
+
+        let mut config = Self::default();
+        if let Some(root) = Workspace::find_root(&project_dir) {
+            let root_config = root.join(CONFIG_FILE_NAME);
+            if root_config.exists() {
+                let content = std::fs::read_to_string(&root_config)?;
+                config = Config::from_toml(&content)?.validate.by_code()?;
+            }
+        }
+        if let Some(member) = Config::load(&project_dir)? {
+            config.rules.extend(member.validate.by_code()?.rules);
+        }
+        Ok(config)
+    }
+
+    /// The levels keyed by rule code instead of code or name.
+    pub fn by_code(self) -> Result<Self> {
+        let mut rules: BTreeMap<String, RuleLevel> = BTreeMap::new();
+        let mut keys: BTreeMap<&str, String> = BTreeMap::new();
+        for (key, level) in self.rules {
+            let rule = find_rule(&key).ok_or_else(|| {
+                EchidnaError::ConfigError(format!(
+                    "Unknown diagnostic code '{}' in [validate] of echidna.toml",
+                    key
+                ))
+            })?;
+            if let Some(other) = keys.insert(rule.code, key.clone()) {
+                return Err(EchidnaError::ConfigError(format!(
+                    "'{}' and '{}' in [validate] of echidna.toml both set rule {}",
+                    other, key, rule.code
+                )));
+            }
+            rules.insert(rule.code.to_string(), level);
+        }
+        Ok(Self { rules })
+    }
+}
+
+/// `[test]` section of echidna.toml.
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct TestConfig {
+    /// Image regression test settings (`[test.visual]`)
+    #[serde(default)]
+    pub visual: VisualConfig,
+
+    /// Coverage defaults (`[test.coverage]`)
+    #[serde(default)]
+    pub coverage: CoverageConfig,
+}
+
+/// `[test.coverage]` section: coverage defaults for `echidna test --coverage`.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct CoverageConfig {
+    /// Fail if total coverage is below this percentage
+    pub fail_under: Option<f64>,
+
+    /// Measure branch coverage
+    pub branch: bool,
+
+    /// Write a Cobertura XML report to this path (relative to the project)
+    pub xml: Option<PathBuf>,
+
+    /// Write an LCOV report to this path (relative to the project)
+    pub lcov: Option<PathBuf>,
+}
+
+/// `[test.visual]` section: image regression test settings.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct VisualConfig {
+    /// Per-channel difference (0-255) ignored as rendering noise
+    pub pixel_tolerance: u8,
+
+    /// Perceptual color distance (CIE76 delta E) below which a changed pixel is ignored
+    pub perceptual_threshold: f64,
+
+    /// Fraction of pixels (0.0-1.0) allowed to differ before a test fails
+    pub max_diff_ratio: f64,
+
+    /// Render size as "WxH" (defaults to the snapshot default)
+    pub size: Option<String>,
+}
+
+impl Default for VisualConfig {
+    fn default() -> Self {
+        Self {
+            pixel_tolerance: 8,
+            // Roughly one "just noticeable difference"
+            perceptual_threshold: 2.3,
+            max_diff_ratio: 0.001,
+            size: None,
+        }
+    }
+}
+
+/// `[run]` section of echidna.toml.
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct RunConfig {
+    /// Named run profiles (`[run.profiles.<name>]`)
+    #[serde(default)]
+    pub profiles: BTreeMap<String, RunProfile>,
+}
+
+impl RunConfig {
+    /// Look up a profile by name.
+    ///
+    /// The returned profile has its `name` field filled in.
+    pub fn profile(&self, name: &str) -> Result<RunProfile> {
+        let profile = self.profiles.get(name).ok_or_else(|| {
+            let available = if self.profiles.is_empty() {
+                "none defined in echidna.toml".to_string()
+            } else {
+                self.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+            };
+            EchidnaError::ConfigError(format!(
+                "Unknown run profile '{}' (available: {})",
+                name, available
+            ))
+        })?;
+
+        if !is_valid_profile_name(name) {
+            return Err(EchidnaError::InvalidName(format!(
+                "profile name '{}' may only contain alphanumeric characters, hyphens, or underscores",
+                name
+            )));
+        }
+
+        Ok(RunProfile {
+            name: name.to_string(),
+            ..profile.clone()
+        })
+    }
+}
+
+/// A named run scenario from `[run.profiles.<name>]`.
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq)]
+pub struct RunProfile {
+    /// Profile name (the table key, filled in by [`RunConfig::profile`])
+    #[serde(skip)]
+    pub name: String,
+
+    /// Short description shown by `echidna run --list-profiles`
+    pub description: Option<String>,
+
+    /// Single script to execute (.cxc or .py)
+    pub script: Option<PathBuf>,
+
+    /// Scripts to execute, in order, after `script`
+    #[serde(default)]
+    pub scripts: Vec<PathBuf>,
+
+    /// Data files to open before running scripts
+    #[serde(default)]
+    pub open: Vec<PathBuf>,
+
+    /// Run in nogui mode
+    #[serde(default)]
+    pub nogui: bool,
+
+    /// Environment variables set for the ChimeraX process
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+
+    /// Extra command-line arguments passed to ChimeraX
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// Use an isolated ChimeraX user profile (settings, preferences, caches)
+    #[serde(default)]
+    pub isolated: bool,
+}
+
+impl RunProfile {
+    /// All scripts of this profile in execution order.
+    pub fn all_scripts(&self) -> Vec<PathBuf> {
+        self.script
+            .iter()
+            .chain(self.scripts.iter())
+            .cloned()
+            .collect()
+    }
+}
+
+/// Check that a profile name is safe to use as a directory name.
+fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
 }
 
 impl Config {
@@ -107,6 +431,30 @@ user_install = true
         assert!(!config.user_install);
     }
 
+    #[test]
+    fn test_parse_version_files() {
+        let toml = r#"
+[[version.files]]
+path = "src/__init__.py"
+pattern = '__version__ = "(.+)"'
+
+[[version.files]]
+path = "docs/meta.toml"
+key = "package.version"
+"#;
+        let config = Config::from_toml(toml).unwrap();
+        assert_eq!(config.version.files.len(), 2);
+        assert_eq!(
+            config.version.files[0].pattern.as_deref(),
+            Some("__version__ = \"(.+)\"")
+        );
+        assert_eq!(
+            config.version.files[1].key.as_deref(),
+            Some("package.version")
+        );
+        assert!(Config::from_toml("[[version.files]]\npath = \"a\"\nregex = \"b\"\n").is_err());
+    }
+
     #[test]
     fn test_parse_partial_config() {
         let toml = r#"
@@ -173,6 +521,190 @@ user_install = true
         assert!(!config.user_install);
     }
 
+    #[test]
+    fn test_parse_run_profiles() {
+        let toml = r#"
+[run.profiles.large]
+description = "Large structure"
+script = "scripts/large.cxc"
+open = ["data/3j3q.cif"]
+
+[run.profiles.batch]
+scripts = ["scripts/a.cxc", "scripts/b.py"]
+nogui = true
+args = ["--nostatus"]
+isolated = true
+
+[run.profiles.batch.env]
+OMP_NUM_THREADS = "1"
+"#;
+        let config = Config::from_toml(toml).unwrap();
+        assert_eq!(config.run.profiles.len(), 2);
+
+        let large = config.run.profile("large").unwrap();
+        assert_eq!(large.name, "large");
+        assert_eq!(large.description.as_deref(), Some("Large structure"));
+        assert_eq!(
+            large.all_scripts(),
+            vec![PathBuf::from("scripts/large.cxc")]
+        );
+        assert_eq!(large.open, vec![PathBuf::from("data/3j3q.cif")]);
+        assert!(!large.nogui);
+
+        let batch = config.run.profile("batch").unwrap();
+        assert!(batch.nogui);
+        assert!(batch.isolated);
+        assert_eq!(batch.all_scripts().len(), 2);
+        assert_eq!(batch.args, vec!["--nostatus".to_string()]);
+        assert_eq!(batch.env.get("OMP_NUM_THREADS"), Some(&"1".to_string()));
+    }
+
+    #[test]
+    fn test_unknown_run_profile() {
+        let toml = r#"
+[run.profiles.density]
+open = ["map.mrc"]
+"#;
+        let config = Config::from_toml(toml).unwrap();
+        let err = config.run.profile("missing").unwrap_err().to_string();
+        assert!(err.contains("missing"));
+        assert!(err.contains("density"));
+    }
+
+    #[test]
+    fn test_invalid_run_profile_name() {
+        let toml = r#"
+[run.profiles."../escape"]
+nogui = true
+"#;
+        let config = Config::from_toml(toml).unwrap();
+        assert!(config.run.profile("../escape").is_err());
+    }
+
+    #[test]
+    fn test_parse_visual_config() {
+        let toml = r#"
+[test.visual]
+pixel_tolerance = 4
+max_diff_ratio = 0.01
+size = "640x480"
+"#;
+        let config = Config::from_toml(toml).unwrap();
+        let visual = &config.test.visual;
+        assert_eq!(visual.pixel_tolerance, 4);
+        assert_eq!(visual.max_diff_ratio, 0.01);
+        assert_eq!(visual.size.as_deref(), Some("640x480"));
+        // Unset keys keep their defaults
+        assert_eq!(visual.perceptual_threshold, 2.3);
+    }
+
+    #[test]
+    fn test_parse_coverage_config() {
+        let toml = r#"
+[test.coverage]
+fail_under = 85
+branch = true
+xml = "build/coverage.xml"
+"#;
+        let config = Config::from_toml(toml).unwrap();
+        let coverage = &config.test.coverage;
+        assert_eq!(coverage.fail_under, Some(85.0));
+        assert!(coverage.branch);
+        assert_eq!(coverage.xml, Some(PathBuf::from("build/coverage.xml")));
+        assert_eq!(coverage.lcov, None);
+    }
+
+    #[test]
+    fn test_parse_validate_config() {
+        let toml = r#"
+[validate]
+min-chimerax-version = "allow"
+CX014 = "deny"
+"#;
+        let config = Config::from_toml(toml).unwrap();
+        let rules = &config.validate.rules;
+        assert_eq!(rules.get("min-chimerax-version"), Some(&RuleLevel::Allow));
+        assert_eq!(rules.get("CX014"), Some(&RuleLevel::Deny));
+
+        assert!(Config::from_toml("[validate]\nCX014 = \"error\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_toolshed_config() {
+        let user: UserConfig =
+            toml::from_str("[toolshed]\nurl = \"https://mirror.example\"\ntoken = \"secret\"\n")
+                .unwrap();
+        assert_eq!(user.toolshed.url.as_deref(), Some("https://mirror.example"));
+        assert_eq!(user.toolshed.token.as_deref(), Some("secret"));
+    }
+
+    #[test]
+    fn test_validate_config_inherits_workspace_defaults() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("workspace.toml"),
+            "[workspace]\nmembers = [\"a\", \"b\"]\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join(CONFIG_FILE_NAME),
+            "[validate]\nCX024 = \"allow\"\nCX014 = \"warn\"\n",
+        )
+        .unwrap();
+        let member_a = temp.path().join("a");
+        let member_b = temp.path().join("b");
+        fs::create_dir_all(&member_a).unwrap();
+        fs::create_dir_all(&member_b).unwrap();
+        fs::write(
+            member_b.join(CONFIG_FILE_NAME),
+            "[validate]\nCX014 = \"deny\"\n",
+        )
+        .unwrap();
+
+        let a = ValidateConfig::load(&member_a).unwrap();
+        assert_eq!(a.rules.get("CX024"), Some(&RuleLevel::Allow));
+        assert_eq!(a.rules.get("CX014"), Some(&RuleLevel::Warn));
+
+        let b = ValidateConfig::load(&member_b).unwrap();
+        assert_eq!(b.rules.get("CX024"), Some(&RuleLevel::Allow));
+        assert_eq!(b.rules.get("CX014"), Some(&RuleLevel::Deny));
+    }
+
+    #[test]
+    fn test_validate_config_member_overrides_by_name() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("workspace.toml"),
+            "[workspace]\nmembers = [\"m\"]\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join(CONFIG_FILE_NAME),
+            "[validate]\nCX014 = \"warn\"\nmin-chimerax-version = \"allow\"\n",
+        )
+        .unwrap();
+        let member = temp.path().join("m");
+        fs::create_dir_all(&member).unwrap();
+        fs::write(
+            member.join(CONFIG_FILE_NAME),
+            "[validate]\nmissing-description = \"deny\"\ncx024 = \"warn\"\n",
+        )
+        .unwrap();
+
+        let config = ValidateConfig::load(&member).unwrap();
+        assert_eq!(config.rules.len(), 2);
+        assert_eq!(config.rules.get("CX014"), Some(&RuleLevel::Deny));
+        assert_eq!(config.rules.get("CX024"), Some(&RuleLevel::Warn));
+
+        fs::write(
+            member.join(CONFIG_FILE_NAME),
+            "[validate]\nmissing-description = \"deny\"\nCX014 = \"allow\"\n",
+        )
+        .unwrap();
+        let err = ValidateConfig::load(&member).unwrap_err().to_string();
+        assert!(err.contains("both set rule CX014"), "{}", err);
+    }
+
     #[test]
     fn test_parse_invalid_toml() {
         let toml = "this is not valid toml [[[";
@@ -0,0 +1,1135 @@
+//! Diagnostics reported by `echidna validate`.
+//!
+//! Every diagnostic belongs to a [`Rule`] with a stable code (`CX001`) and
+//! name (`missing-pyproject`). Diagnostics on pyproject.toml point at a key
+//! path that is resolved to a line and column span once the file is parsed;
+//! they are rendered in rustc style with the offending line, or serialized as
+//! JSON or SARIF for CI annotations.
+
+use crate::error::Result;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Severity of a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => f.pad("error"),
+            Self::Warning => f.pad("warning"),
+        }
+    }
+}
+
+/// A validation rule with a stable code.
+#[derive(Debug)]
+pub struct Rule {
+    /// Stable code (e.g. `CX001`)
+    pub code: &'static str,
+    /// Kebab-case name (e.g. `missing-pyproject`)
+    pub name: &'static str,
+    /// Default severity
+    pub severity: Severity,
+    /// Long-form guidance shown by `echidna explain`
+    pub explanation: &'static str,
+}
+
+impl Rule {
+    /// First sentence of the explanation, on one line.
+    pub fn summary(&self) -> String {
+        let paragraph = self.explanation.split("\n\n").next().unwrap_or_default();
+        let sentence = match paragraph.find(". ") {
+            Some(end) => &paragraph[..=end],
+            None => paragraph,
+        };
+        sentence.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// Rules of `echidna validate` and `echidna publish`.
+pub mod rules {
+    use super::{Rule, Severity};
+
+    pub const MISSING_PYPROJECT: Rule = Rule {
+        code: "CX001",
+        name: "missing-pyproject",
+        severity: Severity::Error,
+        explanation: "\
+The project has no pyproject.toml. ChimeraX bundles are built by
+ChimeraX-BundleBuilder from the [build-system], [project], and [chimerax]
+sections of pyproject.toml.
+
+Run `echidna init` to generate a project, or add a pyproject.toml based on the
+BundleBuilder documentation.",
+    };
+
+    pub const INVALID_TOML: Rule = Rule {
+        code: "CX002",
+        name: "invalid-toml",
+        severity: Severity::Error,
+        explanation: "\
+pyproject.toml is not valid TOML, so neither echidna nor BundleBuilder can
+read it. The diagnostic points at the position where parsing failed.
+
+Common causes are unquoted strings, table names with spaces that are not
+quoted (use [chimerax.tool.\"My Tool\"]), and duplicate keys or tables.",
+    };
+
+    pub const BUILD_SYSTEM: Rule = Rule {
+        code: "CX003",
+        name: "build-system",
+        severity: Severity::Error,
+        explanation: "\
+[build-system] must exist and its `requires` array must include
+ChimeraX-BundleBuilder, which pip installs to build the wheel:
+
+    [build-system]
+    requires = [\"ChimeraX-BundleBuilder\"]
+    build-backend = \"chimerax.bundle_builder.cx_pep517\"",
+    };
+
+    pub const MISSING_BUILD_BACKEND: Rule = Rule {
+        code: "CX004",
+        name: "missing-build-backend",
+        severity: Severity::Error,
+        explanation: "\
+[build-system].build-backend is not set. Without it pip falls back to
+setuptools, which does not understand the [chimerax] section. Set:
+
+    build-backend = \"chimerax.bundle_builder.cx_pep517\"",
+    };
+
+    pub const UNEXPECTED_BUILD_BACKEND: Rule = Rule {
+        code: "CX005",
+        name: "unexpected-build-backend",
+        severity: Severity::Warning,
+        explanation: "\
+[build-system].build-backend is not the BundleBuilder backend. Other backends
+ignore the [chimerax] section, so commands, tools, and formats are not
+registered. Use \"chimerax.bundle_builder.cx_pep517\" unless you build the
+bundle metadata some other way.",
+    };
+
+    pub const MISSING_PROJECT: Rule = Rule {
+        code: "CX010",
+        name: "missing-project",
+        severity: Severity::Error,
+        explanation: "\
+The [project] section (PEP 621) is missing. It holds the distribution name,
+version, description, and dependencies of the bundle.",
+    };
+
+    pub const PROJECT_NAME: Rule = Rule {
+        code: "CX011",
+        name: "project-name",
+        severity: Severity::Error,
+        explanation: "\
+[project].name must be set to a string. It is the distribution name of the
+wheel and the name of the bundle on the Toolshed.",
+    };
+
+    pub const BUNDLE_NAME_CONVENTION: Rule = Rule {
+        code: "CX012",
+        name: "bundle-name-convention",
+        severity: Severity::Warning,
+        explanation: "\
+ChimeraX bundle distributions are conventionally named `ChimeraX-<Name>`
+(e.g. ChimeraX-MyTool), which keeps them recognizable on PyPI and the
+Toolshed.",
+    };
+
+    pub const MISSING_VERSION: Rule = Rule {
+        code: "CX013",
+        name: "missing-version",
+        severity: Severity::Error,
+        explanation: "\
+[project].version is missing. BundleBuilder requires a static version, and
+ChimeraX uses it to decide whether an installed bundle is up to date.",
+    };
+
+    pub const MISSING_DESCRIPTION: Rule = Rule {
+        code: "CX014",
+        name: "missing-description",
+        severity: Severity::Warning,
+        explanation: "\
+[project].description is not set. The Toolshed and `toolshed list` show it as
+the one-line summary of the bundle.",
+    };
+
+    pub const MISSING_PYTHON_CLASSIFIER: Rule = Rule {
+        code: "CX015",
+        name: "missing-python-classifier",
+        severity: Severity::Warning,
+        explanation: "\
+[project].classifiers has no `Programming Language :: Python` classifier.
+Adding one documents which Python versions the bundle supports, e.g.
+
+    classifiers = [\"Programming Language :: Python :: 3\"]",
+    };
+
+    pub const MISSING_CHIMERAX_SECTION: Rule = Rule {
+        code: "CX020",
+        name: "missing-chimerax-section",
+        severity: Severity::Error,
+        explanation: "\
+The [chimerax] section is missing or is not a table. BundleBuilder reads the
+bundle package, categories, session versions, and the command, tool, and
+format declarations from it.",
+    };
+
+    pub const PACKAGE_CONVENTION: Rule = Rule {
+        code: "CX021",
+        name: "package-convention",
+        severity: Severity::Warning,
+        explanation: "\
+[chimerax].package should live in the `chimerax.` namespace (e.g.
+`chimerax.mytool`) so the bundle can be imported like other ChimeraX
+bundles.",
+    };
+
+    pub const MISSING_CATEGORIES: Rule = Rule {
+        code: "CX022",
+        name: "missing-categories",
+        severity: Severity::Warning,
+        explanation: "\
+[chimerax].categories is not set. Categories place the bundle's tools in the
+Tools menu and group it on the Toolshed, e.g. categories = [\"General\"].",
+    };
+
+    pub const MISSING_MIN_SESSION_VERSION: Rule = Rule {
+        code: "CX023",
+        name: "min-session-version",
+        severity: Severity::Warning,
+        explanation: "\
+[chimerax].min-session-version is not set. Together with max-session-version
+it tells ChimeraX which session file versions the bundle can restore. Set
+both to 1 for a new bundle and increase max-session-version when the saved
+state changes.",
+    };
+
+    pub const MISSING_MIN_CHIMERAX_VERSION: Rule = Rule {
+        code: "CX024",
+        name: "min-chimerax-version",
+        severity: Severity::Warning,
+        explanation: "\
+[chimerax].min-chimerax-version is not set. Declaring the oldest supported
+ChimeraX release lets users of older versions get a clear error instead of
+import failures.",
+    };
+
+    pub const UNKNOWN_KEY: Rule = Rule {
+        code: "CX030",
+        name: "unknown-key",
+        severity: Severity::Warning,
+        explanation: "\
+A key in a [chimerax] table is not part of the BundleBuilder schema.
+BundleBuilder silently ignores unknown keys, so a typo such as `descripton`
+builds fine but the setting has no effect inside ChimeraX. The diagnostic
+suggests the closest known key when there is one.",
+    };
+
+    pub const INVALID_TYPE: Rule = Rule {
+        code: "CX031",
+        name: "invalid-type",
+        severity: Severity::Error,
+        explanation: "\
+A [chimerax] key has a value of the wrong type, e.g. `pure = \"yes\"` instead
+of `pure = true`, or a string where BundleBuilder expects an array of
+strings. BundleBuilder fails or misbehaves on such values.",
+    };
+
+    pub const MISSING_REQUIRED_KEY: Rule = Rule {
+        code: "CX032",
+        name: "missing-required-key",
+        severity: Severity::Error,
+        explanation: "\
+A required key of a [chimerax] table is missing, e.g. [chimerax].package,
+`sources` of an extension, or `manager` of a provider.",
+    };
+
+    pub const INVALID_VALUE: Rule = Rule {
+        code: "CX033",
+        name: "invalid-value",
+        severity: Severity::Error,
+        explanation: "\
+A [chimerax] key only accepts a fixed set of values, e.g. the `language` of an
+extension must be \"c\" or \"c++\". The diagnostic lists the accepted values.",
+    };
+
+    pub const MISSING_SRC: Rule = Rule {
+        code: "CX040",
+        name: "missing-src",
+        severity: Severity::Error,
+        explanation: "\
+The src/ directory is missing. BundleBuilder installs src/ as the bundle
+package.",
+    };
+
+    pub const MISSING_INIT: Rule = Rule {
+        code: "CX041",
+        name: "missing-init",
+        severity: Severity::Error,
+        explanation: "\
+src/__init__.py is missing. ChimeraX imports the bundle package and looks up
+`bundle_api` in it.",
+    };
+
+    pub const MISSING_BUNDLE_API: Rule = Rule {
+        code: "CX042",
+        name: "missing-bundle-api",
+        severity: Severity::Warning,
+        explanation: "\
+src/__init__.py defines neither `bundle_api` nor `get_class()`. ChimeraX calls
+methods of the module's `bundle_api` (a BundleAPI subclass instance) to
+register commands, start tools, and open files:
+
+    from chimerax.core.toolshed import BundleAPI
+
+    class _MyAPI(BundleAPI):
+        api_version = 1
+
+    bundle_api = _MyAPI()",
+    };
+
+    pub const UNHANDLED_COMMAND: Rule = Rule {
+        code: "CX043",
+        name: "unhandled-command",
+        severity: Severity::Error,
+        explanation: "\
+A command declared in [chimerax.command] is not handled by the bundle API's
+register_command(). ChimeraX knows the command after installation, but using
+it fails because the bundle never registers it.
+
+Handle every declared name, e.g. by dispatching on `command_info.name`, or
+register `command_info.name` generically.",
+    };
+
+    pub const UNHANDLED_TOOL: Rule = Rule {
+        code: "CX044",
+        name: "unhandled-tool",
+        severity: Severity::Error,
+        explanation: "\
+A tool declared in [chimerax.tool] is not handled by the bundle API's
+start_tool(). The tool shows up in the Tools menu after installation but
+fails to start.",
+    };
+
+    pub const UNHANDLED_FORMAT: Rule = Rule {
+        code: "CX045",
+        name: "unhandled-format",
+        severity: Severity::Error,
+        explanation: "\
+A data format declares open or save support, but the bundle has no opener or
+saver. Implement run_provider() and return an opener for
+`session.open_command` (or a saver for `session.save_command`), or the legacy
+open_file()/save_file() methods.",
+    };
+
+    pub const UNDECLARED_COMMAND: Rule = Rule {
+        code: "CX046",
+        name: "undeclared-command",
+        severity: Severity::Warning,
+        explanation: "\
+The source registers a command that is not declared in [chimerax.command].
+ChimeraX only knows declared commands until the bundle is loaded, so the
+command works in a development session but is \"Unknown command\" in a fresh
+ChimeraX after installation. Declare it:
+
+    [chimerax.command.\"mytool extra\"]
+    category = \"General\"
+    description = \"...\"",
+    };
+
+    pub const MISSING_LICENSE: Rule = Rule {
+        code: "CX050",
+        name: "missing-license",
+        severity: Severity::Error,
+        explanation: "\
+The project has no LICENSE file (LICENSE, LICENSE.txt, LICENSE.md, LICENCE,
+or COPYING). The Toolshed requires a license for submitted bundles.",
+    };
+
+    pub const MISSING_README: Rule = Rule {
+        code: "CX051",
+        name: "missing-readme",
+        severity: Severity::Warning,
+        explanation: "\
+The project has no README. A README describing what the bundle does and how
+to use it is recommended for Toolshed submissions.",
+    };
+
+    pub const MISSING_WHEEL: Rule = Rule {
+        code: "CX052",
+        name: "missing-wheel",
+        severity: Severity::Error,
+        explanation: "\
+No wheel was found in dist/. The Toolshed accepts built wheels; run
+`echidna build` before publishing.",
+    };
+
+    pub const UNMATCHED_FILE_PATTERN: Rule = Rule {
+        code: "CX053",
+        name: "unmatched-file-pattern",
+        severity: Severity::Error,
+        explanation: "\
+A [chimerax.package-data], [chimerax.extra-files], or [chimerax.data-files]
+pattern matches no file. BundleBuilder silently builds a wheel without the
+files, e.g. icons or HTML pages. package-data patterns are relative to the
+package directory named by the key, the others to the project directory:
+
+    [chimerax.package-data]
+    \"src/\" = [\"icons/*.png\", \"docs/**\"]",
+    };
+
+    pub const UNPACKAGED_FILE: Rule = Rule {
+        code: "CX054",
+        name: "unpackaged-file",
+        severity: Severity::Warning,
+        explanation: "\
+A non-Python file under src/ is not matched by any [chimerax.package-data],
+[chimerax.extra-files], or [chimerax.data-files] pattern, so it is left out of
+the wheel. Add a pattern for it, or allow the rule in echidna.toml if the file
+is not needed at runtime. C/C++ sources and build products are not reported.",
+    };
+
+    pub const MISSING_EXTENSION_SOURCE: Rule = Rule {
+        code: "CX060",
+        name: "missing-extension-source",
+        severity: Severity::Error,
+        explanation: "\
+A `sources` entry of a [chimerax.extension] or [chimerax.library] table does
+not exist (or, for a glob such as \"src/*.cpp\", matches no file). Paths are
+relative to the project directory, e.g.
+
+    sources = [\"src/_mytool.cpp\"]",
+    };
+
+    pub const MISSING_EXTENSION_DIR: Rule = Rule {
+        code: "CX061",
+        name: "missing-extension-dir",
+        severity: Severity::Warning,
+        explanation: "\
+An `include-dirs` or `library-dirs` entry of a [chimerax.extension] or
+[chimerax.library] table is not a directory. The compiler silently ignores
+missing directories, so the build fails later with a missing header or
+library. Relative paths are resolved against the project directory.",
+    };
+
+    pub const UNKNOWN_EXTENSION_MODULE: Rule = Rule {
+        code: "CX062",
+        name: "unknown-extension-module",
+        severity: Severity::Error,
+        explanation: "\
+An `include-modules` or `library-modules` entry is not importable in ChimeraX.
+BundleBuilder imports these modules and calls their get_include()/get_lib()
+functions to find headers and libraries, e.g.
+
+    include-modules = [\"chimerax.core\", \"numpy\"]
+    library-modules = [\"chimerax.core\"]
+
+This check runs with `echidna validate --check-imports`.",
+    };
+
+    pub const EXTENSION_NOT_PURE_FALSE: Rule = Rule {
+        code: "CX063",
+        name: "extension-pure",
+        severity: Severity::Error,
+        explanation: "\
+The bundle declares C/C++ extensions or libraries but [chimerax].pure is not
+false. A pure bundle is built as a platform-independent wheel that cannot
+contain compiled code. Set:
+
+    [chimerax]
+    pure = false",
+    };
+
+    pub const INVALID_PLATFORM: Rule = Rule {
+        code: "CX064",
+        name: "invalid-platform",
+        severity: Severity::Error,
+        explanation: "\
+A [chimerax.extension.<name>.platform] subtable has an unknown platform name.
+BundleBuilder only applies the `mac`, `windows`, and `linux` subtables, so
+settings under any other name are ignored.",
+    };
+
+    pub const EXTENSION_MODULE_COLLISION: Rule = Rule {
+        code: "CX065",
+        name: "extension-module-collision",
+        severity: Severity::Error,
+        explanation: "\
+An extension module has the same name as a Python module or package in the
+bundle's source directory. Both are installed into the bundle package, and
+Python imports only one of them. Rename the extension (conventionally with a
+leading underscore, e.g. [chimerax.extension._mytool]).",
+    };
+
+    pub const EXTENSION_MODULE_CHECK_FAILED: Rule = Rule {
+        code: "CX066",
+        name: "extension-module-check-failed",
+        severity: Severity::Warning,
+        explanation: "\
+`echidna validate --check-imports` could not run ChimeraX to check that the
+`include-modules` and `library-modules` entries are importable, so they were
+not checked. The message has ChimeraX's exit code and output; make sure
+ChimeraX starts in nogui mode (`ChimeraX --nogui --exit`).",
+    };
+
+    pub const REMOVED_API: Rule = Rule {
+        code: "CX070",
+        name: "removed-api",
+        severity: Severity::Error,
+        explanation: "\
+The bundle uses a ChimeraX API that was removed in or before the targeted
+ChimeraX release (`echidna lint --target VERSION`), so it fails to
+import or run there. The message names the replacement, e.g.
+
+    from chimerax.core.atomic import Structure  # removed in 0.8
+    from chimerax.atomic import Structure
+
+If the replacement is newer than [chimerax].min-chimerax-version, raise the
+minimum version as well.",
+    };
+
+    pub const DEPRECATED_API: Rule = Rule {
+        code: "CX071",
+        name: "deprecated-api",
+        severity: Severity::Warning,
+        explanation: "\
+The bundle uses a ChimeraX API that is deprecated in the targeted ChimeraX
+release. It still works, but may be removed in a later release. Switch to the
+replacement named in the message.",
+    };
+
+    pub const MISSING_SNAPSHOT_METHODS: Rule = Rule {
+        code: "CX080",
+        name: "missing-snapshot-methods",
+        severity: Severity::Warning,
+        explanation: "\
+A class derived from ToolInstance, Model, or State does not implement both
+take_snapshot() and restore_snapshot(). Its own state is not written to
+sessions, so restoring a session silently loses it. Implement:
+
+    def take_snapshot(self, session, flags):
+        return {'version': 1, 'data': self.data}
+
+    @classmethod
+    def restore_snapshot(cls, session, data):
+        ...
+
+Tools that should not be saved set `SESSION_SAVE = False` instead.",
+    };
+
+    pub const GET_CLASS_COVERAGE: Rule = Rule {
+        code: "CX081",
+        name: "get-class-coverage",
+        severity: Severity::Error,
+        explanation: "\
+A class saved in sessions is not returned by the bundle API's get_class().
+When a session is restored, ChimeraX asks the bundle for each saved class by
+name, and classes it cannot find are dropped. Map every stateful class:
+
+    @staticmethod
+    def get_class(class_name):
+        if class_name == 'MyTool':
+            from .tool import MyTool
+            return MyTool",
+    };
+
+    pub const SESSION_VERSION_RANGE: Rule = Rule {
+        code: "CX082",
+        name: "session-version-range",
+        severity: Severity::Error,
+        explanation: "\
+[chimerax].min-session-version is greater than max-session-version. The
+range lists the session data versions the bundle can restore, so an empty
+range rejects every session.",
+    };
+
+    pub const SESSION_ROUNDTRIP: Rule = Rule {
+        code: "CX083",
+        name: "session-roundtrip",
+        severity: Severity::Error,
+        explanation: "\
+Saving and restoring a session in ChimeraX (`echidna validate --session`)
+failed or lost models or tools. The check runs tests/session.cxc (which
+should create the bundle's models and tools), saves a session, closes it,
+opens it again, and compares the open models and tools.",
+    };
+
+    /// All rules, in code order.
+    pub const ALL: &[&Rule] = &[
+        &MISSING_PYPROJECT,
+        &INVALID_TOML,
+        &BUILD_SYSTEM,
+        &MISSING_BUILD_BACKEND,
+        &UNEXPECTED_BUILD_BACKEND,
+        &MISSING_PROJECT,
+        &PROJECT_NAME,
+        &BUNDLE_NAME_CONVENTION,
+        &MISSING_VERSION,
+        &MISSING_DESCRIPTION,
+        &MISSING_PYTHON_CLASSIFIER,
+        &MISSING_CHIMERAX_SECTION,
+        &PACKAGE_CONVENTION,
+        &MISSING_CATEGORIES,
+        &MISSING_MIN_SESSION_VERSION,
+        &MISSING_MIN_CHIMERAX_VERSION,
+        &UNKNOWN_KEY,
+        &INVALID_TYPE,
+        &MISSING_REQUIRED_KEY,
+        &INVALID_VALUE,
+        &MISSING_SRC,
+        &MISSING_INIT,
+        &MISSING_BUNDLE_API,
+        &UNHANDLED_COMMAND,
+        &UNHANDLED_TOOL,
+        &UNHANDLED_FORMAT,
+        &UNDECLARED_COMMAND,
+        &MISSING_LICENSE,
+        &MISSING_README,
+        &MISSING_WHEEL,
+        &UNMATCHED_FILE_PATTERN,
+        &UNPACKAGED_FILE,
+        &MISSING_EXTENSION_SOURCE,
+        &MISSING_EXTENSION_DIR,
+        &UNKNOWN_EXTENSION_MODULE,
+        &EXTENSION_NOT_PURE_FALSE,
+        &INVALID_PLATFORM,
+        &EXTENSION_MODULE_COLLISION,
+        &EXTENSION_MODULE_CHECK_FAILED,
+        &REMOVED_API,
+        &DEPRECATED_API,
+        &MISSING_SNAPSHOT_METHODS,
+        &GET_CLASS_COVERAGE,
+        &SESSION_VERSION_RANGE,
+        &SESSION_ROUNDTRIP,
+    ];
+}
+
+/// Find a rule by code (case-insensitive) or name.
+pub fn find_rule(code_or_name: &str) -> Option<&'static Rule> {
+    rules::ALL
+        .iter()
+        .copied()
+        .find(|rule| rule.code.eq_ignore_ascii_case(code_or_name) || rule.name == code_or_name)
+}
+
+/// Source location of a diagnostic (1-based lines and columns).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// File relative to the project
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Location {
+    /// Location of a byte range in a file's content.
+    pub fn from_span(file: impl Into<PathBuf>, content: &str, span: Range<usize>) -> Self {
+        let (line, column) = line_column(content, span.start);
+        let (end_line, end_column) = line_column(content, span.end.max(span.start));
+        Self {
+            file: file.into(),
+            line,
+            column,
+            end_line,
+            end_column,
+        }
+    }
+
+    /// Location of a whole line (1-based).
+    pub fn line(file: impl Into<PathBuf>, line: usize, content: &str) -> Self {
+        let width = content
+            .lines()
+            .nth(line - 1)
+            .map_or(0, |l| l.chars().count());
+        Self {
+            file: file.into(),
+            line,
+            column: 1,
+            end_line: line,
+            end_column: width + 1,
+        }
+    }
+}
+
+/// 1-based line and column (in characters) of a byte offset.
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(content.len());
+    let before = &content[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// A validation finding.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub rule: &'static Rule,
+    pub severity: Severity,
+    pub message: String,
+    pub location: Option<Location>,
+    /// pyproject.toml key path, resolved to `location` by [`locate_keys`]
+    pub key: Option<Vec<String>>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic with the rule's default severity.
+    pub fn new(rule: &'static Rule, message: impl Into<String>) -> Self {
+        Self {
+            rule,
+            severity: rule.severity,
+            message: message.into(),
+            location: None,
+            key: None,
+        }
+    }
+
+    /// Point at a pyproject.toml key (or the deepest existing table on the path).
+    pub fn at_key<S: AsRef<str>>(mut self, path: &[S]) -> Self {
+        self.key = Some(path.iter().map(|s| s.as_ref().to_string()).collect());
+        self
+    }
+
+    /// Point at a source location.
+    pub fn at(mut self, location: Location) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// Severity after applying strict mode (warnings become errors).
+    pub fn effective_severity(&self, strict: bool) -> Severity {
+        if strict {
+            Severity::Error
+        } else {
+            self.severity
+        }
+    }
+
+    /// Render in rustc style, with the offending line if the file is readable.
+    ///
+    /// `strict` renders warnings as errors.
+    pub fn render(&self, project_dir: &Path, strict: bool) -> String {
+        let severity = self.effective_severity(strict);
+        let mut out = format!("{}[{}]: {}\n", severity, self.rule.code, self.message);
+
+        let Some(loc) = &self.location else {
+            return out;
+        };
+        let source_line = fs::read_to_string(project_dir.join(&loc.file))
+            .ok()
+            .and_then(|content| content.lines().nth(loc.line - 1).map(str::to_string));
+        let gutter = " ".repeat(loc.line.to_string().len());
+        out.push_str(&format!(
+            "{}--> {}:{}:{}\n",
+            gutter,
+            loc.file.display(),
+            loc.line,
+            loc.column
+        ));
+
+        if let Some(text) = source_line {
+            let width = text.chars().count();
+            let end = if loc.end_line == loc.line {
+                loc.end_column.min(width + 1)
+            } else {
+                width + 1
+            };
+            let carets = end.saturating_sub(loc.column).max(1);
+            out.push_str(&format!("{} |\n", gutter));
+            out.push_str(&format!("{} | {}\n", loc.line, text));
+            out.push_str(&format!(
+                "{} | {}{}\n",
+                gutter,
+                " ".repeat(loc.column - 1),
+                "^".repeat(carets)
+            ));
+        }
+        out
+    }
+}
+
+/// Output format of diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// rustc-style text
+    #[default]
+    Text,
+    /// JSON document
+    Json,
+    /// SARIF 2.1.0 log (for code scanning)
+    Sarif,
+}
+
+/// Path of a diagnostic's file as reported in JSON and SARIF output.
+///
+/// Paths are relative to the project path as given on the command line
+/// (`pyproject.toml` for `.`, `my-tool/pyproject.toml` for `my-tool`), with
+/// `/` separators so output is stable across platforms.
+fn report_path(base: &Path, file: &Path) -> String {
+    use std::path::Component;
+
+    base.join(file)
+        .components()
+        .filter_map(|c| match c {
+            Component::CurDir => None,
+            Component::RootDir => Some(String::new()),
+            other => Some(other.as_os_str().to_string_lossy().to_string()),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Serialize diagnostics as a JSON document.
+pub fn to_json(diagnostics: &[Diagnostic], base: &Path, strict: bool) -> Result<String> {
+    #[derive(Serialize)]
+    struct JsonLocation {
+        file: String,
+        line: usize,
+        column: usize,
+        end_line: usize,
+        end_column: usize,
+    }
+
+    #[derive(Serialize)]
+    struct JsonDiagnostic<'a> {
+        code: &'a str,
+        name: &'a str,
+        severity: Severity,
+        message: &'a str,
+        location: Option<JsonLocation>,
+    }
+
+    #[derive(Serialize)]
+    struct JsonOutput<'a> {
+        valid: bool,
+        errors: usize,
+        warnings: usize,
+        diagnostics: Vec<JsonDiagnostic<'a>>,
+    }
+
+    let diagnostics: Vec<JsonDiagnostic> = diagnostics
+        .iter()
+        .map(|d| JsonDiagnostic {
+            code: d.rule.code,
+            name: d.rule.name,
+            severity: d.effective_severity(strict),
+            message: &d.message,
+            location: d.location.as_ref().map(|loc| JsonLocation {
+                file: report_path(base, &loc.file),
+                line: loc.line,
+                column: loc.column,
+                end_line: loc.end_line,
+                end_column: loc.end_column,
+            }),
+        })
+        .collect();
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    let output = JsonOutput {
+        valid: errors == 0,
+        errors,
+        warnings: diagnostics.len() - errors,
+        diagnostics,
+    };
+    Ok(serde_json::to_string_pretty(&output)?)
+}
+
+/// Serialize diagnostics as a SARIF 2.1.0 log.
+///
+/// Every rule is listed in the tool driver so rule indices are stable.
+/// Diagnostics without a location are reported against `pyproject.toml`.
+pub fn to_sarif(diagnostics: &[Diagnostic], base: &Path, strict: bool) -> Result<String> {
+    use serde_json::json;
+
+    let rules: Vec<serde_json::Value> = rules::ALL
+        .iter()
+        .map(|rule| {
+            json!({
+                "id": rule.code,
+                "name": rule.name,
+                "shortDescription": { "text": rule.summary() },
+                "fullDescription": { "text": rule.explanation },
+                "defaultConfiguration": { "level": rule.severity.to_string() },
+            })
+        })
+        .collect();
+
+    let results: Vec<serde_json::Value> = diagnostics
+        .iter()
+        .map(|d| {
+            let rule_index = rules::ALL
+                .iter()
+                .position(|r| r.code == d.rule.code)
+                .unwrap_or_default();
+            let physical = match &d.location {
+                Some(loc) => json!({
+                    "artifactLocation": { "uri": report_path(base, &loc.file) },
+                    "region": {
+                        "startLine": loc.line,
+                        "startColumn": loc.column,
+                        "endLine": loc.end_line,
+                        "endColumn": loc.end_column,
+                    },
+                }),
+                None => json!({
+                    "artifactLocation": { "uri": report_path(base, Path::new("pyproject.toml")) },
+                }),
+            };
+            json!({
+                "ruleId": d.rule.code,
+                "ruleIndex": rule_index,
+                "level": d.effective_severity(strict).to_string(),
+                "message": { "text": d.message },
+                "locations": [{ "physicalLocation": physical }],
+            })
+        })
+        .collect();
+
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "echidna",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                },
+            },
+            "results": results,
+        }],
+    });
+    Ok(serde_json::to_string_pretty(&log)?)
+}
+
+/// Resolve the key paths of diagnostics to locations in a parsed TOML file.
+///
+/// A path that exists points at `key = value`; otherwise the diagnostic points
+/// at the header (or key) of the deepest table on the path that exists.
+pub fn locate_keys(diagnostics: &mut [Diagnostic], file: &Path, content: &str) {
+    let Ok(doc) = toml_edit::Document::parse(content) else {
+        return;
+    };
+    for diag in diagnostics.iter_mut() {
+        if diag.location.is_some() {
+            continue;
+        }
+        if let Some(span) = diag.key.as_ref().and_then(|k| key_span(&doc, k)) {
+            diag.location = Some(Location::from_span(file, content, span));
+        }
+    }
+}
+
+/// Span of a key path in a parsed document.
+fn key_span(doc: &toml_edit::Document<&str>, path: &[String]) -> Option<Range<usize>> {
+    let mut table: &dyn toml_edit::TableLike = doc.as_table();
+    let mut best = None;
+    for segment in path {
+        let Some((key, item)) = table.get_key_value(segment) else {
+            break;
+        };
+        best = match item {
+            // Explicit table header ([a.b])
+            toml_edit::Item::Table(t) if t.span().is_some() => t.span(),
+            // `key = value`
+            toml_edit::Item::Value(value) => match (key.span(), value.span()) {
+                (Some(k), Some(v)) => Some(k.start..v.end),
+                (k, _) => k.or(best),
+            },
+            _ => key.span().or(best),
+        };
+        match item.as_table_like() {
+            Some(next) => table = next,
+            None => break,
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PYPROJECT: &str = r#"[project]
+name = "MyBundle"
+
+[chimerax]
+package = "chimerax.test"
+
+[chimerax.tool."My Tool"]
+descripton = "Typo"
+"#;
+
+    fn locate(path: &[&str]) -> Option<Location> {
+        let mut diags = vec![Diagnostic::new(&rules::UNKNOWN_KEY, "test").at_key(path)];
+        locate_keys(&mut diags, Path::new("pyproject.toml"), PYPROJECT);
+        diags.pop().unwrap().location
+    }
+
+    #[test]
+    fn test_locate_key_value() {
+        let loc = locate(&["chimerax", "tool", "My Tool", "descripton"]).unwrap();
+        assert_eq!((loc.line, loc.column), (8, 1));
+        assert_eq!((loc.end_line, loc.end_column), (8, 20));
+
+        let loc = locate(&["project", "name"]).unwrap();
+        assert_eq!((loc.line, loc.column, loc.end_column), (2, 1, 18));
+    }
+
+    #[test]
+    fn test_locate_missing_key_points_at_table() {
+        let loc = locate(&["chimerax", "categories"]).unwrap();
+        assert_eq!((loc.line, loc.column), (4, 1));
+        assert_eq!(loc.end_column, 11);
+
+        let loc = locate(&["chimerax", "tool", "My Tool", "category"]).unwrap();
+        assert_eq!(loc.line, 7);
+    }
+
+    #[test]
+    fn test_locate_missing_table() {
+        assert_eq!(locate(&["build-system"]), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::write(temp.path().join("pyproject.toml"), PYPROJECT).unwrap();
+        let mut diags = vec![Diagnostic::new(
+            &rules::UNKNOWN_KEY,
+            "[chimerax.tool.\"My Tool\"].descripton is not a known key",
+        )
+        .at_key(&["chimerax", "tool", "My Tool", "descripton"])];
+        locate_keys(&mut diags, Path::new("pyproject.toml"), PYPROJECT);
+
+        assert_eq!(
+            diags[0].render(temp.path(), false),
+            "warning[CX030]: [chimerax.tool.\"My Tool\"].descripton is not a known key\n \
+             --> pyproject.toml:8:1\n  \
+             |\n\
+             8 | descripton = \"Typo\"\n  \
+             | ^^^^^^^^^^^^^^^^^^^\n"
+        );
+        assert!(diags[0]
+            .render(temp.path(), true)
+            .starts_with("error[CX030]"));
+    }
+
+    #[test]
+    fn test_find_rule() {
+        assert_eq!(find_rule("CX001").unwrap().name, "missing-pyproject");
+        assert_eq!(find_rule("cx030").unwrap().code, "CX030");
+        assert_eq!(find_rule("unknown-key").unwrap().code, "CX030");
+        assert!(find_rule("CX999").is_none());
+    }
+
+    #[test]
+    fn test_rule_codes_unique() {
+        for (i, rule) in rules::ALL.iter().enumerate() {
+            assert!(
+                rules::ALL[i + 1..]
+                    .iter()
+                    .all(|r| r.code != rule.code && r.name != rule.name),
+                "duplicate rule {}",
+                rule.code
+            );
+        }
+    }
+
+    fn sample() -> Vec<Diagnostic> {
+        vec![
+            Diagnostic::new(&rules::MISSING_SRC, "src/ directory not found"),
+            Diagnostic::new(&rules::UNKNOWN_KEY, "[chimerax].zzz is not a known key").at(
+                Location {
+                    file: PathBuf::from("pyproject.toml"),
+                    line: 3,
+                    column: 1,
+                    end_line: 3,
+                    end_column: 8,
+                },
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_to_json() {
+        let json = to_json(&sample(), Path::new("./my-tool"), false).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["valid"], false);
+        assert_eq!(value["errors"], 1);
+        assert_eq!(value["warnings"], 1);
+        assert_eq!(value["diagnostics"][0]["code"], "CX040");
+        assert!(value["diagnostics"][0]["location"].is_null());
+        assert_eq!(value["diagnostics"][1]["severity"], "warning");
+        assert_eq!(
+            value["diagnostics"][1]["location"]["file"],
+            "my-tool/pyproject.toml"
+        );
+
+        let strict = to_json(&sample(), Path::new("."), true).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&strict).unwrap();
+        assert_eq!(value["errors"], 2);
+        assert_eq!(value["diagnostics"][1]["severity"], "error");
+        assert_eq!(
+            value["diagnostics"][1]["location"]["file"],
+            "pyproject.toml"
+        );
+    }
+
+    #[test]
+    fn test_to_sarif() {
+        let sarif = to_sarif(&sample(), Path::new("."), false).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&sarif).unwrap();
+        assert_eq!(value["version"], "2.1.0");
+
+        let run = &value["runs"][0];
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), rules::ALL.len());
+
+        let result = &run["results"][1];
+        assert_eq!(result["ruleId"], "CX030");
+        assert_eq!(
+            rules[result["ruleIndex"].as_u64().unwrap() as usize]["id"],
+            "CX030"
+        );
+        assert_eq!(result["level"], "warning");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "pyproject.toml");
+        assert_eq!(location["region"]["startLine"], 3);
+
+        // Diagnostics without a location are reported against pyproject.toml
+        let result = &run["results"][0];
+        assert_eq!(result["level"], "error");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "pyproject.toml"
+        );
+
+        // Stable across runs
+        assert_eq!(sarif, to_sarif(&sample(), Path::new("."), false).unwrap());
+    }
+
+    #[test]
+    fn test_rule_summary() {
+        assert_eq!(
+            rules::MISSING_PYPROJECT.summary(),
+            "The project has no pyproject.toml."
+        );
+    }
+}
@@ -39,12 +39,36 @@ pub enum EchidnaError {
     #[error("Tests failed (exit code: {0})")]
     TestFailed(i32),
 
+    #[error("Coverage {total:.1}% is below the required {required}%")]
+    CoverageBelowThreshold { total: f64, required: f64 },
+
+    #[error("{0} script test(s) failed")]
+    ScriptTestFailed(usize),
+
+    #[error("{0} visual test(s) differ from baseline")]
+    VisualRegression(usize),
+
+    #[error("{count} version location(s) disagree with pyproject.toml ({version})")]
+    VersionMismatch { count: usize, version: String },
+
+    #[error("Git error: {0}")]
+    Git(String),
+
+    #[error("Toolshed error: {0}")]
+    Toolshed(String),
+
+    #[error("Image error: {0}")]
+    Image(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
     #[error("TOML parsing error: {0}")]
     TomlParse(#[from] toml::de::Error),
 
+    #[error("TOML parsing error: {0}")]
+    TomlEdit(#[from] toml_edit::TomlError),
+
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
@@ -0,0 +1,60 @@
+//! Helpers for running the local `git` executable.
+
+use crate::error::{EchidnaError, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// Run git in a directory and return its standard output.
+pub fn run(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| EchidnaError::Git(format!("failed to run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(EchidnaError::Git(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Most recent version tag (`v*`) reachable from HEAD, if any.
+pub fn last_version_tag(dir: &Path) -> Option<String> {
+    run(
+        dir,
+        &["describe", "--tags", "--abbrev=0", "--match", "v[0-9]*"],
+    )
+    .ok()
+    .map(|tag| tag.trim().to_string())
+    .filter(|tag| !tag.is_empty())
+}
+
+/// A commit message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitMessage {
+    pub subject: String,
+    pub body: String,
+}
+
+/// Non-merge commits after `since` (all history if `None`) that touch `dir`,
+/// newest first.
+pub fn commits_since(dir: &Path, since: Option<&str>) -> Result<Vec<CommitMessage>> {
+    let range = since.map(|tag| format!("{}..HEAD", tag));
+    let mut args = vec!["log", "--no-merges", "--format=%s%x1f%b%x1e"];
+    args.extend(range.as_deref());
+    args.extend(["--", "."]);
+    let log = run(dir, &args)?;
+    Ok(log
+        .split('\x1e')
+        .filter_map(|record| {
+            let (subject, body) = record.trim_start().split_once('\x1f')?;
+            Some(CommitMessage {
+                subject: subject.trim().to_string(),
+                body: body.trim().to_string(),
+            })
+        })
+        .collect())
+}
@@ -5,8 +5,13 @@
 pub mod chimerax;
 pub mod commands;
 pub mod config;
+pub mod diagnostics;
 pub mod error;
+pub mod git;
+pub mod pep440;
 pub mod templates;
+pub mod test_report;
+pub mod toolshed;
 pub mod venv;
 pub mod workspace;
 
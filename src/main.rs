@@ -4,12 +4,15 @@ use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::{generate, Shell};
 use echidna::chimerax::find_chimerax;
 use echidna::commands::{
-    build, clean, debug, docs, info, init, install, publish, python, run, setup_ide, testing,
-    validate, version, watch, workspace,
+    build, changelog, clean, debug, docs, explain, index, info, init, install, lint, publish,
+    python, release, run, setup_ide, snapshot, testing, validate, version, watch, workspace,
 };
 use echidna::config::Config;
+use echidna::diagnostics;
 use echidna::error::{EchidnaError, Result};
+use echidna::pep440::{PreRelease, Version};
 use echidna::templates::BundleType;
+use echidna::test_report::ReportSpec;
 use echidna::workspace::Workspace;
 use std::io;
 use std::path::PathBuf;
@@ -78,17 +81,25 @@ enum Command {
 
     /// Install the bundle to ChimeraX
     Install {
-        /// Project directory or wheel file
+        /// Project directory
         #[arg(default_value = ".")]
         path: PathBuf,
 
         /// Specific wheel file to install
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with = "index")]
         wheel: Option<PathBuf>,
 
         /// Install as user bundle
         #[arg(long)]
         user: bool,
+
+        /// Install --package from a package index (e.g. one served by 'echidna index serve')
+        #[arg(long, value_name = "URL", requires = "package")]
+        index: Option<String>,
+
+        /// Bundle to install from --index
+        #[arg(long, value_name = "NAME", requires = "index")]
+        package: Option<String>,
     },
 
     /// Build, install, and launch ChimeraX
@@ -112,6 +123,14 @@ enum Command {
         /// Run in nogui mode
         #[arg(long)]
         nogui: bool,
+
+        /// Use a named run profile from echidna.toml
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// List run profiles defined in echidna.toml
+        #[arg(long, conflicts_with = "profile")]
+        list_profiles: bool,
     },
 
     /// Show ChimeraX Python environment info
@@ -168,6 +187,56 @@ enum Command {
         /// Treat warnings as errors
         #[arg(long)]
         strict: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: DiagnosticFormat,
+
+        /// Apply safe automatic fixes to pyproject.toml
+        #[arg(long)]
+        fix: bool,
+
+        /// Also apply unsafe fixes (e.g. renaming the distribution)
+        #[arg(long, requires = "fix")]
+        unsafe_fixes: bool,
+
+        /// Show the fixes as a unified diff without writing them
+        #[arg(long, requires = "fix")]
+        dry_run: bool,
+
+        /// Save and restore a session in ChimeraX (nogui), after running
+        /// tests/session.cxc if present
+        #[arg(long)]
+        session: bool,
+
+        /// Check in ChimeraX (nogui) that include-modules and library-modules are importable
+        #[arg(long)]
+        check_imports: bool,
+    },
+
+    /// Lint bundle source code
+    Lint {
+        /// Project directory
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// ChimeraX version to check against (default: newest known release)
+        #[arg(long, value_name = "VERSION")]
+        target: Option<String>,
+
+        /// Treat warnings as errors
+        #[arg(long)]
+        strict: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: DiagnosticFormat,
+    },
+
+    /// Explain a validation diagnostic code (e.g. CX001)
+    Explain {
+        /// Diagnostic code or name (lists all codes if omitted)
+        code: Option<String>,
     },
 
     /// Show bundle information and status
@@ -203,15 +272,82 @@ enum Command {
         #[arg(long)]
         coverage: bool,
 
+        /// Fail if total coverage is below this percentage (implies --coverage)
+        #[arg(long, value_name = "PERCENT")]
+        cov_fail_under: Option<f64>,
+
+        /// Measure branch coverage (implies --coverage)
+        #[arg(long)]
+        cov_branch: bool,
+
+        /// Write a Cobertura XML coverage report (implies --coverage)
+        #[arg(long, value_name = "PATH")]
+        cov_xml: Option<PathBuf>,
+
+        /// Write an LCOV coverage report (implies --coverage)
+        #[arg(long, value_name = "PATH")]
+        cov_lcov: Option<PathBuf>,
+
+        /// Fail a single test that runs longer than SECS (Unix only)
+        #[arg(long, value_name = "SECS")]
+        test_timeout: Option<f64>,
+
+        /// Rerun failing tests up to N times; tests that pass on retry are flaky
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        retries: u32,
+
         /// Test all bundles in workspace
         #[arg(long)]
         all: bool,
 
+        /// Also run .cxc script tests from tests/
+        #[arg(long)]
+        scripts: bool,
+
+        /// Also run image regression tests from tests/visual/
+        #[arg(long)]
+        visual: bool,
+
+        /// Accept new renders as visual test baselines (implies --visual)
+        #[arg(long)]
+        update_baselines: bool,
+
+        /// Write a test report as FORMAT=PATH (junit or json, repeatable)
+        #[arg(long, value_name = "FORMAT=PATH")]
+        report: Vec<ReportSpec>,
+
         /// Additional arguments passed to pytest
         #[arg(last = true)]
         pytest_args: Vec<String>,
     },
 
+    /// Render images headlessly with offscreen ChimeraX
+    Snapshot {
+        /// Scripts to render (several scripts produce a gallery directory)
+        #[arg(required = true)]
+        scripts: Vec<PathBuf>,
+
+        /// Output image, or gallery directory when several scripts are given
+        #[arg(short, long)]
+        out: PathBuf,
+
+        /// Image size as WxH
+        #[arg(long, default_value_t = snapshot::DEFAULT_SIZE)]
+        size: snapshot::ImageSize,
+
+        /// Project directory
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+
+        /// Skip build step
+        #[arg(long)]
+        no_build: bool,
+
+        /// Skip install step
+        #[arg(long)]
+        no_install: bool,
+    },
+
     /// Generate shell completions
     Completions {
         /// Shell to generate completions for
@@ -243,6 +379,22 @@ enum Command {
         /// Validate without publishing
         #[arg(long)]
         dry_run: bool,
+
+        /// Output format of the dry-run checks
+        #[arg(long, value_enum, default_value = "text", requires = "dry_run")]
+        format: DiagnosticFormat,
+
+        /// Toolshed upload API URL (default: [toolshed].url in the user configuration; none opens the submission page)
+        #[arg(long, value_name = "URL", env = "ECHIDNA_TOOLSHED_URL")]
+        toolshed_url: Option<String>,
+
+        /// Open the Toolshed submission page even if an upload URL is configured
+        #[arg(long, conflicts_with = "dry_run")]
+        browser: bool,
+
+        /// Send the version's CHANGELOG.md section as release notes
+        #[arg(long, conflicts_with = "browser")]
+        release_notes: bool,
     },
 
     /// Watch for changes and auto-rebuild
@@ -258,6 +410,10 @@ enum Command {
         /// Run tests on changes
         #[arg(long, conflicts_with = "run")]
         test: bool,
+
+        /// Run the full test suite after the affected tests pass
+        #[arg(long, requires = "test")]
+        full: bool,
     },
 
     /// Manage bundle version in pyproject.toml
@@ -266,9 +422,62 @@ enum Command {
         #[arg(default_value = ".")]
         path: PathBuf,
 
-        /// Version action: show (default), patch, minor, major, or X.Y.Z
+        /// Version action: show (default), check, patch, minor, major, pre, dev, post, release, or a PEP 440 version
         #[arg(default_value = "show")]
         action: String,
+
+        /// Pre-release phase for 'pre': alpha, beta, or rc
+        phase: Option<String>,
+    },
+
+    /// Write a CHANGELOG section for the current version from git commits
+    Changelog {
+        /// Project directory
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Tag or commit to start after (default: the last version tag)
+        #[arg(long, value_name = "REF")]
+        since: Option<String>,
+
+        /// Print the section instead of writing CHANGELOG.md
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Release the bundle: test, bump version, update CHANGELOG, build, validate, publish, tag
+    Release {
+        /// Project directory
+        #[arg(default_value = ".")]
+        path: PathBuf,
+
+        /// Version bump: patch (default), minor, major, pre (with --phase), dev, post, release, or a PEP 440 version
+        #[arg(long, value_name = "VERSION")]
+        bump: Option<String>,
+
+        /// Pre-release phase for '--bump pre': alpha, beta, or rc
+        #[arg(long, value_name = "PHASE")]
+        phase: Option<String>,
+
+        /// Resume the release at this step
+        #[arg(long, value_enum, value_name = "STEP", default_value = "test")]
+        from: release::ReleaseStep,
+
+        /// Show the release steps without running them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip the Toolshed upload
+        #[arg(long)]
+        no_publish: bool,
+
+        /// Write the CHANGELOG section from git commits instead of the [Unreleased] entries
+        #[arg(long)]
+        generate_changelog: bool,
+
+        /// Toolshed upload API URL (default: [toolshed].url in the user configuration)
+        #[arg(long, value_name = "URL", env = "ECHIDNA_TOOLSHED_URL")]
+        toolshed_url: Option<String>,
     },
 
     /// Launch ChimeraX in debug mode
@@ -297,6 +506,10 @@ enum Command {
     /// Manage bundle workspaces (multiple bundles)
     #[command(subcommand)]
     Workspace(WorkspaceCommand),
+
+    /// Build and serve a local package index of bundle wheels
+    #[command(subcommand)]
+    Index(IndexCommand),
 }
 
 /// Workspace subcommands.
@@ -321,6 +534,31 @@ enum WorkspaceCommand {
     },
 }
 
+/// Package index subcommands.
+#[derive(Subcommand)]
+enum IndexCommand {
+    /// Collect the wheels in dist/ of the bundle or each workspace member into a PEP 503 index
+    Build {
+        /// Index directory
+        dir: PathBuf,
+
+        /// Bundle or workspace directory
+        #[arg(long, default_value = ".")]
+        path: PathBuf,
+    },
+
+    /// Serve a package index on localhost
+    Serve {
+        /// Index directory
+        #[arg(default_value = "index")]
+        dir: PathBuf,
+
+        /// Port to listen on (0 picks a free port)
+        #[arg(long, default_value = "8000")]
+        port: u16,
+    },
+}
+
 #[derive(Clone, Copy, ValueEnum)]
 enum OutputFormat {
     Text,
@@ -336,6 +574,23 @@ impl From<OutputFormat> for python::OutputFormat {
     }
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum DiagnosticFormat {
+    Text,
+    Json,
+    Sarif,
+}
+
+impl From<DiagnosticFormat> for diagnostics::OutputFormat {
+    fn from(f: DiagnosticFormat) -> Self {
+        match f {
+            DiagnosticFormat::Text => diagnostics::OutputFormat::Text,
+            DiagnosticFormat::Json => diagnostics::OutputFormat::Json,
+            DiagnosticFormat::Sarif => diagnostics::OutputFormat::Sarif,
+        }
+    }
+}
+
 fn main() {
     if let Err(e) = run_cli() {
         eprintln!("error: {}", e);
@@ -428,10 +683,17 @@ fn run_cli() -> Result<()> {
             }
         }
 
-        Command::Install { path, wheel, user } => install::execute(install::InstallArgs {
+        Command::Install {
+            path,
+            wheel,
+            user,
+            index,
+            package,
+        } => install::execute(install::InstallArgs {
             path,
             wheel,
             user: user || config.user_install,
+            index: index.zip(package),
             chimerax: chimerax_path()?,
             verbosity,
         }),
@@ -442,15 +704,32 @@ fn run_cli() -> Result<()> {
             no_build,
             no_install,
             nogui,
-        } => run::execute(run::RunArgs {
-            path,
-            script: script.or(config.default_script),
-            no_build,
-            no_install,
-            nogui,
-            chimerax: chimerax_path()?,
-            verbosity,
-        }),
+            profile,
+            list_profiles,
+        } => {
+            if list_profiles {
+                return run::list_profiles(&config.run);
+            }
+
+            let profile = profile.map(|name| config.run.profile(&name)).transpose()?;
+            // Profiles bring their own scripts; only fall back to default_script without one
+            let script = if profile.is_some() {
+                script
+            } else {
+                script.or(config.default_script)
+            };
+
+            run::execute(run::RunArgs {
+                path,
+                script,
+                no_build,
+                no_install,
+                nogui,
+                profile,
+                chimerax: chimerax_path()?,
+                verbosity,
+            })
+        }
 
         Command::Python { format } => python::execute(python::PythonArgs {
             format: format.into(),
@@ -478,9 +757,41 @@ fn run_cli() -> Result<()> {
             clean::execute(clean::CleanArgs { path, all, dry_run })
         }
 
-        Command::Validate { path, strict } => {
-            validate::execute(validate::ValidateArgs { path, strict })
-        }
+        Command::Validate {
+            path,
+            strict,
+            format,
+            fix,
+            unsafe_fixes,
+            dry_run,
+            session,
+            check_imports,
+        } => validate::execute(validate::ValidateArgs {
+            path,
+            strict,
+            format: format.into(),
+            fix,
+            unsafe_fixes,
+            dry_run,
+            session,
+            check_imports,
+            chimerax: (session || check_imports).then(chimerax_path).transpose()?,
+            verbosity,
+        }),
+
+        Command::Lint {
+            path,
+            target,
+            strict,
+            format,
+        } => lint::execute(lint::LintArgs {
+            path,
+            target,
+            strict,
+            format: format.into(),
+        }),
+
+        Command::Explain { code } => explain::execute(explain::ExplainArgs { code }),
 
         Command::Info { path } => info::execute(info::InfoArgs {
             path,
@@ -495,9 +806,37 @@ fn run_cli() -> Result<()> {
             no_build,
             no_install,
             coverage,
+            cov_fail_under,
+            cov_branch,
+            cov_xml,
+            cov_lcov,
+            test_timeout,
+            retries,
             all,
+            scripts,
+            visual,
+            update_baselines,
+            report,
             pytest_args,
         } => {
+            // Command-line coverage options override [test.coverage]
+            let coverage = coverage
+                || cov_fail_under.is_some()
+                || cov_branch
+                || cov_xml.is_some()
+                || cov_lcov.is_some();
+            let mut coverage_config = config.test.coverage.clone();
+            if cov_fail_under.is_some() {
+                coverage_config.fail_under = cov_fail_under;
+            }
+            coverage_config.branch |= cov_branch;
+            if cov_xml.is_some() {
+                coverage_config.xml = cov_xml;
+            }
+            if cov_lcov.is_some() {
+                coverage_config.lcov = cov_lcov;
+            }
+
             if all {
                 // Test all bundles in workspace
                 let path = path.canonicalize().unwrap_or(path.clone());
@@ -509,34 +848,31 @@ fn run_cli() -> Result<()> {
                                 "Workspace has no members".into(),
                             ));
                         }
-                        println!("Testing {} bundles in workspace...\n", members.len());
                         let chimerax = chimerax_path()?;
-                        let mut all_passed = true;
-                        for member in &members {
-                            println!("=== {} ===", member.display());
-                            let result = testing::execute(testing::TestArgs {
-                                path: member.clone(),
+                        let members = members
+                            .into_iter()
+                            .map(|member| testing::TestArgs {
+                                path: member,
+                                paths: Vec::new(),
                                 filter: filter.clone(),
                                 verbose,
                                 no_build,
                                 no_install,
                                 coverage,
+                                coverage_config: coverage_config.clone(),
+                                timeout: test_timeout,
+                                retries,
                                 pytest_args: pytest_args.clone(),
+                                scripts,
+                                visual,
+                                update_baselines,
+                                visual_config: config.test.visual.clone(),
+                                reports: Vec::new(),
                                 chimerax: chimerax.clone(),
                                 verbosity,
-                            });
-                            if result.is_err() {
-                                all_passed = false;
-                                eprintln!("Tests failed for {}", member.display());
-                            }
-                            println!();
-                        }
-                        if all_passed {
-                            println!("All {} bundles passed tests.", members.len());
-                            Ok(())
-                        } else {
-                            Err(EchidnaError::TestFailed(1))
-                        }
+                            })
+                            .collect();
+                        testing::execute_workspace(&root, members, &report)
                     }
                     None => Err(EchidnaError::ConfigError(
                         "No workspace found. Use 'echidna workspace init' to create one.".into(),
@@ -545,18 +881,45 @@ fn run_cli() -> Result<()> {
             } else {
                 testing::execute(testing::TestArgs {
                     path,
+                    paths: Vec::new(),
                     filter,
                     verbose,
                     no_build,
                     no_install,
                     coverage,
+                    coverage_config,
+                    timeout: test_timeout,
+                    retries,
                     pytest_args,
+                    scripts,
+                    visual,
+                    update_baselines,
+                    visual_config: config.test.visual.clone(),
+                    reports: report,
                     chimerax: chimerax_path()?,
                     verbosity,
                 })
             }
         }
 
+        Command::Snapshot {
+            scripts,
+            out,
+            size,
+            path,
+            no_build,
+            no_install,
+        } => snapshot::execute(snapshot::SnapshotArgs {
+            path,
+            scripts,
+            out,
+            size,
+            no_build,
+            no_install,
+            chimerax: chimerax_path()?,
+            verbosity,
+        }),
+
         Command::Completions { shell } => {
             let mut cmd = Cli::command();
             generate(shell, &mut cmd, "echidna", &mut io::stdout());
@@ -569,26 +932,102 @@ fn run_cli() -> Result<()> {
             query: search,
         }),
 
-        Command::Publish { path, dry_run } => {
-            publish::execute(publish::PublishArgs { path, dry_run })
-        }
+        Command::Publish {
+            path,
+            dry_run,
+            format,
+            toolshed_url,
+            browser,
+            release_notes,
+        } => publish::execute(publish::PublishArgs {
+            path,
+            dry_run,
+            format: format.into(),
+            toolshed_url,
+            browser,
+            release_notes,
+        }),
 
-        Command::Watch { path, run, test } => watch::execute(watch::WatchArgs {
+        Command::Watch {
+            path,
+            run,
+            test,
+            full,
+        } => watch::execute(watch::WatchArgs {
             path,
             run,
             test,
+            full,
             chimerax: chimerax_path()?,
             verbosity,
         }),
 
-        Command::Version { path, action } => {
-            let version_action = parse_version_action(&action)?;
+        Command::Version {
+            path,
+            action,
+            phase,
+        } => {
+            let version_action = parse_version_action(&action, phase.as_deref())?;
             version::execute(version::VersionArgs {
                 path,
                 action: version_action,
             })
         }
 
+        Command::Changelog {
+            path,
+            since,
+            dry_run,
+        } => changelog::execute(changelog::ChangelogArgs {
+            path,
+            since,
+            dry_run,
+        }),
+
+        Command::Release {
+            path,
+            bump,
+            phase,
+            from,
+            dry_run,
+            no_publish,
+            generate_changelog,
+            toolshed_url,
+        } => {
+            let action = match bump.as_deref() {
+                Some(action @ ("show" | "check")) => {
+                    return Err(EchidnaError::ConfigError(format!(
+                        "Invalid release version '{}'. Use: patch, minor, major, pre --phase alpha|beta|rc, dev, post, release, or a version such as 1.2.0rc1",
+                        action
+                    )))
+                }
+                Some("pre") if phase.is_none() => {
+                    return Err(EchidnaError::ConfigError(
+                        "'pre' needs --phase alpha, beta, or rc (e.g. 'release --bump pre --phase rc')"
+                            .into(),
+                    ))
+                }
+                Some(action) => Some(parse_version_action(action, phase.as_deref())?),
+                None if phase.is_some() => {
+                    return Err(EchidnaError::ConfigError(
+                        "--phase is only used with '--bump pre'".into(),
+                    ))
+                }
+                None => None,
+            };
+            release::execute(release::ReleaseArgs {
+                path,
+                action,
+                from,
+                dry_run,
+                no_publish,
+                generate_changelog,
+                toolshed_url,
+                chimerax: chimerax_path().ok(),
+                verbosity,
+            })
+        }
+
         Command::Debug {
             path,
             pdb,
@@ -605,6 +1044,11 @@ fn run_cli() -> Result<()> {
             verbosity,
         }),
 
+        Command::Index(cmd) => match cmd {
+            IndexCommand::Build { dir, path } => index::build(index::IndexBuildArgs { path, dir }),
+            IndexCommand::Serve { dir, port } => index::serve(index::IndexServeArgs { dir, port }),
+        },
+
         Command::Workspace(cmd) => match cmd {
             WorkspaceCommand::Init { path, force } => {
                 workspace::init(workspace::WorkspaceInitArgs { path, force })
@@ -617,21 +1061,37 @@ fn run_cli() -> Result<()> {
 }
 
 /// Parse version action string into VersionAction enum.
-fn parse_version_action(action: &str) -> Result<version::VersionAction> {
+fn parse_version_action(action: &str, phase: Option<&str>) -> Result<version::VersionAction> {
+    if action != "pre" && phase.is_some() {
+        return Err(EchidnaError::ConfigError(format!(
+            "Unexpected argument after '{}'; only 'pre' takes a phase",
+            action
+        )));
+    }
     match action {
         "show" => Ok(version::VersionAction::Show),
+        "check" => Ok(version::VersionAction::Check),
         "patch" => Ok(version::VersionAction::BumpPatch),
         "minor" => Ok(version::VersionAction::BumpMinor),
         "major" => Ok(version::VersionAction::BumpMajor),
+        "dev" => Ok(version::VersionAction::Dev),
+        "post" => Ok(version::VersionAction::Post),
+        "release" => Ok(version::VersionAction::Release),
+        "pre" => phase
+            .and_then(PreRelease::parse)
+            .map(version::VersionAction::Pre)
+            .ok_or_else(|| {
+                EchidnaError::ConfigError(
+                    "'pre' needs a phase: alpha, beta, or rc (e.g. 'pre rc')".into(),
+                )
+            }),
         _ => {
-            // Check if it's a valid version string (X.Y.Z)
-            if action.split('.').count() == 3
-                && action.split('.').all(|part| part.parse::<u32>().is_ok())
-            {
+            // Check if it's a valid PEP 440 version string
+            if Version::parse(action).is_some() {
                 Ok(version::VersionAction::Set(action.to_string()))
             } else {
                 Err(EchidnaError::ConfigError(format!(
-                    "Invalid version action '{}'. Use: show, patch, minor, major, or X.Y.Z",
+                    "Invalid version action '{}'. Use: show, check, patch, minor, major, pre alpha|beta|rc, dev, post, release, or a version such as 1.2.0rc1",
                     action
                 )))
             }
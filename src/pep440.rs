@@ -0,0 +1,461 @@
+//! PEP 440 versions: parsing, normalization, ordering, and bumps.
+//!
+//! `1!2.0.0rc1.post2.dev3+local.7` is epoch 1, release 2.0.0, pre-release
+//! rc1, post-release 2, development release 3, and local label `local.7`.
+//! Alternative spellings (`1.0-alpha.1`, `1.0.0-2`, `v1.0`) are accepted and
+//! displayed in normalized form (`1.0a1`, `1.0.0.post2`, `1.0`).
+
+use crate::error::{EchidnaError, Result};
+use regex::Regex;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// Pre-release phase, in release order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PreRelease {
+    Alpha,
+    Beta,
+    Rc,
+}
+
+impl PreRelease {
+    /// Parse a phase name: `alpha`/`a`, `beta`/`b`, or `rc`/`c`/`pre`/`preview`.
+    pub fn parse(label: &str) -> Option<Self> {
+        match label.to_ascii_lowercase().as_str() {
+            "a" | "alpha" => Some(Self::Alpha),
+            "b" | "beta" => Some(Self::Beta),
+            "rc" | "c" | "pre" | "preview" => Some(Self::Rc),
+            _ => None,
+        }
+    }
+
+    /// Phase name as accepted by `echidna version pre`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Alpha => "alpha",
+            Self::Beta => "beta",
+            Self::Rc => "rc",
+        }
+    }
+
+    fn suffix(self) -> &'static str {
+        match self {
+            Self::Alpha => "a",
+            Self::Beta => "b",
+            Self::Rc => "rc",
+        }
+    }
+}
+
+/// A segment of a local version label; letters sort before numbers.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum LocalPart {
+    Text(String),
+    Number(u64),
+}
+
+/// A PEP 440 version.
+#[derive(Debug, Clone)]
+pub struct Version {
+    pub epoch: u64,
+    pub release: Vec<u64>,
+    pub pre: Option<(PreRelease, u64)>,
+    pub post: Option<u64>,
+    pub dev: Option<u64>,
+    /// Local label, normalized to lowercase with `.` separators
+    pub local: Option<String>,
+}
+
+impl Version {
+    /// Parse a version; `None` if it is not valid PEP 440.
+    pub fn parse(version: &str) -> Option<Self> {
+        let pattern = Regex::new(
+            r"(?ix)^\s*v?
+            (?:(?P<epoch>[0-9]+)!)?
+            (?P<release>[0-9]+(?:\.[0-9]+)*)
+            (?:[-_.]?(?P<pre_l>alpha|a|beta|b|preview|pre|c|rc)[-_.]?(?P<pre_n>[0-9]+)?)?
+            (?:-(?P<post_n1>[0-9]+)|[-_.]?(?P<post_l>post|rev|r)[-_.]?(?P<post_n2>[0-9]+)?)?
+            (?:[-_.]?(?P<dev_l>dev)[-_.]?(?P<dev_n>[0-9]+)?)?
+            (?:\+(?P<local>[a-z0-9]+(?:[-_.][a-z0-9]+)*))?
+            \s*$",
+        )
+        .unwrap();
+        let captures = pattern.captures(version)?;
+        let number = |name: &str| -> Option<Option<u64>> {
+            match captures.name(name) {
+                Some(n) => n.as_str().parse().ok().map(Some),
+                None => Some(None),
+            }
+        };
+
+        let release = captures["release"]
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect::<Option<Vec<u64>>>()?;
+        let pre = match captures.name("pre_l") {
+            Some(label) => Some((
+                PreRelease::parse(label.as_str())?,
+                number("pre_n")?.unwrap_or(0),
+            )),
+            None => None,
+        };
+        let post = if captures.name("post_n1").is_some() {
+            number("post_n1")?
+        } else if captures.name("post_l").is_some() {
+            Some(number("post_n2")?.unwrap_or(0))
+        } else {
+            None
+        };
+        let dev = if captures.name("dev_l").is_some() {
+            Some(number("dev_n")?.unwrap_or(0))
+        } else {
+            None
+        };
+        let local = captures.name("local").map(|local| {
+            local
+                .as_str()
+                .to_lowercase()
+                .split(['-', '_', '.'])
+                .collect::<Vec<_>>()
+                .join(".")
+        });
+
+        Some(Self {
+            epoch: number("epoch")?.unwrap_or(0),
+            release,
+            pre,
+            post,
+            dev,
+            local,
+        })
+    }
+
+    /// Whether this is a pre-release or development release.
+    pub fn is_prerelease(&self) -> bool {
+        self.pre.is_some() || self.dev.is_some()
+    }
+
+    /// The final release with `index` (0 = major, 1 = minor, 2 = patch)
+    /// incremented and the later release numbers reset to zero.
+    pub fn bump_release(&self, index: usize) -> Self {
+        let len = self.release.len().max(3).max(index + 1);
+        let mut release = self.release.clone();
+        release.resize(len, 0);
+        release[index] += 1;
+        for part in &mut release[index + 1..] {
+            *part = 0;
+        }
+        release.truncate(3.max(index + 1));
+        Self {
+            epoch: self.epoch,
+            release,
+            pre: None,
+            post: None,
+            dev: None,
+            local: None,
+        }
+    }
+
+    /// The next pre-release of `phase`: `1.2.0b1` -> `1.2.0b2` (beta) or
+    /// `1.2.0rc1` (rc); a final release starts the next patch, `1.2.0` -> `1.2.1b1`,
+    /// while a development release stays on its release, `1.2.1.dev1` -> `1.2.1b1`.
+    pub fn bump_pre(&self, phase: PreRelease) -> Result<Self> {
+        let (release, number) = match self.pre {
+            // 1.2.1.dev1 precedes 1.2.1a1
+            None if self.dev.is_some() && self.post.is_none() => (self.release.clone(), 1),
+            None => (self.bump_release(2).release, 1),
+            Some((current, n)) if current == phase => {
+                // 1.2.0b1.dev3 precedes 1.2.0b1 itself
+                let next = if self.dev.is_some() && self.post.is_none() {
+                    n
+                } else {
+                    n + 1
+                };
+                (self.release.clone(), next)
+            }
+            Some((current, _)) if current < phase => (self.release.clone(), 1),
+            Some(_) => {
+                return Err(EchidnaError::ConfigError(format!(
+                    "Cannot go back from {} to a {} pre-release",
+                    self,
+                    phase.name()
+                )))
+            }
+        };
+        Ok(Self {
+            epoch: self.epoch,
+            release,
+            pre: Some((phase, number)),
+            post: None,
+            dev: None,
+            local: None,
+        })
+    }
+
+    /// The next development release: `1.2.0.dev1` -> `1.2.0.dev2`; otherwise the
+    /// first development release of the next post-release, pre-release, or patch.
+    pub fn bump_dev(&self) -> Self {
+        let mut next = Self {
+            local: None,
+            ..self.clone()
+        };
+        match self.dev {
+            Some(n) => next.dev = Some(n + 1),
+            None => {
+                if let Some(post) = self.post {
+                    next.post = Some(post + 1);
+                } else if let Some((phase, n)) = self.pre {
+                    next.pre = Some((phase, n + 1));
+                } else {
+                    next = self.bump_release(2);
+                }
+                next.dev = Some(1);
+            }
+        }
+        next
+    }
+
+    /// The next post-release: `1.2.0` -> `1.2.0.post1`, `1.2.0.post1` ->
+    /// `1.2.0.post2`, `1.2.0.post2.dev1` -> `1.2.0.post2`.
+    pub fn bump_post(&self) -> Result<Self> {
+        let post = match (self.post, self.dev) {
+            (Some(n), Some(_)) => n,
+            (Some(n), None) => n + 1,
+            (None, None) => 1,
+            (None, Some(_)) => {
+                return Err(EchidnaError::ConfigError(format!(
+                    "Cannot make a post-release of the development release {}",
+                    self
+                )))
+            }
+        };
+        Ok(Self {
+            post: Some(post),
+            dev: None,
+            local: None,
+            ..self.clone()
+        })
+    }
+
+    /// The final release: pre-release and development segments dropped.
+    pub fn finalize(&self) -> Result<Self> {
+        if !self.is_prerelease() {
+            return Err(EchidnaError::ConfigError(format!(
+                "{} is already a final release",
+                self
+            )));
+        }
+        Ok(Self {
+            pre: None,
+            dev: None,
+            ..self.clone()
+        })
+    }
+
+    /// Release numbers without trailing zeros (`1.0.0` == `1`).
+    fn release_key(&self) -> &[u64] {
+        let len = self
+            .release
+            .iter()
+            .rposition(|&n| n != 0)
+            .map_or(0, |i| i + 1);
+        &self.release[..len]
+    }
+
+    /// Pre-release key: a development release of a final release sorts before
+    /// all pre-releases, and a final release after them.
+    fn pre_key(&self) -> (u8, Option<(PreRelease, u64)>) {
+        match (self.pre, self.post, self.dev) {
+            (None, None, Some(_)) => (0, None),
+            (Some(pre), _, _) => (1, Some(pre)),
+            (None, _, _) => (2, None),
+        }
+    }
+
+    fn local_key(&self) -> Option<Vec<LocalPart>> {
+        self.local.as_ref().map(|local| {
+            local
+                .split('.')
+                .map(|part| match part.parse() {
+                    Ok(n) => LocalPart::Number(n),
+                    Err(_) => LocalPart::Text(part.to_string()),
+                })
+                .collect()
+        })
+    }
+}
+
+impl FromStr for Version {
+    type Err = EchidnaError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s).ok_or_else(|| {
+            EchidnaError::ConfigError(format!("Invalid version '{}' (expected PEP 440)", s))
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.epoch != 0 {
+            write!(f, "{}!", self.epoch)?;
+        }
+        let release: Vec<String> = self.release.iter().map(u64::to_string).collect();
+        write!(f, "{}", release.join("."))?;
+        if let Some((phase, n)) = self.pre {
+            write!(f, "{}{}", phase.suffix(), n)?;
+        }
+        if let Some(n) = self.post {
+            write!(f, ".post{}", n)?;
+        }
+        if let Some(n) = self.dev {
+            write!(f, ".dev{}", n)?;
+        }
+        if let Some(local) = &self.local {
+            write!(f, "+{}", local)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| self.release_key().cmp(other.release_key()))
+            .then_with(|| self.pre_key().cmp(&other.pre_key()))
+            .then_with(|| self.post.cmp(&other.post))
+            .then_with(|| {
+                // No development segment sorts last
+                let dev = |v: &Self| v.dev.map_or((1, 0), |n| (0, n));
+                dev(self).cmp(&dev(other))
+            })
+            .then_with(|| self.local_key().cmp(&other.local_key()))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(version: &str) -> Version {
+        Version::parse(version).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_normalize() {
+        let version = v("1!2.0.0rc1.post2.dev3+Local-7");
+        assert_eq!(version.epoch, 1);
+        assert_eq!(version.release, vec![2, 0, 0]);
+        assert_eq!(version.pre, Some((PreRelease::Rc, 1)));
+        assert_eq!(version.post, Some(2));
+        assert_eq!(version.dev, Some(3));
+        assert_eq!(version.to_string(), "1!2.0.0rc1.post2.dev3+local.7");
+
+        for (input, normalized) in [
+            ("1.2.3", "1.2.3"),
+            ("v1.0", "1.0"),
+            ("1.0-alpha.1", "1.0a1"),
+            ("1.0.0-beta2", "1.0.0b2"),
+            ("1.0c1", "1.0rc1"),
+            ("1.0.0-2", "1.0.0.post2"),
+            ("1.0.0.post", "1.0.0.post0"),
+            ("1.0.0-dev", "1.0.0.dev0"),
+            ("1.2.0.DEV3", "1.2.0.dev3"),
+        ] {
+            assert_eq!(v(input).to_string(), normalized, "{}", input);
+        }
+
+        for invalid in ["", "abc", "1.2.x", "1.0+", "1.0-", "1..0", "1.0rc1rc2"] {
+            assert!(Version::parse(invalid).is_none(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        let ordered = [
+            "1.0.dev0",
+            "1.0a1.dev1",
+            "1.0a1",
+            "1.0a1.post1",
+            "1.0b1",
+            "1.0rc1",
+            "1.0",
+            "1.0+abc",
+            "1.0+5",
+            "1.0.post1.dev1",
+            "1.0.post1",
+            "1.0.1",
+            "1.10",
+            "1!0.1",
+        ];
+        for pair in ordered.windows(2) {
+            assert!(v(pair[0]) < v(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(v("1.6"), v("1.6.0"));
+        assert_ne!(v("1.6"), v("1.6.1"));
+    }
+
+    #[test]
+    fn test_bump_release() {
+        assert_eq!(v("1.2.3").bump_release(2).to_string(), "1.2.4");
+        assert_eq!(v("1.2.3").bump_release(1).to_string(), "1.3.0");
+        assert_eq!(v("1.2.3").bump_release(0).to_string(), "2.0.0");
+        assert_eq!(v("1.2").bump_release(2).to_string(), "1.2.1");
+        assert_eq!(v("1.2.3.4").bump_release(2).to_string(), "1.2.4");
+        assert_eq!(v("1.2.0rc1.dev2").bump_release(1).to_string(), "1.3.0");
+    }
+
+    #[test]
+    fn test_bump_pre() {
+        let bump = |version: &str, phase| v(version).bump_pre(phase).unwrap().to_string();
+        assert_eq!(bump("1.2.0", PreRelease::Alpha), "1.2.1a1");
+        assert_eq!(bump("1.2.1a1", PreRelease::Alpha), "1.2.1a2");
+        assert_eq!(bump("1.2.1a2", PreRelease::Rc), "1.2.1rc1");
+        assert_eq!(bump("1.2.1b1.dev3", PreRelease::Beta), "1.2.1b1");
+        assert_eq!(bump("1.2.1.dev1", PreRelease::Alpha), "1.2.1a1");
+        assert_eq!(bump("1.2.0.post1.dev1", PreRelease::Alpha), "1.2.1a1");
+        assert!(v("1.2.1rc1").bump_pre(PreRelease::Beta).is_err());
+    }
+
+    #[test]
+    fn test_bump_dev_post_and_finalize() {
+        assert_eq!(v("1.2.0").bump_dev().to_string(), "1.2.1.dev1");
+        assert_eq!(v("1.2.1.dev1").bump_dev().to_string(), "1.2.1.dev2");
+        assert_eq!(v("1.2.0rc1").bump_dev().to_string(), "1.2.0rc2.dev1");
+        assert_eq!(v("1.2.0.post1").bump_dev().to_string(), "1.2.0.post2.dev1");
+
+        assert_eq!(v("1.2.0").bump_post().unwrap().to_string(), "1.2.0.post1");
+        assert_eq!(
+            v("1.2.0.post1").bump_post().unwrap().to_string(),
+            "1.2.0.post2"
+        );
+        assert_eq!(
+            v("1.2.0.post2.dev1").bump_post().unwrap().to_string(),
+            "1.2.0.post2"
+        );
+        assert!(v("1.2.0.dev1").bump_post().is_err());
+
+        assert_eq!(v("1.2.0rc2.dev1").finalize().unwrap().to_string(), "1.2.0");
+        assert_eq!(
+            v("1.2.0.post1.dev1").finalize().unwrap().to_string(),
+            "1.2.0.post1"
+        );
+        assert!(v("1.2.0").finalize().is_err());
+    }
+}
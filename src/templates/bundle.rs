@@ -55,8 +55,10 @@ impl BundleType {
 const PYPROJECT_TOML_TEMPLATE: &str = include_str!("../../templates/command/pyproject.toml.tmpl");
 const INIT_PY_TEMPLATE: &str = include_str!("../../templates/command/init_py.tmpl");
 const CMD_PY_TEMPLATE: &str = include_str!("../../templates/command/cmd_py.tmpl");
+const CMD_TEST_PY_TEMPLATE: &str = include_str!("../../templates/command/test_py.tmpl");
 const SMOKE_CXC_TEMPLATE: &str = include_str!("../../templates/common/smoke_cxc.tmpl");
 const README_MD_TEMPLATE: &str = include_str!("../../templates/common/readme_md.tmpl");
+const TEST_PY_TEMPLATE: &str = include_str!("../../templates/common/test_py.tmpl");
 
 // Tool (Qt) templates
 const TOOL_PYPROJECT_TEMPLATE: &str = include_str!("../../templates/tool/pyproject.toml.tmpl");
@@ -187,16 +189,26 @@ impl BundleTemplate {
         // So we put files directly in src/, not in a subdirectory
         let src_dir = target_dir.join("src");
         let scripts_dir = target_dir.join("scripts");
+        let tests_dir = target_dir.join("tests");
 
         std::fs::create_dir_all(&src_dir)?;
         std::fs::create_dir_all(&scripts_dir)?;
+        std::fs::create_dir_all(&tests_dir)?;
 
         let mut created_files = Vec::new();
 
-        // Common files (README and smoke test)
+        // Common files (README, smoke test, and pytest tests using echidna's fixtures)
+        let test_template = match self.bundle_type {
+            BundleType::Command => CMD_TEST_PY_TEMPLATE,
+            _ => TEST_PY_TEMPLATE,
+        };
         let common_files = [
             (scripts_dir.join("smoke.cxc"), SMOKE_CXC_TEMPLATE),
             (target_dir.join("README.md"), README_MD_TEMPLATE),
+            (
+                tests_dir.join(format!("test_{}.py", self.command_name)),
+                test_template,
+            ),
         ];
 
         for (path, template) in common_files {
@@ -563,9 +575,10 @@ mod tests {
         assert!(temp.path().join("src/cmd.py").exists());
         assert!(temp.path().join("scripts/smoke.cxc").exists());
         assert!(temp.path().join("README.md").exists());
+        assert!(temp.path().join("tests/test_test_bundle.py").exists());
 
         // Check created files list
-        assert_eq!(created.len(), 5);
+        assert_eq!(created.len(), 6);
     }
 
     #[test]
@@ -581,6 +594,19 @@ mod tests {
         assert!(content.contains("[chimerax.command.my_tool]"));
     }
 
+    #[test]
+    fn test_generate_tests_use_plugin_fixtures() {
+        let temp = TempDir::new().unwrap();
+        let template = BundleTemplate::new("my-tool").unwrap();
+
+        template.generate(temp.path()).unwrap();
+
+        let content = std::fs::read_to_string(temp.path().join("tests/test_my_tool.py")).unwrap();
+        assert!(content.contains("def test_my_tool_default_message(session, chimerax_log):"));
+        assert!(content.contains("run(session, \"my_tool\")"));
+        assert!(content.contains("find_bundle(\"ChimeraX-MyTool\""));
+    }
+
     #[test]
     fn test_trimmed_name() {
         let template = BundleTemplate::new("  my-tool  ").unwrap();
@@ -645,7 +671,8 @@ mod tests {
         assert!(temp.path().join("src/tool.py").exists());
         assert!(temp.path().join("scripts/smoke.cxc").exists());
         assert!(temp.path().join("README.md").exists());
-        assert_eq!(created.len(), 5);
+        assert!(temp.path().join("tests/test_test_tool.py").exists());
+        assert_eq!(created.len(), 6);
     }
 
     #[test]
@@ -658,7 +685,7 @@ mod tests {
         assert!(temp.path().join("pyproject.toml").exists());
         assert!(temp.path().join("src/__init__.py").exists());
         assert!(temp.path().join("src/open.py").exists());
-        assert_eq!(created.len(), 5);
+        assert_eq!(created.len(), 6);
     }
 
     #[test]
@@ -671,7 +698,7 @@ mod tests {
         assert!(temp.path().join("pyproject.toml").exists());
         assert!(temp.path().join("src/__init__.py").exists());
         assert!(temp.path().join("src/selector.py").exists());
-        assert_eq!(created.len(), 5);
+        assert_eq!(created.len(), 6);
     }
 
     #[test]
@@ -702,7 +729,8 @@ mod tests {
             .exists());
         assert!(temp.path().join("scripts/smoke.cxc").exists());
         assert!(temp.path().join("README.md").exists());
-        assert_eq!(created.len(), 6);
+        assert!(temp.path().join("tests/test_test_cpp.py").exists());
+        assert_eq!(created.len(), 7);
     }
 
     #[test]
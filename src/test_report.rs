@@ -0,0 +1,650 @@
+//! Structured test results and report output.
+//!
+//! Results from pytest (read from its `--junitxml` output) and from echidna's
+//! own test runners are collected into a [`TestReport`], which can be written
+//! as JUnit XML or JSON and merged across workspace members.
+
+use crate::error::{EchidnaError, Result};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Status of a single test case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Error,
+    Skipped,
+    /// Failed, then passed on retry
+    Flaky,
+}
+
+/// JUnit property set by echidna's pytest plugin on tests that passed on retry.
+pub const FLAKY_PROPERTY: &str = "echidna_flaky";
+
+/// A single test case result.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TestCase {
+    /// Test name (e.g., "test_open_structure")
+    pub name: String,
+    /// Class or module containing the test (e.g., "tests.test_cmd")
+    pub classname: String,
+    /// Duration in seconds
+    pub time: f64,
+    /// Test status
+    pub status: TestStatus,
+    /// Short failure, error, or skip message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Full failure or error details (e.g., traceback)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    /// Number of attempts needed to pass (flaky tests)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<u32>,
+}
+
+impl TestCase {
+    /// Create a test case with the given status.
+    pub fn new(name: impl Into<String>, classname: impl Into<String>, status: TestStatus) -> Self {
+        Self {
+            name: name.into(),
+            classname: classname.into(),
+            time: 0.0,
+            status,
+            message: None,
+            details: None,
+            attempts: None,
+        }
+    }
+}
+
+/// Pass/fail/skip counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TestCounts {
+    pub passed: usize,
+    pub failed: usize,
+    pub errors: usize,
+    pub skipped: usize,
+    pub flaky: usize,
+}
+
+impl TestCounts {
+    /// Total number of tests.
+    pub fn total(&self) -> usize {
+        self.passed + self.failed + self.errors + self.skipped + self.flaky
+    }
+
+    /// Whether any test failed or errored.
+    pub fn has_failures(&self) -> bool {
+        self.failed > 0 || self.errors > 0
+    }
+
+    fn add(&mut self, other: TestCounts) {
+        self.passed += other.passed;
+        self.failed += other.failed;
+        self.errors += other.errors;
+        self.skipped += other.skipped;
+        self.flaky += other.flaky;
+    }
+}
+
+impl std::fmt::Display for TestCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} passed, {} failed, {} skipped",
+            self.passed,
+            self.failed + self.errors,
+            self.skipped
+        )?;
+        if self.flaky > 0 {
+            write!(f, ", {} flaky", self.flaky)?;
+        }
+        Ok(())
+    }
+}
+
+/// A named group of test cases (e.g., pytest, or one workspace member).
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TestSuite {
+    /// Suite name
+    pub name: String,
+    /// Test cases
+    pub tests: Vec<TestCase>,
+}
+
+impl TestSuite {
+    /// Create an empty suite.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            tests: Vec::new(),
+        }
+    }
+
+    /// Count test cases by status.
+    pub fn counts(&self) -> TestCounts {
+        let mut counts = TestCounts::default();
+        for test in &self.tests {
+            match test.status {
+                TestStatus::Passed => counts.passed += 1,
+                TestStatus::Failed => counts.failed += 1,
+                TestStatus::Error => counts.errors += 1,
+                TestStatus::Skipped => counts.skipped += 1,
+                TestStatus::Flaky => counts.flaky += 1,
+            }
+        }
+        counts
+    }
+
+    /// Total duration in seconds.
+    pub fn time(&self) -> f64 {
+        self.tests.iter().map(|t| t.time).sum()
+    }
+}
+
+/// A collection of test suites.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TestReport {
+    pub suites: Vec<TestSuite>,
+}
+
+impl TestReport {
+    /// Counts across all suites.
+    pub fn counts(&self) -> TestCounts {
+        let mut counts = TestCounts::default();
+        for suite in &self.suites {
+            counts.add(suite.counts());
+        }
+        counts
+    }
+
+    /// Prefix every suite name, e.g. with a workspace member name.
+    pub fn prefixed(mut self, prefix: &str) -> Self {
+        for suite in &mut self.suites {
+            suite.name = format!("{}/{}", prefix, suite.name);
+        }
+        self
+    }
+
+    /// Append all suites of another report.
+    pub fn merge(&mut self, other: TestReport) {
+        self.suites.extend(other.suites);
+    }
+
+    /// Parse pytest's JUnit XML output into a single suite named `suite_name`.
+    ///
+    /// Both the `<testsuites>` root (pytest >= 5.1) and a bare `<testsuite>`
+    /// root are accepted; every `<testcase>` is collected.
+    pub fn parse_junit_xml(xml: &str, suite_name: &str) -> Result<TestSuite> {
+        let xml_err = |e: String| EchidnaError::ConfigError(format!("Invalid JUnit XML: {}", e));
+
+        let mut reader = Reader::from_str(xml);
+        let mut suite = TestSuite::new(suite_name);
+        let mut current: Option<TestCase> = None;
+        let mut in_detail = false;
+
+        loop {
+            match reader.read_event().map_err(|e| xml_err(e.to_string()))? {
+                Event::Start(e) => match e.name().as_ref() {
+                    b"testcase" => current = Some(testcase_from_xml(&e)?),
+                    b"failure" | b"error" | b"skipped" => {
+                        if let Some(case) = current.as_mut() {
+                            apply_outcome(case, &e)?;
+                            in_detail = true;
+                        }
+                    }
+                    _ => {}
+                },
+                Event::Empty(e) => match e.name().as_ref() {
+                    b"testcase" => suite.tests.push(testcase_from_xml(&e)?),
+                    b"property" => {
+                        if let Some(case) = current.as_mut() {
+                            apply_property(case, &e)?;
+                        }
+                    }
+                    b"failure" | b"error" | b"skipped" => {
+                        if let Some(case) = current.as_mut() {
+                            apply_outcome(case, &e)?;
+                        }
+                    }
+                    _ => {}
+                },
+                Event::End(e) => match e.name().as_ref() {
+                    b"testcase" => {
+                        if let Some(case) = current.take() {
+                            suite.tests.push(case);
+                        }
+                    }
+                    b"failure" | b"error" | b"skipped" => in_detail = false,
+                    _ => {}
+                },
+                Event::Text(t) if in_detail => {
+                    let text = t.decode().map_err(|e| xml_err(e.to_string()))?;
+                    append_details(&mut current, &text);
+                }
+                Event::CData(t) if in_detail => {
+                    let text = t.decode().map_err(|e| xml_err(e.to_string()))?;
+                    append_details(&mut current, &text);
+                }
+                Event::GeneralRef(r) if in_detail => {
+                    let resolved = if r.is_char_ref() {
+                        r.resolve_char_ref()
+                            .map_err(|e| xml_err(e.to_string()))?
+                            .map(|c| c.to_string())
+                    } else {
+                        let name = r.decode().map_err(|e| xml_err(e.to_string()))?;
+                        quick_xml::escape::resolve_predefined_entity(&name).map(str::to_string)
+                    };
+                    if let Some(text) = resolved {
+                        append_details(&mut current, &text);
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        Ok(suite)
+    }
+
+    /// Serialize as JUnit XML.
+    pub fn to_junit_xml(&self) -> String {
+        let counts = self.counts();
+        let total_time: f64 = self.suites.iter().map(|s| s.time()).sum();
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuites name=\"echidna\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+            counts.total(),
+            counts.failed,
+            counts.errors,
+            counts.skipped,
+            total_time
+        ));
+
+        for suite in &self.suites {
+            let c = suite.counts();
+            xml.push_str(&format!(
+                "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+                xml_escape(&suite.name),
+                c.total(),
+                c.failed,
+                c.errors,
+                c.skipped,
+                suite.time()
+            ));
+            for test in &suite.tests {
+                xml.push_str(&format!(
+                    "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+                    xml_escape(&test.classname),
+                    xml_escape(&test.name),
+                    test.time
+                ));
+                let tag = match test.status {
+                    TestStatus::Passed => {
+                        xml.push_str(" />\n");
+                        continue;
+                    }
+                    TestStatus::Flaky => {
+                        xml.push_str(&format!(
+                            ">\n      <properties><property name=\"{}\" value=\"{}\" /></properties>\n    </testcase>\n",
+                            FLAKY_PROPERTY,
+                            test.attempts.unwrap_or(2)
+                        ));
+                        continue;
+                    }
+                    TestStatus::Failed => "failure",
+                    TestStatus::Error => "error",
+                    TestStatus::Skipped => "skipped",
+                };
+                xml.push_str(">\n");
+                xml.push_str(&format!(
+                    "      <{} message=\"{}\">{}</{}>\n",
+                    tag,
+                    xml_escape(test.message.as_deref().unwrap_or("")),
+                    xml_escape(test.details.as_deref().unwrap_or("")),
+                    tag
+                ));
+                xml.push_str("    </testcase>\n");
+            }
+            xml.push_str("  </testsuite>\n");
+        }
+
+        xml.push_str("</testsuites>\n");
+        xml
+    }
+
+    /// Serialize as JSON.
+    pub fn to_json(&self) -> Result<String> {
+        #[derive(Serialize)]
+        struct Summary {
+            #[serde(flatten)]
+            counts: TestCounts,
+            total: usize,
+            time: f64,
+        }
+
+        #[derive(Serialize)]
+        struct JsonSuite<'a> {
+            name: &'a str,
+            summary: Summary,
+            tests: &'a [TestCase],
+        }
+
+        #[derive(Serialize)]
+        struct JsonReport<'a> {
+            summary: Summary,
+            suites: Vec<JsonSuite<'a>>,
+        }
+
+        let counts = self.counts();
+        let report = JsonReport {
+            summary: Summary {
+                counts,
+                total: counts.total(),
+                time: self.suites.iter().map(|s| s.time()).sum(),
+            },
+            suites: self
+                .suites
+                .iter()
+                .map(|s| {
+                    let counts = s.counts();
+                    JsonSuite {
+                        name: &s.name,
+                        summary: Summary {
+                            counts,
+                            total: counts.total(),
+                            time: s.time(),
+                        },
+                        tests: &s.tests,
+                    }
+                })
+                .collect(),
+        };
+
+        Ok(serde_json::to_string_pretty(&report)?)
+    }
+
+    /// Write the report in the requested format.
+    pub fn write(&self, spec: &ReportSpec) -> Result<()> {
+        let content = match spec.format {
+            ReportFormat::Junit => self.to_junit_xml(),
+            ReportFormat::Json => self.to_json()?,
+        };
+        if let Some(parent) = spec.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+        std::fs::write(&spec.path, content)?;
+        Ok(())
+    }
+}
+
+/// Report output format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Junit,
+    Json,
+}
+
+/// A requested report: `junit=path.xml` or `json=path.json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportSpec {
+    pub format: ReportFormat,
+    pub path: PathBuf,
+}
+
+impl std::str::FromStr for ReportSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (format, path) = s
+            .split_once('=')
+            .ok_or_else(|| format!("invalid report '{}' (expected FORMAT=PATH)", s))?;
+        let format = match format {
+            "junit" => ReportFormat::Junit,
+            "json" => ReportFormat::Json,
+            other => {
+                return Err(format!(
+                    "unknown report format '{}' (expected junit or json)",
+                    other
+                ))
+            }
+        };
+        if path.is_empty() {
+            return Err(format!("missing report path in '{}'", s));
+        }
+        Ok(Self {
+            format,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+/// Build a test case from `<testcase>` attributes.
+fn testcase_from_xml(e: &BytesStart) -> Result<TestCase> {
+    let mut case = TestCase::new(
+        attribute(e, "name")?.unwrap_or_default(),
+        attribute(e, "classname")?.unwrap_or_default(),
+        TestStatus::Passed,
+    );
+    case.time = attribute(e, "time")?
+        .and_then(|t| t.parse().ok())
+        .unwrap_or(0.0);
+    Ok(case)
+}
+
+/// Apply a `<failure>`, `<error>`, or `<skipped>` element to a test case.
+fn apply_outcome(case: &mut TestCase, e: &BytesStart) -> Result<()> {
+    let status = match e.name().as_ref() {
+        b"failure" => TestStatus::Failed,
+        b"error" => TestStatus::Error,
+        _ => TestStatus::Skipped,
+    };
+    // An error (e.g., in teardown) outranks an earlier failure or skip
+    if case.status == TestStatus::Passed || status == TestStatus::Error {
+        case.status = status;
+        case.message = attribute(e, "message")?;
+    }
+    Ok(())
+}
+
+/// Apply a `<property>` of a test case (marks flaky tests).
+fn apply_property(case: &mut TestCase, e: &BytesStart) -> Result<()> {
+    if attribute(e, "name")?.as_deref() != Some(FLAKY_PROPERTY) {
+        return Ok(());
+    }
+    if case.status == TestStatus::Passed {
+        case.status = TestStatus::Flaky;
+        case.attempts = attribute(e, "value")?.and_then(|v| v.parse().ok());
+    }
+    Ok(())
+}
+
+/// Append text to the details of the current test case.
+fn append_details(case: &mut Option<TestCase>, text: &str) {
+    if let Some(case) = case.as_mut() {
+        case.details.get_or_insert_with(String::new).push_str(text);
+    }
+}
+
+/// Read and unescape an attribute value.
+fn attribute(e: &BytesStart, name: &str) -> Result<Option<String>> {
+    match e.try_get_attribute(name) {
+        Ok(Some(attr)) => attr
+            .unescape_value()
+            .map(|v| Some(v.into_owned()))
+            .map_err(|err| EchidnaError::ConfigError(format!("Invalid JUnit XML: {}", err))),
+        Ok(None) => Ok(None),
+        Err(err) => Err(EchidnaError::ConfigError(format!(
+            "Invalid JUnit XML: {}",
+            err
+        ))),
+    }
+}
+
+/// Escape text for XML attributes and content.
+fn xml_escape(s: &str) -> String {
+    quick_xml::escape::escape(s).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PYTEST_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<testsuites name="pytest tests"><testsuite name="pytest" errors="0" failures="1" skipped="1" tests="3" time="0.05">
+<testcase classname="tests.test_cmd" name="test_ok" time="0.010" />
+<testcase classname="tests.test_cmd" name="test_bad" time="0.020"><failure message="assert 1 == 2">def test_bad():
+&gt;       assert 1 == 2</failure></testcase>
+<testcase classname="tests.test_cmd" name="test_skip" time="0.000"><skipped type="pytest.skip" message="needs GUI">tests/test_cmd.py:9: needs GUI</skipped></testcase>
+</testsuite></testsuites>"#;
+
+    #[test]
+    fn test_parse_pytest_junit() {
+        let suite = TestReport::parse_junit_xml(PYTEST_XML, "pytest").unwrap();
+        assert_eq!(suite.name, "pytest");
+        assert_eq!(suite.tests.len(), 3);
+
+        let counts = suite.counts();
+        assert_eq!(counts.passed, 1);
+        assert_eq!(counts.failed, 1);
+        assert_eq!(counts.skipped, 1);
+
+        let bad = &suite.tests[1];
+        assert_eq!(bad.classname, "tests.test_cmd");
+        assert_eq!(bad.message.as_deref(), Some("assert 1 == 2"));
+        assert!(bad
+            .details
+            .as_deref()
+            .unwrap()
+            .contains(">       assert 1 == 2"));
+        assert_eq!(bad.time, 0.02);
+    }
+
+    #[test]
+    fn test_parse_bare_testsuite_root() {
+        let xml = r#"<testsuite name="pytest"><testcase classname="t" name="a" time="1"><error message="boom"/></testcase></testsuite>"#;
+        let suite = TestReport::parse_junit_xml(xml, "pytest").unwrap();
+        assert_eq!(suite.tests.len(), 1);
+        assert_eq!(suite.tests[0].status, TestStatus::Error);
+        assert!(suite.counts().has_failures());
+    }
+
+    #[test]
+    fn test_parse_flaky_property() {
+        let xml = r#"<testsuites><testsuite name="pytest">
+<testcase classname="t" name="a" time="0.1"><properties><property name="echidna_flaky" value="3" /></properties></testcase>
+<testcase classname="t" name="b" time="0.1"><properties><property name="other" value="x" /></properties></testcase>
+</testsuite></testsuites>"#;
+        let suite = TestReport::parse_junit_xml(xml, "pytest").unwrap();
+        assert_eq!(suite.tests[0].status, TestStatus::Flaky);
+        assert_eq!(suite.tests[0].attempts, Some(3));
+        assert_eq!(suite.tests[1].status, TestStatus::Passed);
+
+        let counts = suite.counts();
+        assert_eq!(counts.flaky, 1);
+        assert!(!counts.has_failures());
+
+        // Flaky status survives a roundtrip through our own JUnit output
+        let report = TestReport {
+            suites: vec![suite],
+        };
+        let reparsed = TestReport::parse_junit_xml(&report.to_junit_xml(), "pytest").unwrap();
+        assert_eq!(reparsed.tests[0].status, TestStatus::Flaky);
+        assert_eq!(reparsed.tests[0].attempts, Some(3));
+    }
+
+    #[test]
+    fn test_parse_invalid_xml() {
+        assert!(TestReport::parse_junit_xml("<testsuite><testcase", "x").is_err());
+    }
+
+    #[test]
+    fn test_junit_roundtrip() {
+        let suite = TestReport::parse_junit_xml(PYTEST_XML, "pytest").unwrap();
+        let report = TestReport {
+            suites: vec![suite.clone()],
+        };
+        let xml = report.to_junit_xml();
+        assert!(xml.contains("tests=\"3\" failures=\"1\" errors=\"0\" skipped=\"1\""));
+
+        let reparsed = TestReport::parse_junit_xml(&xml, "pytest").unwrap();
+        assert_eq!(reparsed.counts(), suite.counts());
+        assert_eq!(reparsed.tests[1].message, suite.tests[1].message);
+        assert_eq!(reparsed.tests[1].details, suite.tests[1].details);
+    }
+
+    #[test]
+    fn test_merge_workspace_reports() {
+        let suite = TestReport::parse_junit_xml(PYTEST_XML, "pytest").unwrap();
+        let mut merged = TestReport::default();
+        merged.merge(
+            TestReport {
+                suites: vec![suite.clone()],
+            }
+            .prefixed("bundle-a"),
+        );
+        merged.merge(
+            TestReport {
+                suites: vec![suite],
+            }
+            .prefixed("bundle-b"),
+        );
+
+        assert_eq!(merged.suites.len(), 2);
+        assert_eq!(merged.suites[0].name, "bundle-a/pytest");
+        assert_eq!(merged.counts().total(), 6);
+        assert_eq!(merged.counts().failed, 2);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let suite = TestReport::parse_junit_xml(PYTEST_XML, "pytest").unwrap();
+        let report = TestReport {
+            suites: vec![suite],
+        };
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["summary"]["passed"], 1);
+        assert_eq!(json["summary"]["total"], 3);
+        assert_eq!(json["suites"][0]["name"], "pytest");
+        assert_eq!(json["suites"][0]["tests"][1]["status"], "failed");
+        assert!(json["suites"][0]["tests"][0].get("message").is_none());
+    }
+
+    #[test]
+    fn test_parse_report_spec() {
+        let spec: ReportSpec = "junit=out/results.xml".parse().unwrap();
+        assert_eq!(spec.format, ReportFormat::Junit);
+        assert_eq!(spec.path, PathBuf::from("out/results.xml"));
+
+        let spec: ReportSpec = "json=r.json".parse().unwrap();
+        assert_eq!(spec.format, ReportFormat::Json);
+
+        assert!("xml=r.xml".parse::<ReportSpec>().is_err());
+        assert!("junit".parse::<ReportSpec>().is_err());
+        assert!("junit=".parse::<ReportSpec>().is_err());
+    }
+
+    #[test]
+    fn test_counts_display() {
+        let counts = TestCounts {
+            passed: 3,
+            failed: 1,
+            errors: 1,
+            skipped: 2,
+            flaky: 0,
+        };
+        assert_eq!(counts.to_string(), "3 passed, 2 failed, 2 skipped");
+
+        let counts = TestCounts { flaky: 1, ..counts };
+        assert_eq!(counts.to_string(), "3 passed, 2 failed, 2 skipped, 1 flaky");
+        assert!(counts.has_failures());
+    }
+}
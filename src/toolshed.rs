@@ -0,0 +1,259 @@
+//! Toolshed upload API client used by `echidna publish`.
+//!
+//! The public ChimeraX Toolshed takes submissions through its web form only
+//! and has no token-based API. This client talks to Toolshed servers that do
+//! offer one, such as an internal mirror, with these endpoints relative to
+//! the configured base URL:
+//!
+//! - `GET /api/bundles/<name>`: `{"name": ..., "versions": [...]}`, or 404 for
+//!   a bundle that was never published
+//! - `POST /api/bundles/upload`: multipart form with a `wheel` file field, an
+//!   optional `notes` (release notes, Markdown) field, and an
+//!   `Authorization: Bearer <token>` header; answers
+//!   `{"status": ..., "message": ..., "url": ...}`
+//!
+//! There is no default server: the base URL must be configured by the user,
+//! never by a project, as the API token is sent to it.
+
+use crate::commands::version_sync::same_version;
+use crate::config::UserConfig;
+use crate::error::{EchidnaError, Result};
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Duration;
+
+/// Environment variable holding the Toolshed API token.
+pub const TOKEN_ENV: &str = "ECHIDNA_TOOLSHED_TOKEN";
+
+/// Request timeout (uploads of large wheels included).
+const TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Toolshed API URL: the given one (command line or environment), else
+/// `[toolshed].url` of the user configuration. A project's echidna.toml is
+/// not consulted, so a cloned repository cannot redirect the API token.
+pub fn resolve_url(url: Option<String>, user: &UserConfig) -> Option<String> {
+    url.filter(|url| !url.trim().is_empty())
+        .or_else(|| user.toolshed.url.clone())
+}
+
+/// API token from [`TOKEN_ENV`], else `[toolshed].token` of the user
+/// configuration.
+pub fn resolve_token(user: &UserConfig) -> Option<String> {
+    std::env::var(TOKEN_ENV)
+        .ok()
+        .filter(|token| !token.trim().is_empty())
+        .or_else(|| user.toolshed.token.clone())
+}
+
+/// Bundle information returned by the Toolshed.
+#[derive(Debug, Deserialize)]
+pub struct BundleInfo {
+    pub name: String,
+    #[serde(default)]
+    pub versions: Vec<String>,
+}
+
+/// Outcome of an upload.
+#[derive(Debug, Deserialize)]
+pub struct Submission {
+    /// e.g. "pending" (awaiting review), "published", or "rejected"
+    pub status: String,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Page of the submission
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+impl Submission {
+    /// Whether the Toolshed accepted the upload (possibly pending review).
+    pub fn is_accepted(&self) -> bool {
+        !matches!(self.status.as_str(), "rejected" | "error" | "failed")
+    }
+}
+
+/// Client for the Toolshed API.
+pub struct ToolshedClient {
+    base_url: String,
+    token: Option<String>,
+    agent: ureq::Agent,
+}
+
+impl ToolshedClient {
+    /// Create a client; uploads need a token.
+    pub fn new(base_url: &str, token: Option<String>) -> Self {
+        let agent = ureq::Agent::config_builder()
+            .http_status_as_error(false)
+            .timeout_global(Some(TIMEOUT))
+            .build()
+            .into();
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token,
+            agent,
+        }
+    }
+
+    /// Base URL without trailing slash.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Published information of a bundle, or `None` if it was never published.
+    pub fn bundle(&self, name: &str) -> Result<Option<BundleInfo>> {
+        let url = format!(
+            "{}/api/bundles/{}",
+            self.base_url,
+            urlencoding::encode(name)
+        );
+        let mut response = self
+            .agent
+            .get(&url)
+            .header("Accept", "application/json")
+            .call()
+            .map_err(|e| request_error(&url, e))?;
+        let status = response.status().as_u16();
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| request_error(&url, e))?;
+        match status {
+            404 => Ok(None),
+            200..=299 => Ok(Some(serde_json::from_str(&body)?)),
+            _ => Err(status_error(&url, status, &body)),
+        }
+    }
+
+    /// Whether a version of a bundle is already published (compared as
+    /// PEP 440 versions, so `1.2.0-rc1` matches `1.2.0rc1`).
+    pub fn version_exists(&self, name: &str, version: &str) -> Result<bool> {
+        Ok(self
+            .bundle(name)?
+            .map(|info| info.versions.iter().any(|v| same_version(v, version)))
+            .unwrap_or(false))
+    }
+
+    /// Upload a wheel, with optional release notes.
+    pub fn upload(&self, wheel: &Path, notes: Option<&str>) -> Result<Submission> {
+        let token = self.token.as_deref().ok_or_else(|| {
+            EchidnaError::Toolshed(format!(
+                "no API token; set {} or [toolshed].token in the user configuration",
+                TOKEN_ENV
+            ))
+        })?;
+        let file_name = wheel
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "bundle.whl".into());
+        let boundary = format!("echidna-{}", std::process::id());
+        let body = multipart_body(&boundary, &file_name, &std::fs::read(wheel)?, notes);
+
+        let url = format!("{}/api/bundles/upload", self.base_url);
+        let mut response = self
+            .agent
+            .post(&url)
+            .header("Authorization", &format!("Bearer {}", token))
+            .header("Accept", "application/json")
+            .header(
+                "Content-Type",
+                &format!("multipart/form-data; boundary={}", boundary),
+            )
+            .send(&body[..])
+            .map_err(|e| request_error(&url, e))?;
+        let status = response.status().as_u16();
+        let body = response
+            .body_mut()
+            .read_to_string()
+            .map_err(|e| request_error(&url, e))?;
+        match status {
+            200..=299 => Ok(serde_json::from_str(&body)?),
+            401 | 403 => Err(EchidnaError::Toolshed(format!(
+                "{} rejected the API token (HTTP {})",
+                self.base_url, status
+            ))),
+            _ => Err(status_error(&url, status, &body)),
+        }
+    }
+}
+
+/// `multipart/form-data` body with the wheel as the `wheel` field, preceded
+/// by the release notes as the `notes` field.
+fn multipart_body(boundary: &str, file_name: &str, content: &[u8], notes: Option<&str>) -> Vec<u8> {
+    let mut body = Vec::new();
+    if let Some(notes) = notes {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"notes\"\r\n\
+                 Content-Type: text/markdown; charset=utf-8\r\n\r\n{}\r\n",
+                boundary, notes
+            )
+            .as_bytes(),
+        );
+    }
+    let header = format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"wheel\"; filename=\"{}\"\r\n\
+         Content-Type: application/octet-stream\r\n\r\n",
+        boundary,
+        file_name.replace('"', "")
+    );
+    body.extend_from_slice(header.as_bytes());
+    body.extend_from_slice(content);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+fn request_error(url: &str, error: ureq::Error) -> EchidnaError {
+    EchidnaError::Toolshed(format!("request to {} failed: {}", url, error))
+}
+
+/// Error for an unexpected HTTP status, with the server's message if any.
+fn status_error(url: &str, status: u16, body: &str) -> EchidnaError {
+    let message = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| v.get("message").and_then(|m| m.as_str()).map(String::from))
+        .unwrap_or_else(|| body.trim().chars().take(200).collect());
+    EchidnaError::Toolshed(format!("{} returned HTTP {}: {}", url, status, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multipart_body() {
+        let body = multipart_body("b", "x\".whl", b"wheel", Some("- Fix"));
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "--b\r\nContent-Disposition: form-data; name=\"notes\"\r\n\
+             Content-Type: text/markdown; charset=utf-8\r\n\r\n- Fix\r\n\
+             --b\r\nContent-Disposition: form-data; name=\"wheel\"; filename=\"x.whl\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\nwheel\r\n--b--\r\n"
+        );
+    }
+
+    #[test]
+    fn test_upload_requires_token() {
+        let client = ToolshedClient::new("http://127.0.0.1:9", None);
+        let err = client
+            .upload(Path::new("x.whl"), None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(TOKEN_ENV));
+    }
+
+    #[test]
+    fn test_resolve_url() {
+        let mut user = UserConfig::default();
+        assert_eq!(resolve_url(None, &user), None);
+
+        user.toolshed.url = Some("https://user.example".into());
+        assert_eq!(
+            resolve_url(None, &user).as_deref(),
+            Some("https://user.example")
+        );
+        assert_eq!(
+            resolve_url(Some("http://localhost:8000".into()), &user).as_deref(),
+            Some("http://localhost:8000")
+        );
+    }
+}
@@ -0,0 +1,278 @@
+"""pytest plugin injected by ``echidna test``.
+
+Provides fixtures for tests that run inside ChimeraX:
+
+- ``session``: the running ChimeraX session, reset with ``close session``
+  after each test
+- ``open_structure``: open a structure file (relative paths are resolved
+  against the test file) and return the opened models
+- ``chimerax_log``: captures log messages during a test, with
+  ``assert_logged``, ``assert_not_logged``, and ``assert_no_errors``
+
+And a marker to skip tests by ChimeraX version::
+
+    @pytest.mark.chimerax_version(">=1.6")
+    def test_new_feature(session):
+        ...
+
+Options set by ``echidna test``:
+
+- ``--echidna-timeout=SECS``: fail a single test that runs longer than SECS
+  (Unix only; the ChimeraX process keeps running)
+- ``--echidna-retries=N``: rerun a failing test up to N times; a test that
+  passes on retry is reported as flaky via the ``echidna_flaky`` property
+"""
+
+import re
+import signal
+import threading
+from pathlib import Path
+
+import pytest
+
+_session = None
+
+# JUnit property marking a test that passed on retry (value: attempts needed)
+FLAKY_PROPERTY = "echidna_flaky"
+
+
+def set_session(session):
+    """Register the ChimeraX session (called by the echidna test runner)."""
+    global _session
+    _session = session
+
+
+def chimerax_version():
+    """Return the running ChimeraX version string, or None if unknown."""
+    try:
+        from chimerax.core import version
+
+        return str(version)
+    except ImportError:
+        pass
+    try:
+        from importlib.metadata import version
+
+        return version("ChimeraX-Core")
+    except Exception:
+        return None
+
+
+def pytest_addoption(parser):
+    group = parser.getgroup("echidna")
+    group.addoption(
+        "--echidna-timeout",
+        type=float,
+        default=None,
+        help="Fail a test that runs longer than this many seconds",
+    )
+    group.addoption(
+        "--echidna-retries",
+        type=int,
+        default=0,
+        help="Rerun failing tests up to this many times",
+    )
+
+
+def pytest_configure(config):
+    config.addinivalue_line(
+        "markers",
+        "chimerax_version(spec, reason=None): skip unless the ChimeraX version "
+        "matches the specifier (e.g. '>=1.6,<2')",
+    )
+    config._echidna_flaky = []
+
+
+@pytest.hookimpl(hookwrapper=True)
+def pytest_runtest_call(item):
+    timeout = item.config.getoption("echidna_timeout")
+    if (
+        not timeout
+        or not hasattr(signal, "SIGALRM")
+        or threading.current_thread() is not threading.main_thread()
+    ):
+        yield
+        return
+
+    def _timed_out(signum, frame):
+        pytest.fail(f"Test exceeded timeout of {timeout:g}s", pytrace=False)
+
+    previous = signal.signal(signal.SIGALRM, _timed_out)
+    signal.setitimer(signal.ITIMER_REAL, timeout)
+    try:
+        yield
+    finally:
+        signal.setitimer(signal.ITIMER_REAL, 0)
+        signal.signal(signal.SIGALRM, previous)
+
+
+@pytest.hookimpl(tryfirst=True)
+def pytest_runtest_protocol(item, nextitem):
+    retries = item.config.getoption("echidna_retries")
+    if not retries:
+        return None
+
+    from _pytest.runner import runtestprotocol
+
+    item.ihook.pytest_runtest_logstart(nodeid=item.nodeid, location=item.location)
+    for attempt in range(retries + 1):
+        if attempt and hasattr(item, "_initrequest"):
+            # Fresh fixture values for the retry
+            item._initrequest()
+        reports = runtestprotocol(item, nextitem=nextitem, log=False)
+        failed = any(report.failed for report in reports)
+        if not failed:
+            break
+
+    if attempt and not failed:
+        item.config._echidna_flaky.append((item.nodeid, attempt + 1))
+        for report in reports:
+            report.user_properties = list(report.user_properties) + [
+                (FLAKY_PROPERTY, str(attempt + 1))
+            ]
+
+    for report in reports:
+        item.ihook.pytest_runtest_logreport(report=report)
+    item.ihook.pytest_runtest_logfinish(nodeid=item.nodeid, location=item.location)
+    return True
+
+
+def pytest_terminal_summary(terminalreporter, config):
+    flaky = getattr(config, "_echidna_flaky", [])
+    if flaky:
+        terminalreporter.section("flaky tests (passed on retry)")
+        for nodeid, attempts in flaky:
+            terminalreporter.line(f"FLAKY {nodeid} (passed on attempt {attempts})")
+
+
+def pytest_runtest_setup(item):
+    for marker in item.iter_markers(name="chimerax_version"):
+        if not marker.args:
+            raise pytest.UsageError("chimerax_version marker requires a version specifier")
+        spec = marker.args[0]
+        current = chimerax_version()
+        if current is None:
+            pytest.skip("ChimeraX version unknown")
+
+        from packaging.specifiers import SpecifierSet
+        from packaging.version import Version
+
+        if Version(current) not in SpecifierSet(spec):
+            reason = marker.kwargs.get("reason")
+            pytest.skip(reason or f"requires ChimeraX {spec} (running {current})")
+
+
+@pytest.fixture
+def session():
+    """The running ChimeraX session, reset after each test."""
+    if _session is None:
+        pytest.fail("No ChimeraX session available (run tests with 'echidna test')")
+
+    yield _session
+
+    from chimerax.core.commands import run
+
+    run(_session, "close session", log=False)
+
+
+@pytest.fixture
+def open_structure(session, request):
+    """Open a structure file and return the opened models."""
+    base = Path(str(request.node.fspath)).parent
+
+    def _open(path, *options):
+        from chimerax.core.commands import run
+
+        path = Path(path)
+        if not path.is_absolute():
+            path = base / path
+        command = " ".join(["open", f'"{path}"', *options])
+        return run(session, command, log=False)
+
+    return _open
+
+
+class LogCapture:
+    """Log messages captured while a test runs."""
+
+    def __init__(self):
+        self.records = []
+
+    def _add(self, level, msg):
+        self.records.append((level, msg.strip()))
+
+    def _messages(self, level=None):
+        return [msg for lvl, msg in self.records if level is None or lvl == level]
+
+    @property
+    def messages(self):
+        return self._messages()
+
+    @property
+    def infos(self):
+        return self._messages("info")
+
+    @property
+    def warnings(self):
+        return self._messages("warning")
+
+    @property
+    def errors(self):
+        return self._messages("error")
+
+    @property
+    def text(self):
+        return "\n".join(self.messages)
+
+    def assert_logged(self, pattern, level=None):
+        """Assert a message containing ``pattern`` (a string or regex) was logged."""
+        messages = self._messages(level)
+        if not any(_matches(pattern, msg) for msg in messages):
+            raise AssertionError(
+                f"{pattern!r} not found in {level or 'log'} messages:\n" + "\n".join(messages)
+            )
+
+    def assert_not_logged(self, pattern, level=None):
+        """Assert no message containing ``pattern`` was logged."""
+        found = [msg for msg in self._messages(level) if _matches(pattern, msg)]
+        if found:
+            raise AssertionError(f"{pattern!r} unexpectedly logged:\n" + "\n".join(found))
+
+    def assert_no_errors(self):
+        """Assert no errors or warnings were logged."""
+        problems = self.errors + self.warnings
+        if problems:
+            raise AssertionError("errors or warnings logged:\n" + "\n".join(problems))
+
+
+def _matches(pattern, msg):
+    if isinstance(pattern, re.Pattern):
+        return pattern.search(msg) is not None
+    return pattern in msg
+
+
+@pytest.fixture
+def chimerax_log(session):
+    """Capture ChimeraX log messages for the duration of a test."""
+    from chimerax.core.logger import PlainTextLog
+
+    capture = LogCapture()
+
+    class _Log(PlainTextLog):
+        excludes_other_logs = False
+
+        def log(self, level, msg):
+            if level >= self.LEVEL_ERROR:
+                capture._add("error", msg)
+            elif level == self.LEVEL_WARNING:
+                capture._add("warning", msg)
+            else:
+                capture._add("info", msg)
+            return False
+
+    log = _Log()
+    session.logger.add_log(log)
+    try:
+        yield capture
+    finally:
+        session.logger.remove_log(log)
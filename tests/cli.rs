@@ -18,16 +18,21 @@ fn test_help() {
         .stdout(predicate::str::contains("ChimeraX Bundle Development CLI"))
         .stdout(predicate::str::contains("init"))
         .stdout(predicate::str::contains("build"))
+        .stdout(predicate::str::contains("changelog"))
+        .stdout(predicate::str::contains("index"))
         .stdout(predicate::str::contains("install"))
         .stdout(predicate::str::contains("run"))
         .stdout(predicate::str::contains("setup-ide"))
         .stdout(predicate::str::contains("clean"))
         .stdout(predicate::str::contains("validate"))
+        .stdout(predicate::str::contains("explain"))
+        .stdout(predicate::str::contains("lint"))
         .stdout(predicate::str::contains("info"))
         .stdout(predicate::str::contains("test"))
         .stdout(predicate::str::contains("watch"))
         .stdout(predicate::str::contains("version"))
         .stdout(predicate::str::contains("debug"))
+        .stdout(predicate::str::contains("release"))
         .stdout(predicate::str::contains("workspace"));
 }
 
@@ -75,6 +80,20 @@ fn test_install_help() {
         .stdout(predicate::str::contains("--user"));
 }
 
+#[test]
+fn test_install_index_requires_package() {
+    echidna()
+        .args(["install", "--index", "http://127.0.0.1:8000/"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--package <NAME>"));
+    echidna()
+        .args(["install", "--package", "ChimeraX-Test"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--index <URL>"));
+}
+
 #[test]
 fn test_run_help() {
     echidna()
@@ -86,7 +105,34 @@ fn test_run_help() {
         ))
         .stdout(predicate::str::contains("--script"))
         .stdout(predicate::str::contains("--no-build"))
-        .stdout(predicate::str::contains("--nogui"));
+        .stdout(predicate::str::contains("--nogui"))
+        .stdout(predicate::str::contains("--profile"))
+        .stdout(predicate::str::contains("--list-profiles"));
+}
+
+#[test]
+fn test_run_list_profiles() {
+    let temp = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp.path().join("echidna.toml"),
+        r#"
+[run.profiles.density]
+description = "Open a density map"
+open = ["data/map.mrc"]
+
+[run.profiles.batch]
+nogui = true
+"#,
+    )
+    .unwrap();
+
+    echidna()
+        .current_dir(temp.path())
+        .args(["run", "--list-profiles"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("density - Open a density map"))
+        .stdout(predicate::str::contains("batch [nogui]"));
 }
 
 #[test]
@@ -122,7 +168,500 @@ fn test_publish_help() {
         .stdout(predicate::str::contains(
             "Publish bundle to ChimeraX Toolshed",
         ))
-        .stdout(predicate::str::contains("--dry-run"));
+        .stdout(predicate::str::contains("--dry-run"))
+        .stdout(predicate::str::contains("--toolshed-url"))
+        .stdout(predicate::str::contains("--release-notes"));
+}
+
+/// Serve canned `(status, JSON body)` responses from a local stand-in
+/// Toolshed, one per connection; returns its URL and the received requests
+/// (head and body).
+fn toolshed_server(
+    responses: Vec<(u16, &'static str)>,
+) -> (String, std::thread::JoinHandle<Vec<String>>) {
+    use std::io::{BufRead, BufReader, Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = std::thread::spawn(move || {
+        let mut requests = Vec::new();
+        for (status, body) in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                head.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut content = vec![0; length];
+            reader.read_exact(&mut content).unwrap();
+            requests.push(head + &String::from_utf8_lossy(&content));
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+                 Connection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+        requests
+    });
+    (url, handle)
+}
+
+/// A bundle that is ready to publish.
+fn publishable_bundle() -> tempfile::TempDir {
+    let temp = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp.path().join("pyproject.toml"),
+        r#"[build-system]
+requires = ["ChimeraX-BundleBuilder"]
+build-backend = "chimerax.bundle_builder.cx_pep517"
+
+[project]
+name = "ChimeraX-Test"
+version = "0.1.0"
+description = "Test bundle"
+
+[chimerax]
+package = "chimerax.test"
+categories = ["General"]
+"#,
+    )
+    .unwrap();
+    std::fs::create_dir_all(temp.path().join("src")).unwrap();
+    std::fs::write(temp.path().join("src/__init__.py"), "bundle_api = None").unwrap();
+    std::fs::write(temp.path().join("LICENSE"), "MIT").unwrap();
+    std::fs::write(temp.path().join("README.md"), "# Test").unwrap();
+    std::fs::create_dir_all(temp.path().join("dist")).unwrap();
+    std::fs::write(
+        temp.path()
+            .join("dist/ChimeraX_Test-0.1.0-py3-none-any.whl"),
+        "wheel",
+    )
+    .unwrap();
+    temp
+}
+
+#[test]
+fn test_publish_upload() {
+    let temp = publishable_bundle();
+    std::fs::write(
+        temp.path().join("CHANGELOG.md"),
+        "# Changelog\n\n## [0.1.0] - 2026-01-01\n\n### Fixed\n\n- Bug\n",
+    )
+    .unwrap();
+    let (url, server) = toolshed_server(vec![
+        (404, r#"{"message": "not found"}"#),
+        (
+            200,
+            r#"{"status": "pending", "message": "Awaiting review", "url": "http://toolshed/s/1"}"#,
+        ),
+    ]);
+
+    echidna()
+        .args(["publish", "--release-notes", "--toolshed-url", &url])
+        .arg(temp.path())
+        .env("ECHIDNA_TOOLSHED_TOKEN", "secret")
+        .env("ECHIDNA_CONFIG", temp.path().join("no-config.toml"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "✓ Submitted ChimeraX-Test 0.1.0 (status: pending)",
+        ))
+        .stdout(predicate::str::contains("Awaiting review"));
+
+    let requests = server.join().unwrap();
+    assert!(requests[0].starts_with("GET /api/bundles/ChimeraX-Test HTTP/1.1\r\n"));
+    assert!(requests[1].starts_with("POST /api/bundles/upload HTTP/1.1\r\n"));
+    assert!(requests[1]
+        .to_ascii_lowercase()
+        .contains("authorization: bearer secret"));
+    assert!(requests[1].contains("filename=\"ChimeraX_Test-0.1.0-py3-none-any.whl\""));
+    assert!(requests[1].contains(
+        "name=\"notes\"\r\nContent-Type: text/markdown; charset=utf-8\r\n\r\n### Fixed\n\n- Bug\r\n"
+    ));
+}
+
+#[test]
+fn test_publish_rejected_token() {
+    let temp = publishable_bundle();
+    let (url, server) = toolshed_server(vec![
+        (404, r#"{"message": "not found"}"#),
+        (401, r#"{"message": "bad token"}"#),
+    ]);
+
+    echidna()
+        .args(["publish", "--toolshed-url", &url])
+        .arg(temp.path())
+        .env("ECHIDNA_TOOLSHED_TOKEN", "expired")
+        .env("ECHIDNA_CONFIG", temp.path().join("no-config.toml"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "rejected the API token (HTTP 401)",
+        ));
+    assert!(!server.join().unwrap()[1].contains("name=\"notes\""));
+}
+
+#[test]
+fn test_publish_existing_version() {
+    let temp = publishable_bundle();
+    let (url, server) = toolshed_server(vec![(
+        200,
+        r#"{"name": "ChimeraX-Test", "versions": ["0.0.9", "v0.1.0"]}"#,
+    )]);
+
+    echidna()
+        .args(["publish", "--toolshed-url", &url])
+        .arg(temp.path())
+        .env("ECHIDNA_TOOLSHED_TOKEN", "secret")
+        .env("ECHIDNA_CONFIG", temp.path().join("no-config.toml"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "ChimeraX-Test 0.1.0 is already published",
+        ));
+    assert_eq!(server.join().unwrap().len(), 1);
+}
+
+#[test]
+fn test_publish_requires_token() {
+    let temp = publishable_bundle();
+    echidna()
+        .args(["publish", "--toolshed-url", "http://127.0.0.1:9"])
+        .arg(temp.path())
+        .env_remove("ECHIDNA_TOOLSHED_TOKEN")
+        .env("ECHIDNA_CONFIG", temp.path().join("no-config.toml"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No Toolshed API token"));
+}
+
+/// Run git in a directory with a fixed identity.
+fn git(dir: &std::path::Path, args: &[&str]) {
+    let status = std::process::Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .status()
+        .unwrap();
+    assert!(status.success(), "git {:?}", args);
+}
+
+/// A publishable bundle committed to a git repository, with a CHANGELOG.
+fn releasable_bundle() -> tempfile::TempDir {
+    let temp = publishable_bundle();
+    std::fs::write(temp.path().join(".gitignore"), "dist/\n").unwrap();
+    std::fs::write(
+        temp.path().join("CHANGELOG.md"),
+        "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- Something new\n",
+    )
+    .unwrap();
+    git(temp.path(), &["init", "-q"]);
+    git(temp.path(), &["add", "."]);
+    git(temp.path(), &["commit", "-q", "-m", "Initial commit"]);
+    temp
+}
+
+#[test]
+fn test_release_dry_run() {
+    let temp = releasable_bundle();
+    echidna()
+        .args(["release", "--bump", "minor", "--dry-run"])
+        .arg(temp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Release plan for 0.2.0 (dry run)"))
+        .stdout(predicate::str::contains(
+            "bump the version 0.1.0 -> 0.2.0 in pyproject.toml",
+        ))
+        .stdout(predicate::str::contains("tag v0.2.0"));
+
+    echidna()
+        .arg("release")
+        .arg(temp.path())
+        .arg("--dry-run")
+        .env("ECHIDNA_CONFIG", temp.path().join("no-config.toml"))
+        .env_remove("ECHIDNA_TOOLSHED_URL")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Release plan for 0.1.1 (dry run)"))
+        .stdout(predicate::str::contains("stop until the wheel is uploaded"));
+
+    echidna()
+        .args(["release", "--bump", "pre", "--phase", "rc", "--dry-run"])
+        .arg(temp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Release plan for 0.1.1rc1"));
+    echidna()
+        .args(["release", "--bump", "pre", "--dry-run"])
+        .arg(temp.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'pre' needs --phase"));
+
+    echidna()
+        .args(["release", "--dry-run", "--from", "build", "--no-publish"])
+        .current_dir(temp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Release plan for 0.1.0"))
+        .stdout(predicate::str::contains("version").not())
+        .stdout(predicate::str::contains("publish").not());
+}
+
+#[test]
+fn test_release_refuses_dirty_tree() {
+    let temp = releasable_bundle();
+    std::fs::write(temp.path().join("README.md"), "# Changed").unwrap();
+    echidna()
+        .args(["release", "--dry-run", "--bump", "patch"])
+        .arg(temp.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("uncommitted changes (README.md)"));
+}
+
+#[test]
+fn test_release_stops_at_failed_step() {
+    let temp = releasable_bundle();
+    echidna()
+        .args(["release", "--from", "changelog", "--no-publish"])
+        .current_dir(temp.path())
+        .arg("--chimerax")
+        .arg(temp.path().join("no-chimerax"))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Release stopped at step 'build'"))
+        .stdout(predicate::str::contains("echidna release --from build"));
+    // The changelog step ran before the build failed
+    let changelog = std::fs::read_to_string(temp.path().join("CHANGELOG.md")).unwrap();
+    assert!(changelog.contains("## [0.1.0] - "));
+}
+
+/// Add a file and commit it.
+fn commit_file(dir: &std::path::Path, file: &str, content: &str, message: &str) {
+    std::fs::write(dir.join(file), content).unwrap();
+    git(dir, &["add", file]);
+    git(dir, &["commit", "-q", "-m", message]);
+}
+
+#[test]
+fn test_changelog_from_commits() {
+    let temp = releasable_bundle();
+    let dir = temp.path();
+    git(dir, &["tag", "v0.1.0"]);
+    commit_file(dir, "src/measure.py", "", "feat: add measure command");
+    commit_file(dir, "README.md", "# Test\n", "docs: fix typo");
+    commit_file(
+        dir,
+        "src/tool.py",
+        "",
+        "fix(tool): keep selection on refresh",
+    );
+    let pyproject = std::fs::read_to_string(dir.join("pyproject.toml")).unwrap();
+    commit_file(
+        dir,
+        "pyproject.toml",
+        &pyproject.replace("0.1.0", "0.2.0"),
+        "Bump version",
+    );
+
+    echidna()
+        .args(["changelog", "--dry-run"])
+        .arg(dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("## [0.2.0] - "))
+        .stdout(predicate::str::contains(
+            "### Added\n\n- Add measure command\n",
+        ))
+        .stdout(predicate::str::contains(
+            "### Fixed\n\n- **tool:** Keep selection on refresh\n",
+        ))
+        .stdout(predicate::str::contains("typo").not());
+
+    echidna()
+        .args([
+            "release",
+            "--dry-run",
+            "--generate-changelog",
+            "--from",
+            "changelog",
+        ])
+        .current_dir(dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "write [0.2.0] to CHANGELOG.md from the commits since v0.1.0",
+        ));
+
+    echidna()
+        .arg("changelog")
+        .arg(dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "✓ Added [0.2.0] to CHANGELOG.md from the commits since v0.1.0",
+        ));
+    let changelog = std::fs::read_to_string(dir.join("CHANGELOG.md")).unwrap();
+    assert!(changelog.contains("- Something new\n\n## [0.2.0] - "));
+
+    echidna()
+        .arg("changelog")
+        .arg(dir)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "CHANGELOG.md already has a section for 0.2.0",
+        ));
+}
+
+#[test]
+fn test_publish_release_notes_require_section() {
+    let temp = publishable_bundle();
+    echidna()
+        .args([
+            "publish",
+            "--release-notes",
+            "--toolshed-url",
+            "http://127.0.0.1:9",
+        ])
+        .arg(temp.path())
+        .env("ECHIDNA_TOOLSHED_TOKEN", "secret")
+        .env("ECHIDNA_CONFIG", temp.path().join("no-config.toml"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "CHANGELOG.md has no entries for 0.1.0 to use as release notes",
+        ));
+}
+
+/// Send a GET request and return the raw response.
+fn http_get(address: &str, path: &str) -> String {
+    use std::io::{Read, Write};
+
+    let mut stream = std::net::TcpStream::connect(address).unwrap();
+    write!(stream, "GET {} HTTP/1.1\r\nHost: {}\r\n\r\n", path, address).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn test_index_build_and_serve() {
+    use std::io::{BufRead, BufReader};
+
+    let workspace = tempfile::TempDir::new().unwrap();
+    let bundle = publishable_bundle();
+    let member = workspace.path().join("test-bundle");
+    std::fs::create_dir_all(member.join("dist")).unwrap();
+    std::fs::copy(
+        bundle.path().join("pyproject.toml"),
+        member.join("pyproject.toml"),
+    )
+    .unwrap();
+    std::fs::write(
+        member.join("dist/ChimeraX_Test-0.1.0-py3-none-any.whl"),
+        "wheel",
+    )
+    .unwrap();
+    std::fs::create_dir_all(workspace.path().join("unbuilt")).unwrap();
+    std::fs::write(workspace.path().join("unbuilt/pyproject.toml"), "").unwrap();
+    std::fs::write(
+        workspace.path().join("workspace.toml"),
+        "[workspace]\nmembers = [\"test-bundle\", \"unbuilt\"]\n",
+    )
+    .unwrap();
+    let index = workspace.path().join("index");
+
+    echidna()
+        .args(["index", "build"])
+        .arg(&index)
+        .arg("--path")
+        .arg(workspace.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("unbuilt: no wheels in dist/"))
+        .stdout(predicate::str::contains(
+            "✓ Indexed 1 wheel(s) of 1 bundle(s)",
+        ));
+    assert!(index
+        .join("chimerax-test/ChimeraX_Test-0.1.0-py3-none-any.whl")
+        .exists());
+
+    let mut server = std::process::Command::new(assert_cmd::cargo::cargo_bin!("echidna"))
+        .args(["index", "serve", "--port", "0"])
+        .arg(&index)
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Keep the pipe open while the server prints
+    let mut stdout = BufReader::new(server.stdout.take().unwrap());
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    let address = line
+        .trim()
+        .rsplit("http://")
+        .next()
+        .unwrap()
+        .trim_end_matches('/')
+        .to_string();
+
+    let root = http_get(&address, "/");
+    let page = http_get(&address, "/chimerax-test/");
+    let redirect = http_get(&address, "/chimerax-test");
+    let wheel = http_get(
+        &address,
+        "/chimerax-test/ChimeraX_Test-0.1.0-py3-none-any.whl",
+    );
+    let outside = http_get(&address, "/../pyproject.toml");
+    let oversized = http_get(&address, &format!("/{}", "x".repeat(10_000)));
+    server.kill().unwrap();
+    server.wait().unwrap();
+
+    assert!(root.contains("<a href=\"chimerax-test/\">chimerax-test</a>"));
+    assert!(page.starts_with("HTTP/1.1 200 OK"));
+    assert!(page.contains("ChimeraX_Test-0.1.0-py3-none-any.whl#sha256="));
+    assert!(redirect.contains("Location: /chimerax-test/"));
+    assert!(wheel.ends_with("\r\n\r\nwheel"));
+    assert!(outside.starts_with("HTTP/1.1 404"));
+    assert!(oversized.starts_with("HTTP/1.1 431"));
+}
+
+#[test]
+fn test_snapshot_help() {
+    echidna()
+        .args(["snapshot", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Render images headlessly with offscreen ChimeraX",
+        ))
+        .stdout(predicate::str::contains("--out"))
+        .stdout(predicate::str::contains("--size"))
+        .stdout(predicate::str::contains("--no-build"));
+}
+
+#[test]
+fn test_snapshot_rejects_invalid_size() {
+    echidna()
+        .args(["snapshot", "a.cxc", "--out", "a.png", "--size", "big"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid size"));
 }
 
 #[test]
@@ -231,7 +770,187 @@ fn test_validate_help() {
         .stdout(predicate::str::contains(
             "Validate bundle structure and configuration",
         ))
-        .stdout(predicate::str::contains("--strict"));
+        .stdout(predicate::str::contains("--strict"))
+        .stdout(predicate::str::contains("--format"))
+        .stdout(predicate::str::contains("sarif"))
+        .stdout(predicate::str::contains("--session"))
+        .stdout(predicate::str::contains("--check-imports"));
+}
+
+#[test]
+fn test_validate_session_requires_chimerax() {
+    let temp = tempfile::TempDir::new().unwrap();
+    echidna()
+        .args([
+            "--chimerax",
+            "/nonexistent/ChimeraX",
+            "validate",
+            "--session",
+        ])
+        .arg(temp.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("ChimeraX not found"));
+}
+
+#[test]
+fn test_validate_json_output() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let output = echidna()
+        .args(["validate", "--format", "json"])
+        .arg(temp.path())
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(json["valid"], false);
+    assert_eq!(json["diagnostics"][0]["code"], "CX001");
+}
+
+const UNFIXED_PYPROJECT: &str = r#"[build-system]
+requires = ["ChimeraX-BundleBuilder"]
+
+[project]
+name = "my-tool" # keep this comment
+version = "0.1.0"
+"#;
+
+#[test]
+fn test_validate_fix_dry_run() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let pyproject = temp.path().join("pyproject.toml");
+    std::fs::write(&pyproject, UNFIXED_PYPROJECT).unwrap();
+
+    echidna()
+        .args(["validate", "--fix", "--dry-run"])
+        .arg(temp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "+build-backend = \"chimerax.bundle_builder.cx_pep517\"",
+        ))
+        .stdout(predicate::str::contains("--unsafe-fixes"));
+
+    assert_eq!(
+        std::fs::read_to_string(&pyproject).unwrap(),
+        UNFIXED_PYPROJECT
+    );
+}
+
+#[test]
+fn test_validate_fix() {
+    let temp = tempfile::TempDir::new().unwrap();
+    let pyproject = temp.path().join("pyproject.toml");
+    std::fs::write(&pyproject, UNFIXED_PYPROJECT).unwrap();
+
+    echidna()
+        .args(["validate", "--fix"])
+        .arg(temp.path())
+        .assert()
+        .stdout(predicate::str::contains("Fixed [CX004]"));
+
+    let fixed = std::fs::read_to_string(&pyproject).unwrap();
+    assert!(fixed.contains("build-backend = \"chimerax.bundle_builder.cx_pep517\""));
+    assert!(fixed.contains("name = \"my-tool\" # keep this comment"));
+
+    echidna()
+        .args(["validate", "--fix", "--unsafe-fixes"])
+        .arg(temp.path())
+        .assert()
+        .stdout(predicate::str::contains("Fixed [CX012]"));
+
+    let fixed = std::fs::read_to_string(&pyproject).unwrap();
+    assert!(fixed.contains("name = \"ChimeraX-my-tool\" # keep this comment"));
+}
+
+#[test]
+fn test_validate_unsafe_fixes_requires_fix() {
+    echidna()
+        .args(["validate", "--unsafe-fixes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--fix"));
+}
+
+#[test]
+fn test_publish_format_requires_dry_run() {
+    echidna()
+        .args(["publish", "--format", "json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--dry-run"));
+}
+
+#[test]
+fn test_lint_api() {
+    let temp = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp.path().join("pyproject.toml"),
+        "[chimerax]\nmin-chimerax-version = \"1.0\"\n",
+    )
+    .unwrap();
+    std::fs::create_dir_all(temp.path().join("src")).unwrap();
+    std::fs::write(
+        temp.path().join("src/__init__.py"),
+        "from PyQt5.QtWidgets import QWidget\n",
+    )
+    .unwrap();
+
+    echidna()
+        .args(["lint", "--target", "1.3"])
+        .arg(temp.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No deprecated or removed"));
+
+    echidna()
+        .arg("lint")
+        .arg(temp.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("error[CX070]"))
+        .stdout(predicate::str::contains("src/__init__.py:1:1"));
+}
+
+#[test]
+fn test_lint_requires_bundle() {
+    let temp = tempfile::TempDir::new().unwrap();
+    echidna()
+        .arg("lint")
+        .arg(temp.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing pyproject.toml"));
+}
+
+#[test]
+fn test_explain_code() {
+    echidna()
+        .args(["explain", "CX001"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("CX001 (missing-pyproject"));
+}
+
+#[test]
+fn test_explain_lists_codes() {
+    echidna()
+        .arg("explain")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("CX030"))
+        .stdout(predicate::str::contains("unknown-key"));
+}
+
+#[test]
+fn test_explain_unknown_code() {
+    echidna()
+        .args(["explain", "CX999"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown diagnostic code"));
 }
 
 #[test]
@@ -257,7 +976,26 @@ fn test_test_help() {
         .stdout(predicate::str::contains("--filter"))
         .stdout(predicate::str::contains("--no-build"))
         .stdout(predicate::str::contains("--no-install"))
-        .stdout(predicate::str::contains("--coverage"));
+        .stdout(predicate::str::contains("--coverage"))
+        .stdout(predicate::str::contains("--cov-fail-under"))
+        .stdout(predicate::str::contains("--cov-xml"))
+        .stdout(predicate::str::contains("--cov-lcov"))
+        .stdout(predicate::str::contains("--cov-branch"))
+        .stdout(predicate::str::contains("--test-timeout"))
+        .stdout(predicate::str::contains("--retries"))
+        .stdout(predicate::str::contains("--scripts"))
+        .stdout(predicate::str::contains("--visual"))
+        .stdout(predicate::str::contains("--update-baselines"))
+        .stdout(predicate::str::contains("--report"));
+}
+
+#[test]
+fn test_test_rejects_unknown_report_format() {
+    echidna()
+        .args(["test", "--report", "html=out.html"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown report format"));
 }
 
 #[test]
@@ -270,7 +1008,17 @@ fn test_watch_help() {
             "Watch for changes and auto-rebuild",
         ))
         .stdout(predicate::str::contains("--run"))
-        .stdout(predicate::str::contains("--test"));
+        .stdout(predicate::str::contains("--test"))
+        .stdout(predicate::str::contains("--full"));
+}
+
+#[test]
+fn test_watch_full_requires_test() {
+    echidna()
+        .args(["watch", "--full"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--test"));
 }
 
 #[test]
@@ -284,6 +1032,108 @@ fn test_version_help() {
         ));
 }
 
+#[test]
+fn test_version_pep440_actions() {
+    let temp = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp.path().join("pyproject.toml"),
+        "[project]\nname = \"test\"\nversion = \"1.2.0\"\n",
+    )
+    .unwrap();
+
+    for (action, expected) in [
+        (vec!["pre", "rc"], "1.2.0 -> 1.2.1rc1"),
+        (vec!["dev"], "1.2.1rc1 -> 1.2.1rc2.dev1"),
+        (vec!["release"], "1.2.1rc2.dev1 -> 1.2.1"),
+        (vec!["post"], "1.2.1 -> 1.2.1.post1"),
+        (vec!["2.0.0-RC.1"], "1.2.1.post1 -> 2.0.0rc1"),
+    ] {
+        echidna()
+            .arg("version")
+            .arg(temp.path())
+            .args(&action)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(expected));
+    }
+
+    echidna()
+        .arg("version")
+        .arg(temp.path())
+        .arg("pre")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'pre' needs a phase"));
+}
+
+#[test]
+fn test_version_files_sync_and_check() {
+    let temp = tempfile::TempDir::new().unwrap();
+    std::fs::write(
+        temp.path().join("pyproject.toml"),
+        "[project]\nname = \"test\"\nversion = \"1.2.0\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        temp.path().join("echidna.toml"),
+        r#"
+[[version.files]]
+path = "src/__init__.py"
+pattern = '__version__ = "(.+)"'
+"#,
+    )
+    .unwrap();
+    std::fs::create_dir_all(temp.path().join("src")).unwrap();
+    std::fs::write(
+        temp.path().join("src/__init__.py"),
+        "__version__ = \"1.2.0\"\n",
+    )
+    .unwrap();
+
+    echidna()
+        .arg("version")
+        .arg(temp.path())
+        .arg("minor")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1.2.0 -> 1.3.0"))
+        .stdout(predicate::str::contains("updated src/__init__.py"));
+    echidna()
+        .arg("version")
+        .arg(temp.path())
+        .arg("check")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "2 version location(s) agree on 1.3.0",
+        ));
+
+    std::fs::write(
+        temp.path().join("src/__init__.py"),
+        "__version__ = \"1.1.0\"\n",
+    )
+    .unwrap();
+    echidna()
+        .arg("version")
+        .arg(temp.path())
+        .arg("check")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("✗ src/__init__.py:1"))
+        .stderr(predicate::str::contains(
+            "1 version location(s) disagree with pyproject.toml (1.3.0)",
+        ));
+    echidna()
+        .arg("version")
+        .arg(temp.path())
+        .arg("patch")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "src/__init__.py:1 has version 1.1.0",
+        ));
+}
+
 #[test]
 fn test_debug_help() {
     echidna()